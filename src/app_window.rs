@@ -1,12 +1,20 @@
 use crate::{
-    graph::graph_editor_egui::editor_state::EditorState,
+    graph::graph_editor_egui::editor_state::{EditorState, OrbitCamera},
+    graph::poly_asm::PolyAsmProgram,
     mesh::debug_viz::{self, DebugMeshes},
-    prelude::graph::NodeId,
+    prelude::graph::{GraphGeneration, NodeId},
     prelude::*,
+    rendergraph::{
+        tonemap_pass::{TonemapOperator, Tonemapping},
+        normals_pass::NormalsSettings,
+        points_pass::PointsSettings,
+        wireframe_pass::{WireframeMode, WireframeSettings},
+    },
 };
 use std::time::{Instant, Duration};
 
 use egui_winit_platform::Platform;
+use rend3::types::SampleCount;
 use winit::{
     dpi::PhysicalSize,
     event::{Event, MouseButton, WindowEvent},
@@ -15,28 +23,15 @@ use winit::{
 };
 
 pub mod default_scene;
+pub mod evaluation;
 pub mod gui_overlay;
 pub mod input;
 
 use crate::render_context::RenderContext;
 
+use self::evaluation::Evaluation;
 use self::input::InputSystem;
 
-struct OrbitCamera {
-    yaw: f32,
-    pitch: f32,
-    distance: f32,
-}
-impl Default for OrbitCamera {
-    fn default() -> Self {
-        Self {
-            yaw: -30.0,
-            pitch: 30.0,
-            distance: 8.0,
-        }
-    }
-}
-
 pub struct AppWindow {
     window: Window,
     scale_factor: f32,
@@ -48,10 +43,17 @@ pub struct AppWindow {
 pub struct AppState {
     window_size: Vec2,
     input_system: InputSystem,
-    orbit_camera: OrbitCamera,
     debug_meshes: Option<DebugMeshes>,
     mesh: Option<HalfEdgeMesh>,
     editor_state: EditorState,
+    /// The evaluation of the active node, while it's running.
+    evaluation: Option<Evaluation>,
+    /// The generation of the graph, active node and element limit that were
+    /// last evaluated, so the graph is only evaluated again when one of them
+    /// changes.
+    evaluated_inputs: Option<(GraphGeneration, Option<NodeId>, usize)>,
+    /// The error of the last evaluation, if it failed.
+    evaluation_error: Option<String>,
 }
 
 impl AppWindow {
@@ -89,10 +91,12 @@ impl AppWindow {
             state: AppState {
                 window_size: Vec2::new(window_size.width as f32, window_size.height as f32),
                 input_system: InputSystem::default(),
-                orbit_camera: OrbitCamera::default(),
                 debug_meshes: None,
                 mesh: None,
                 editor_state,
+                evaluation: None,
+                evaluated_inputs: None,
+                evaluation_error: None,
             },
             event_loop,
             window,
@@ -128,11 +132,7 @@ impl AppWindow {
         }
         camera.distance += input.mouse.wheel_delta();
 
-        // Compute view matrix
-        let view = Mat4::from_translation(Vec3::Z * camera.distance)
-            * Mat4::from_rotation_x(-camera.pitch.to_radians())
-            * Mat4::from_rotation_y(-camera.yaw.to_radians());
-        render_ctx.set_camera(view);
+        render_ctx.set_camera(camera.view_matrix());
     }
 
     fn compile_and_run_side_effect(state: &mut AppState, node: NodeId) -> Result<()> {
@@ -141,22 +141,225 @@ impl AppWindow {
         Ok(())
     }
 
-    fn compile_and_execute_program(
-        state: &mut AppState,
-        render_ctx: &mut RenderContext,
-    ) -> Result<()> {
+    fn compile_program(state: &AppState) -> Result<PolyAsmProgram> {
         let active = state
             .editor_state
             .active_node
             .ok_or(anyhow!("No active node"))?;
-        let program =
+        let mut program =
             crate::graph::graph_compiler::compile_graph(&state.editor_state.graph, active)?;
-        let mesh = program.execute()?;
-        let r3mesh = default_scene::build_mesh(&mesh);
-        debug_viz::add_halfedge_debug(render_ctx, &mut state.debug_meshes.as_mut().unwrap(), &mesh);
-        state.mesh = Some(mesh);
-        render_ctx.add_mesh_as_object(r3mesh);
-        Ok(())
+        // Debug marks are only useful while developing new operations
+        program.set_debug_marks(cfg!(debug_assertions));
+        Ok(program)
+    }
+
+    /// Starts evaluating the active node in the background whenever the
+    /// graph changes, and collects the result once it's ready.
+    ///
+    /// NOTE: The evaluation is not restarted when it gets cancelled, only
+    /// when the graph is edited again.
+    fn update_evaluation(state: &mut AppState) {
+        let inputs = Some((
+            state.editor_state.graph.generation(),
+            state.editor_state.active_node,
+            halfedge::limits::max_elements(),
+        ));
+        if inputs != state.evaluated_inputs {
+            state.evaluated_inputs = inputs;
+            // Replacing a running evaluation cancels it
+            state.evaluation = None;
+            match Self::compile_program(state) {
+                Ok(program) => state.evaluation = Some(Evaluation::start(program)),
+                Err(err) => state.evaluation_error = Some(err.to_string()),
+            }
+        }
+
+        if let Some(result) = state.evaluation.as_ref().and_then(|e| e.try_finish()) {
+            state.evaluation = None;
+            match result {
+                Ok(mesh) => {
+                    state.mesh = Some(mesh);
+                    state.evaluation_error = None;
+                    state.editor_state.baked_mesh = None;
+                }
+                Err(err) => state.evaluation_error = Some(err.to_string()),
+            }
+        }
+    }
+
+    /// Adds the evaluated mesh to the scene. Files saved with a baked mesh
+    /// display it until the graph is successfully evaluated.
+    fn draw_mesh(state: &mut AppState, render_ctx: &mut RenderContext) {
+        if let Some(baked_mesh) = state.editor_state.baked_mesh.as_ref() {
+            render_ctx.add_mesh_as_object(default_scene::build_mesh(baked_mesh));
+            render_ctx.set_overlay_mesh(baked_mesh);
+            return;
+        }
+        // The last mesh stays on screen while the graph is evaluated again
+        let mesh = match state.mesh.as_ref() {
+            Some(mesh) if state.evaluation_error.is_none() => mesh,
+            _ => return,
+        };
+        render_ctx.set_overlay_mesh(mesh);
+        // Meshes without faces, like polylines, only show up in the wireframe
+        if mesh.iter_faces().next().is_some() {
+            render_ctx.add_mesh_as_object(default_scene::build_mesh(mesh));
+        }
+        debug_viz::add_halfedge_debug(render_ctx, state.debug_meshes.as_mut().unwrap(), mesh);
+    }
+
+    /// Shows the progress of the running evaluation, with a button to cancel
+    /// it. Quick evaluations finish before the window shows up.
+    fn draw_evaluation_progress(ctx: &egui::CtxRef, evaluation: &Evaluation) {
+        if evaluation.elapsed() < 0.25 {
+            return;
+        }
+        egui::Window::new("Evaluating")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.add(egui::ProgressBar::new(evaluation.progress()).show_percentage());
+                if ui.button("Cancel").clicked() {
+                    evaluation.cancel();
+                }
+            });
+        // Keep repainting, so the progress bar moves
+        ctx.request_repaint();
+    }
+
+    /// Shows a small panel with information about the active node's output.
+    fn draw_mesh_stats(ctx: &egui::CtxRef, stats: &MeshStats) {
+        egui::Window::new("Mesh info").show(ctx, |ui| {
+            ui.label(format!("Vertices: {}", stats.num_vertices));
+            ui.label(format!("Edges: {}", stats.num_edges));
+            ui.label(format!("Faces: {}", stats.num_faces));
+            ui.label(format!("Closed: {}", stats.is_closed));
+            ui.label(format!("Manifold: {}", stats.is_manifold));
+            let size = stats.bbox.1 - stats.bbox.0;
+            ui.label(format!(
+                "Size: {:.3} x {:.3} x {:.3}",
+                size.x, size.y, size.z
+            ));
+        });
+    }
+
+    /// Shows a small panel to choose how the mesh is drawn in the viewport.
+    fn draw_viewport_settings(
+        ctx: &egui::CtxRef,
+        settings: &mut WireframeSettings,
+        normals: &mut NormalsSettings,
+        face_orientation: &mut bool,
+        points: &mut PointsSettings,
+        tonemapping: &mut Tonemapping,
+        samples: &mut SampleCount,
+    ) {
+        egui::Window::new("Viewport").show(ctx, |ui| {
+            egui::ComboBox::from_label("Edges")
+                .selected_text(match settings.mode {
+                    WireframeMode::Disabled => "Hidden",
+                    WireframeMode::Wireframe => "Wireframe",
+                    WireframeMode::WireOnShaded => "Wire on shaded",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut settings.mode, WireframeMode::Disabled, "Hidden");
+                    ui.selectable_value(&mut settings.mode, WireframeMode::Wireframe, "Wireframe");
+                    ui.selectable_value(
+                        &mut settings.mode,
+                        WireframeMode::WireOnShaded,
+                        "Wire on shaded",
+                    );
+                });
+            ui.horizontal(|ui| {
+                ui.label("Edge color");
+                let mut color = settings.color.to_array();
+                if ui.color_edit_button_rgb(&mut color).changed() {
+                    settings.color = Vec3::from(color);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Edge opacity");
+                ui.add(egui::Slider::new(&mut settings.opacity, 0.0..=1.0));
+            });
+            ui.separator();
+            ui.checkbox(face_orientation, "Face orientation")
+                .on_hover_text("Shows the front of faces in blue and the back in red");
+            ui.separator();
+            ui.checkbox(&mut points.enabled, "Vertices");
+            ui.horizontal(|ui| {
+                ui.label("Vertex size");
+                ui.add(egui::Slider::new(&mut points.size, 1.0..=20.0));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Vertex color");
+                let mut color = points.color.to_array();
+                if ui.color_edit_button_rgb(&mut color).changed() {
+                    points.color = Vec3::from(color);
+                }
+            });
+            ui.separator();
+            ui.checkbox(&mut normals.enabled, "Vertex normals");
+            ui.horizontal(|ui| {
+                ui.label("Normal length");
+                ui.add(egui::Slider::new(&mut normals.length, 0.01..=1.0).logarithmic(true));
+            });
+            ui.horizontal(|ui| {
+                ui.label("Normal color");
+                let mut color = normals.color.to_array();
+                if ui.color_edit_button_rgb(&mut color).changed() {
+                    normals.color = Vec3::from(color);
+                }
+            });
+            ui.separator();
+            egui::ComboBox::from_label("Tonemapping")
+                .selected_text(format!("{:?}", tonemapping.operator))
+                .show_ui(ui, |ui| {
+                    for operator in [
+                        TonemapOperator::Linear,
+                        TonemapOperator::Reinhard,
+                        TonemapOperator::Aces,
+                    ] {
+                        ui.selectable_value(
+                            &mut tonemapping.operator,
+                            operator,
+                            format!("{:?}", operator),
+                        );
+                    }
+                });
+            ui.horizontal(|ui| {
+                ui.label("Exposure");
+                ui.add(egui::Slider::new(&mut tonemapping.exposure, -5.0..=5.0));
+            });
+            ui.separator();
+            egui::ComboBox::from_label("Antialiasing")
+                .selected_text(format!("{}x MSAA", *samples as u32))
+                .show_ui(ui, |ui| {
+                    for sample_count in [SampleCount::One, SampleCount::Four] {
+                        ui.selectable_value(
+                            samples,
+                            sample_count,
+                            format!("{}x MSAA", sample_count as u32),
+                        );
+                    }
+                });
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Max elements");
+                let mut max_elements = halfedge::limits::max_elements();
+                let response = ui
+                    .add(
+                        egui::DragValue::new(&mut max_elements)
+                            .speed(1000.0)
+                            .clamp_range(1..=usize::MAX),
+                    )
+                    .on_hover_text(
+                        "The maximum number of vertices, edges or faces that operations \
+                         like subdivision or arrays are allowed to produce",
+                    );
+                if response.changed() {
+                    halfedge::limits::set_max_elements(max_elements);
+                }
+            });
+        });
     }
 
     fn on_main_events_cleared(
@@ -169,7 +372,7 @@ impl AppWindow {
 
         Self::update_camera(
             &mut state.input_system,
-            &mut state.orbit_camera,
+            &mut state.editor_state.camera,
             render_ctx,
             state.window_size,
         );
@@ -189,16 +392,36 @@ impl AppWindow {
         crate::graph::graph_editor_egui::draw_app(
             &egui_platform.context(),
             &mut state.editor_state,
+            state.mesh.as_ref(),
         );
 
+        if let Some(mesh) = state.mesh.as_ref() {
+            Self::draw_mesh_stats(&egui_platform.context(), &mesh.stats());
+        }
+        let mut samples = render_ctx.samples();
+        Self::draw_viewport_settings(
+            &egui_platform.context(),
+            &mut render_ctx.wireframe_routine.settings,
+            &mut render_ctx.normals_routine.settings,
+            &mut render_ctx.face_orientation_routine.enabled,
+            &mut render_ctx.points_routine.settings,
+            &mut render_ctx.tonemapping,
+            &mut samples,
+        );
+        render_ctx.set_samples(samples);
+
         if let Some(side_effect) = state.editor_state.run_side_effect.take() {
             Self::compile_and_run_side_effect(state, side_effect)
                 .unwrap_or_else(|err| println!("Error when executing node: {}", err));
         }
 
-        let execution_result = Self::compile_and_execute_program(state, render_ctx);
+        Self::update_evaluation(state);
+        Self::draw_mesh(state, render_ctx);
+        if let Some(evaluation) = state.evaluation.as_ref() {
+            Self::draw_evaluation_progress(&egui_platform.context(), evaluation);
+        }
 
-        if let Err(err) = execution_result {
+        if let Some(err) = &state.evaluation_error {
             let painter = egui_platform.context().debug_painter();
             let width = egui_platform.context().available_rect().width();
             painter.text(
@@ -210,6 +433,20 @@ impl AppWindow {
             );
         }
 
+        // Files saved without a camera are framed after their first evaluation
+        if state.evaluation.is_none() && std::mem::take(&mut state.editor_state.frame_all_op) {
+            let mesh = if state.evaluation_error.is_none() {
+                state.mesh.as_ref()
+            } else {
+                state.editor_state.baked_mesh.as_ref()
+            };
+            let bbox = mesh.and_then(|mesh| mesh.bounding_box());
+            state.editor_state.camera = OrbitCamera::default();
+            if let Some(bbox) = bbox {
+                state.editor_state.camera.frame_all(bbox);
+            }
+        }
+
         render_ctx.render_frame(Some(egui_platform));
 
         // Sleep for the remaining time to cap at 60Hz
@@ -20,6 +20,7 @@ fn load_obj(path: &str) -> halfedge::HalfEdgeMesh {
 }
 
 fn debug_vertex_ids(mesh: &mut halfedge::HalfEdgeMesh) {
+    mesh.set_debug_marks_enabled(true);
     let vs = mesh.iter_vertices().map(|x| x.0).collect::<Vec<_>>();
     for v in vs {
         mesh.add_debug_vertex(
@@ -30,6 +31,7 @@ fn debug_vertex_ids(mesh: &mut halfedge::HalfEdgeMesh) {
 }
 
 fn debug_halfedge_ids(mesh: &mut halfedge::HalfEdgeMesh) {
+    mesh.set_debug_marks_enabled(true);
     let es = mesh.iter_halfedges().map(|x| x.0).collect::<Vec<_>>();
     for h in es {
         mesh.add_debug_halfedge(h, DebugMark::blue(h.idx().to_string().as_str()));
@@ -195,10 +197,33 @@ fn make_halfedge_mesh() -> halfedge::HalfEdgeMesh {
 
 pub fn build_mesh(mesh: &halfedge::HalfEdgeMesh) -> r3::Mesh {
     let (positions, indices) = mesh.generate_buffers();
-    r3::MeshBuilder::new(positions)
-        .with_indices(indices)
-        .build()
-        .unwrap()
+
+    // The UVs and tangents, in the same per-corner layout as `generate_buffers`.
+    // Tangents are meaningless without UVs, so they are skipped in that case.
+    let tangents = mesh.has_uvs().then(|| mesh.compute_tangents());
+    let mut vertex_uvs = vec![];
+    let mut vertex_tangents = vec![];
+    for (face_id, _) in mesh.iter_faces() {
+        let halfedges = mesh.face_edges(face_id);
+        let h1 = halfedges[0];
+        for (&h2, &h3) in halfedges[1..].iter().tuple_windows() {
+            for h in [h1, h2, h3] {
+                vertex_uvs.push(mesh.halfedge_uv(h).unwrap_or(Vec2::ZERO));
+                if let Some(tangents) = &tangents {
+                    let v = mesh.at_halfedge(h).vertex().end();
+                    // NOTE: rend3 reconstructs the bitangent from the normal and
+                    // the tangent, so the handedness in w can't be passed along.
+                    vertex_tangents.push(tangents[&v].truncate());
+                }
+            }
+        }
+    }
+
+    let mut builder = r3::MeshBuilder::new(positions).with_vertex_uv0(vertex_uvs);
+    if tangents.is_some() {
+        builder = builder.with_vertex_tangents(vertex_tangents);
+    }
+    builder.with_indices(indices).build().unwrap()
 }
 
 pub fn add_default_scene(render_ctx: &mut RenderContext, debug_meshes: &mut DebugMeshes) {
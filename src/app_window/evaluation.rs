@@ -0,0 +1,81 @@
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::graph::poly_asm::PolyAsmProgram;
+use crate::prelude::*;
+
+/// A program being executed in a background thread, so that expensive
+/// operations don't freeze the UI. Dropping the evaluation cancels it.
+pub struct Evaluation {
+    /// The progress of the program, as the bits of an `f32`.
+    progress: Arc<AtomicU32>,
+    cancel: Arc<AtomicBool>,
+    result: Receiver<Result<HalfEdgeMesh>>,
+    started: Instant,
+}
+
+impl Evaluation {
+    pub fn start(program: PolyAsmProgram) -> Self {
+        let progress = Arc::new(AtomicU32::new(0.0f32.to_bits()));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (sender, result) = mpsc::channel();
+
+        let (thread_progress, thread_cancel) = (progress.clone(), cancel.clone());
+        std::thread::spawn(move || {
+            let report = |fraction: f32| {
+                thread_progress.store(fraction.to_bits(), Ordering::Relaxed);
+                if thread_cancel.load(Ordering::Relaxed) {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            };
+            // The receiver is gone when the evaluation was dropped, and
+            // nobody is interested in the result anymore.
+            let _ = sender.send(program.execute_with_progress(&report));
+        });
+
+        Self {
+            progress,
+            cancel,
+            result,
+            started: Instant::now(),
+        }
+    }
+
+    /// The fraction of the program that has been executed so far.
+    pub fn progress(&self) -> f32 {
+        f32::from_bits(self.progress.load(Ordering::Relaxed))
+    }
+
+    /// The number of seconds since the evaluation started.
+    pub fn elapsed(&self) -> f32 {
+        self.started.elapsed().as_secs_f32()
+    }
+
+    /// Asks the program to stop. The evaluation then finishes with a
+    /// [`Cancelled`] error as soon as the current operation notices.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns the result of the program, if it has finished.
+    pub fn try_finish(&self) -> Option<Result<HalfEdgeMesh>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err(anyhow!("The evaluation stopped without a result")))
+            }
+        }
+    }
+}
+
+impl Drop for Evaluation {
+    fn drop(&mut self) {
+        self.cancel();
+    }
+}
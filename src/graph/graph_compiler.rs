@@ -1,6 +1,7 @@
 use egui::any::TypeMap;
 
 use crate::{graph::poly_asm::PolyAsmProgram, prelude::graph::*, prelude::*};
+use crate::graph::graph_types::node_types::VectorOp;
 
 use self::outputs_cache::OutputsCache;
 
@@ -34,11 +35,11 @@ where
         let addr = match graph[param].value() {
             InputParamValue::Vector(val) => Ok(program.mem_alloc_raw(val)),
             InputParamValue::Scalar(val) => Ok(program.mem_alloc_raw(val)),
-            InputParamValue::Selection { text, selection } => {
-                Ok(program.mem_alloc_raw(selection.ok_or_else(|| {
+            InputParamValue::Selection { text, selection } => Ok(program.mem_alloc_raw(
+                Selection::Indices(selection.ok_or_else(|| {
                     anyhow!("Error parsing selection for parameter {:?}", param_name)
-                })?))
-            }
+                })?),
+            )),
             InputParamValue::None => Err(anyhow!(
                 "Parameter {} of node {:?} should have a connection",
                 param_name,
@@ -58,11 +59,35 @@ where
                     path.ok_or_else(|| anyhow!("Path is not set"))?.clone();
                 Ok(program.mem_alloc_raw(path))
             }
+            InputParamValue::Color(val) => Ok(program.mem_alloc_raw(val)),
         }?;
         MemAddr::from_raw_checked(program, addr, param_name)
     }
 }
 
+/// Reads the value of an enum parameter and maps it to its corresponding Rust
+/// enum. Enum parameters only accept constants, so the value is always known
+/// at compile time.
+fn enum_input_value<E>(graph: &Graph, node_id: NodeId, param_name: &str) -> Result<E>
+where
+    E: std::str::FromStr,
+{
+    let param = graph[node_id].get_input(param_name)?;
+    match graph[param].value() {
+        InputParamValue::Enum { values, selection } => {
+            let selection = selection.ok_or_else(|| {
+                anyhow!("No selection has been made for parameter {}", param_name)
+            })?;
+            let value = values.get(selection as usize).ok_or_else(|| {
+                anyhow!("Invalid selection index for parameter {}", param_name)
+            })?;
+            E::from_str(value)
+                .map_err(|_| anyhow!("Invalid value {} for parameter {}", value, param_name))
+        }
+        _ => Err(anyhow!("Parameter {} is not an enum", param_name)),
+    }
+}
+
 /// Allocates the return address for an output parameter, and registers this
 /// fact on the outputs cache to avoid re-generating code for nodes.
 fn gen_output_value<T>(
@@ -76,8 +101,12 @@ where
     T: Send + Sync + 'static + Clone,
 {
     let addr = program.mem_reserve();
-    let param = graph[node_id].get_output(param_name)?;
-    outputs_cache.insert(param, addr);
+    // NOTE: Nodes saved before an output was added to their type don't have
+    // it. Nothing can be connected to it, so the value is computed but never
+    // read.
+    if let Ok(param) = graph[node_id].get_output(param_name) {
+        outputs_cache.insert(param, addr);
+    }
     Ok(addr)
 }
 
@@ -95,6 +124,11 @@ fn gen_code_for_node(
             gen_input_value(program, graph, outputs_cache, node_id, $name)?
         };
     }
+    macro_rules! enum_input {
+        ($name:expr) => {
+            enum_input_value(graph, node_id, $name)?
+        };
+    }
     macro_rules! output {
         ($name:expr) => {
             gen_output_value(program, graph, outputs_cache, node_id, $name)?
@@ -120,6 +154,46 @@ fn gen_code_for_node(
             };
             program.add_operation(operation);
         }
+        "MakeGrid" => {
+            let operation = PolyAsmInstruction::MakeGrid {
+                center: input!("center"),
+                size: input!("size"),
+                divisions_x: input!("divisions_x"),
+                divisions_z: input!("divisions_z"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "MakeLine" => {
+            let operation = PolyAsmInstruction::MakeLine {
+                start: input!("start"),
+                end: input!("end"),
+                segments: input!("segments"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "MakeUVSphere" => {
+            let operation = PolyAsmInstruction::MakeUVSphere {
+                center: input!("center"),
+                radius: input!("radius"),
+                segments: input!("segments"),
+                rings: input!("rings"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "MakeTorus" => {
+            let operation = PolyAsmInstruction::MakeTorus {
+                center: input!("center"),
+                major_radius: input!("major_radius"),
+                minor_radius: input!("minor_radius"),
+                major_segments: input!("major_segments"),
+                minor_segments: input!("minor_segments"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
         "BevelEdges" => {
             let operation = PolyAsmInstruction::BevelEdges {
                 edges: input!("edges"),
@@ -135,6 +209,7 @@ fn gen_code_for_node(
                 amount: input!("amount"),
                 in_mesh: input!("in_mesh"),
                 out_mesh: output!("out_mesh"),
+                out_faces: output!("extruded_faces"),
             };
             program.add_operation(operation);
         }
@@ -144,6 +219,7 @@ fn gen_code_for_node(
                 amount: input!("amount"),
                 in_mesh: input!("in_mesh"),
                 out_mesh: output!("out_mesh"),
+                out_faces: output!("chamfer_faces"),
             };
             program.add_operation(operation);
         }
@@ -157,25 +233,18 @@ fn gen_code_for_node(
             program.add_operation(operation);
         }
         "VectorMath" => {
-            let op: MemAddr<String> = input!("vec_op");
-            let op_str = program
-                .mem_fetch(op)
-                .map_err(|err| anyhow!("Expected constant.").context(err))?;
-
-            let operation = match op_str.as_str() {
-                "ADD" => PolyAsmInstruction::VectorAdd {
+            let op: VectorOp = enum_input!("vec_op");
+            let operation = match op {
+                VectorOp::Add => PolyAsmInstruction::VectorAdd {
                     a: input!("A"),
                     b: input!("B"),
                     out_vec: output!("out_vec"),
                 },
-                "SUB" => PolyAsmInstruction::VectorSub {
+                VectorOp::Sub => PolyAsmInstruction::VectorSub {
                     a: input!("A"),
                     b: input!("B"),
                     out_vec: output!("out_vec"),
                 },
-                invalid => {
-                    bail!("Invalid VectorMath operation: {}", invalid)
-                }
             };
 
             program.add_operation(operation);
@@ -188,6 +257,150 @@ fn gen_code_for_node(
             };
             program.add_operation(operation);
         }
+        "Revolve" => {
+            let operation = PolyAsmInstruction::Revolve {
+                profile: input!("profile"),
+                origin: input!("origin"),
+                axis: input!("axis"),
+                angle: input!("angle"),
+                steps: input!("steps"),
+                height: input!("height"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "SnapToGrid" => {
+            let operation = PolyAsmInstruction::SnapToGrid {
+                cell: input!("cell"),
+                merge_distance: input!("merge_distance"),
+                in_mesh: input!("in_mesh"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Mirror" => {
+            let operation = PolyAsmInstruction::Mirror {
+                axis: enum_input!("axis"),
+                origin: input!("origin"),
+                weld_threshold: input!("weld_threshold"),
+                in_mesh: input!("in_mesh"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Transform" => {
+            let operation = PolyAsmInstruction::Transform {
+                translation: input!("translation"),
+                rotation: input!("rotation"),
+                scale: input!("scale"),
+                in_mesh: input!("in_mesh"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Spherize" => {
+            let operation = PolyAsmInstruction::Spherize {
+                factor: input!("factor"),
+                in_mesh: input!("in_mesh"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Twist" => {
+            let operation = PolyAsmInstruction::Twist {
+                axis: enum_input!("axis"),
+                angle_per_unit: input!("angle_per_unit"),
+                in_mesh: input!("in_mesh"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Taper" => {
+            let operation = PolyAsmInstruction::Taper {
+                axis: enum_input!("axis"),
+                factor: input!("factor"),
+                in_mesh: input!("in_mesh"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Bend" => {
+            let operation = PolyAsmInstruction::Bend {
+                axis: enum_input!("axis"),
+                angle: input!("angle"),
+                start: input!("start"),
+                end: input!("end"),
+                in_mesh: input!("in_mesh"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Jitter" => {
+            let operation = PolyAsmInstruction::Jitter {
+                amount: input!("amount"),
+                seed: input!("seed"),
+                direction: enum_input!("direction"),
+                in_mesh: input!("in_mesh"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Displace" => {
+            let operation = PolyAsmInstruction::Displace {
+                frequency: input!("frequency"),
+                amplitude: input!("amplitude"),
+                seed: input!("seed"),
+                in_mesh: input!("in_mesh"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Smooth" => {
+            let operation = PolyAsmInstruction::Smooth {
+                passes: input!("passes"),
+                lambda: input!("lambda"),
+                mu: input!("mu"),
+                in_mesh: input!("in_mesh"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Shrinkwrap" => {
+            let operation = PolyAsmInstruction::Shrinkwrap {
+                mode: enum_input!("mode"),
+                in_mesh: input!("in_mesh"),
+                target: input!("target"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Subdivide" => {
+            let operation = PolyAsmInstruction::Subdivide {
+                scheme: enum_input!("scheme"),
+                iterations: input!("iterations"),
+                boundary: enum_input!("boundary"),
+                in_mesh: input!("in_mesh"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Boolean" => {
+            let operation = PolyAsmInstruction::Boolean {
+                op: enum_input!("operation"),
+                a: input!("A"),
+                b: input!("B"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
+        "Decimate" => {
+            let operation = PolyAsmInstruction::Decimate {
+                threshold: input!("threshold"),
+                in_mesh: input!("in_mesh"),
+                out_mesh: output!("out_mesh"),
+            };
+            program.add_operation(operation);
+        }
         "ExportObj" => {
             let operation = PolyAsmInstruction::ExportObj {
                 in_mesh: input!("mesh"),
@@ -195,6 +408,13 @@ fn gen_code_for_node(
             };
             program.add_operation(operation);
         }
+        "ExportGltf" => {
+            let operation = PolyAsmInstruction::ExportGltf {
+                in_mesh: input!("mesh"),
+                export_path: input!("export_path"),
+            };
+            program.add_operation(operation);
+        }
         invalid => return Err(anyhow!("Unknown op_name {}", invalid)),
     }
     Ok(())
@@ -207,3 +427,41 @@ pub fn compile_graph(graph: &Graph, final_node: NodeId) -> Result<PolyAsmProgram
     gen_code_for_node(&mut program, &graph, final_node, &mut outputs_cache)?;
     Ok(program)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::graph::node_types::GraphNodeType;
+
+    fn connect(graph: &mut Graph, from: NodeId, to: NodeId, input: &str) {
+        let output = graph[from].get_output("out_mesh").unwrap();
+        let input = graph[to].get_input(input).unwrap();
+        graph.add_connection(output, input);
+    }
+
+    #[test]
+    pub fn test_compile_boolean_and_decimate() {
+        let mut graph = Graph::new();
+        let make_box = graph.add_node(GraphNodeType::MakeBox.to_descriptor());
+        let make_sphere = graph.add_node(GraphNodeType::MakeUVSphere.to_descriptor());
+        let boolean = graph.add_node(GraphNodeType::Boolean.to_descriptor());
+        let decimate = graph.add_node(GraphNodeType::Decimate.to_descriptor());
+        connect(&mut graph, make_box, boolean, "A");
+        connect(&mut graph, make_sphere, boolean, "B");
+        connect(&mut graph, boolean, decimate, "in_mesh");
+
+        // The default box fits inside the default sphere, so their union is
+        // the sphere alone.
+        let sphere = halfedge::primitives::UVSphere::build(Vec3::ZERO, 1.0, 16, 8);
+        let union = compile_graph(&graph, boolean).unwrap().execute().unwrap();
+        assert_eq!(union.validate(), Ok(()));
+        assert!(union.stats().is_closed);
+        assert!((union.volume() - sphere.volume()).abs() < 1e-3);
+
+        let decimated = compile_graph(&graph, decimate).unwrap().execute().unwrap();
+        assert_eq!(decimated.validate(), Ok(()));
+        assert!(decimated.stats().is_closed);
+        assert!(decimated.iter_vertices().count() <= union.iter_vertices().count());
+        assert!((decimated.volume() - sphere.volume()).abs() < 0.1);
+    }
+}
@@ -13,8 +13,12 @@ pub mod node_finder;
 
 pub mod serialization;
 
-/// Returns true if dirty
-pub fn draw_app(ctx: &CtxRef, state: &mut EditorState) -> bool {
+/// How long the error for a rejected connection stays on screen.
+const CONNECTION_ERROR_SECONDS: f64 = 3.0;
+
+/// Returns true if dirty. The `last_mesh` is the latest evaluated mesh, and
+/// will be embedded in the saved file when mesh baking is enabled.
+pub fn draw_app(ctx: &CtxRef, state: &mut EditorState, last_mesh: Option<&HalfEdgeMesh>) -> bool {
     let screen_rect = ctx.available_rect();
     let screen_size = ctx.available_rect().size();
     egui::TopBottomPanel::bottom("graph_panel").show(ctx, |ui| {
@@ -41,8 +45,41 @@ pub fn draw_app(ctx: &CtxRef, state: &mut EditorState) -> bool {
                         .add_filter("Blackjack Models", &["blj"])
                         .save_file();
                     if let Some(path) = file_location {
+                        // If the graph hasn't been evaluated since loading, the
+                        // previously baked mesh is still the latest result.
+                        let baked_mesh = state
+                            .bake_mesh_on_save
+                            .then(|| last_mesh.or(state.baked_mesh.as_ref()))
+                            .flatten();
                         // TODO: Do not panic for this. Show error modal instead.
-                        serialization::save(state, ctx, path).expect("Serialization error");
+                        serialization::save(state, ctx, path, baked_mesh)
+                            .expect("Serialization error");
+                    }
+                }
+                ui.checkbox(&mut state.bake_mesh_on_save, "Bake mesh on save");
+                if ui.button("Export OBJ...").clicked() {
+                    if let Some(mesh) = last_mesh.or(state.baked_mesh.as_ref()) {
+                        let file_location = rfd::FileDialog::new()
+                            .set_file_name("Untitled.obj")
+                            .add_filter("Wavefront OBJ", &["obj"])
+                            .save_file();
+                        if let Some(path) = file_location {
+                            // TODO: Do not panic for this. Show error modal instead.
+                            mesh.to_wavefront_obj(path).expect("OBJ export error");
+                        }
+                    }
+                }
+                if ui.button("Export STL...").clicked() {
+                    if let Some(mesh) = last_mesh.or(state.baked_mesh.as_ref()) {
+                        let file_location = rfd::FileDialog::new()
+                            .set_file_name("Untitled.stl")
+                            .add_filter("STL", &["stl"])
+                            .save_file();
+                        if let Some(path) = file_location {
+                            // TODO: Do not panic for this. Show error modal instead.
+                            halfedge::io::stl::export(mesh, &path, true)
+                                .expect("STL export error");
+                        }
                     }
                 }
                 if ui.button("Load").clicked() {
@@ -173,7 +210,24 @@ pub fn draw_graph_editor(ctx: &CtxRef, state: &mut EditorState, clip_rect: Rect)
                 };
 
                 if let Some((input, output)) = in_out {
-                    state.graph.add_connection(output, input)
+                    let previous = state.graph.connection(input);
+                    state.graph.add_connection(output, input);
+
+                    // Cycles would make evaluation loop forever. Roll back the
+                    // connection and let the user know.
+                    if let Some(cycle) = state.graph.graph_has_cycle() {
+                        match previous {
+                            Some(previous) => state.graph.add_connection(previous, input),
+                            None => {
+                                state.graph.remove_connection(input);
+                            }
+                        }
+                        let labels = cycle.iter().map(|n| state.graph[*n].label.as_str());
+                        state.connection_error = Some((
+                            format!("Connection would create a cycle: {}", labels.join(" -> ")),
+                            ctx.input().time,
+                        ));
+                    }
                 }
             }
             DrawGraphNodeResponse::SetActiveNode(node_id) => {
@@ -208,6 +262,21 @@ pub fn draw_graph_editor(ctx: &CtxRef, state: &mut EditorState, clip_rect: Rect)
         }
     }
 
+    /* Flash the last connection error for a few seconds */
+    if let Some((message, time)) = &state.connection_error {
+        if ctx.input().time - time < CONNECTION_ERROR_SECONDS {
+            ctx.debug_painter().text(
+                clip_rect.left_top() + vec2(10.0, 10.0),
+                Align2::LEFT_TOP,
+                message,
+                TextStyle::Body,
+                Color32::RED,
+            );
+        } else {
+            state.connection_error = None;
+        }
+    }
+
     /* Mouse input handling */
 
     if mouse.any_released() && state.connection_in_progress.is_some() {
@@ -5,6 +5,48 @@ use crate::prelude::*;
 
 use super::node_finder::NodeFinder;
 
+/// A camera orbiting around a focus point. Angles are in degrees.
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+pub struct OrbitCamera {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub focus: Vec3,
+}
+
+impl Default for OrbitCamera {
+    fn default() -> Self {
+        Self {
+            yaw: -30.0,
+            pitch: 30.0,
+            distance: 8.0,
+            focus: Vec3::ZERO,
+        }
+    }
+}
+
+impl OrbitCamera {
+    pub fn view_matrix(&self) -> Mat4 {
+        Mat4::from_translation(Vec3::Z * self.distance)
+            * Mat4::from_rotation_x(-self.pitch.to_radians())
+            * Mat4::from_rotation_y(-self.yaw.to_radians())
+            * Mat4::from_translation(-self.focus)
+    }
+
+    /// Resets the camera to the default angles, looking at the center of the
+    /// given bounding box from far enough to see all of it.
+    pub fn frame_all(&mut self, (min, max): (Vec3, Vec3)) {
+        let radius = (max - min).length() * 0.5;
+        *self = Self {
+            focus: (min + max) * 0.5,
+            // NOTE: The vertical field of view is 60 degrees, so the distance
+            // from which a sphere fits in view is radius / sin(30 degrees).
+            distance: (radius * 2.0).max(1.0),
+            ..Self::default()
+        };
+    }
+}
+
 pub struct EditorState {
     pub graph: Graph,
     /// An ongoing connection interaction: The mouse has dragged away from a
@@ -23,6 +65,19 @@ pub struct EditorState {
     pub node_finder: Option<NodeFinder>,
     /// When set, the file path stored in the inner string will be loaded.
     pub load_op: Option<String>,
+    /// When set, the evaluated mesh is embedded in the file on save.
+    pub bake_mesh_on_save: bool,
+    /// A mesh loaded from a file that was saved with a baked mesh. It is
+    /// displayed until the graph is successfully evaluated for the first time.
+    pub baked_mesh: Option<HalfEdgeMesh>,
+    /// The error message for the last rejected connection, and the time at
+    /// which it happened, in seconds, as reported by egui.
+    pub connection_error: Option<(String, f64)>,
+    /// The viewport camera. Saved along with the graph.
+    pub camera: OrbitCamera,
+    /// When set, the camera will be moved to frame the evaluated mesh once it
+    /// is available. Used for files saved without a camera.
+    pub frame_all_op: bool,
 }
 
 impl EditorState {
@@ -35,6 +90,11 @@ impl EditorState {
             node_position_ops: HashMap::default(),
             node_finder: None,
             load_op: None,
+            bake_mesh_on_save: false,
+            baked_mesh: None,
+            connection_error: None,
+            camera: OrbitCamera::default(),
+            frame_all_op: false,
         }
     }
 }
@@ -1,4 +1,5 @@
 use crate::color_hex_utils::*;
+use crate::prelude::graph::node_types::GraphNodeType;
 use crate::prelude::graph::*;
 use crate::prelude::*;
 use egui::any::AnyMap;
@@ -58,23 +59,28 @@ pub fn show_graph_node(
 
     child_ui.vertical(|ui| {
         ui.horizontal(|ui| {
-            ui.add(
+            let title = ui.add(
                 Label::new(&graph[node_id].label)
                     .text_style(TextStyle::Button)
                     .text_color(color_from_hex("#fefefe").unwrap()),
             );
+            if let Some(archetype) = GraphNodeType::from_op_name(&graph[node_id].op_name) {
+                title.on_hover_text(archetype.description());
+            }
         });
         ui.add_space(margin.y);
         title_height = ui.min_size().y;
 
-        // First pass: Draw the inner fields. Compute port heights
+        // First pass: Draw the inner fields. Compute port heights. Inputs
+        // with an incoming connection take their value from upstream, so only
+        // their name is shown. Unconnected inputs show an editable widget.
         let inputs = graph[node_id].inputs.clone();
         for (param_name, param) in inputs {
             let height_before = ui.min_rect().bottom();
             if graph.connection(param).is_some() {
                 ui.label(param_name);
-            } else {
-                graph[param].value_widget(&param_name, ui);
+            } else if graph[param].value_widget(&param_name, ui) {
+                graph.mark_changed();
             }
             let height_after = ui.min_rect().bottom();
             input_port_heights.push((height_before + height_after) / 2.0);
@@ -318,6 +324,7 @@ pub fn data_type_color(data_type: DataType) -> egui::Color32 {
         DataType::Vector => color_from_hex("#eecf6d").unwrap(),
         DataType::Scalar => color_from_hex("#eb9fef").unwrap(),
         DataType::Selection => color_from_hex("#4b7f52").unwrap(),
+        DataType::Color => color_from_hex("#e05a47").unwrap(),
         DataType::Enum => color_from_hex("#ff0000").unwrap(), // Should never be in a port, so highlight in red
         DataType::NewFile => color_from_hex("#ff0000").unwrap(), // Should never be in a port, so highlight in red
     }
@@ -19,6 +19,34 @@ impl NodeFinder {
         }
     }
 
+    /// Returns the node archetypes matching the query, best matches first.
+    /// Matching is case-insensitive. Labels starting with the query rank above
+    /// labels containing a word that starts with it, and those above labels
+    /// containing it anywhere else.
+    fn search(query: &str) -> Vec<GraphNodeType> {
+        let query = query.to_lowercase();
+        GraphNodeType::all_types()
+            .filter_map(|archetype| {
+                let label = archetype.type_label().to_lowercase();
+                let rank = if label.starts_with(&query) {
+                    0
+                } else if label
+                    .split_whitespace()
+                    .any(|word| word.starts_with(&query))
+                {
+                    1
+                } else if label.contains(&query) {
+                    2
+                } else {
+                    return None;
+                };
+                Some((rank, archetype))
+            })
+            .sorted_by_key(|(rank, archetype)| (*rank, archetype.type_label()))
+            .map(|(_, archetype)| archetype)
+            .collect()
+    }
+
     /// Shows the node selector panel with a search bar. Returns whether a node
     /// archetype was selected and, in that case, the finder should be hidden on
     /// the next frame.
@@ -46,14 +74,39 @@ impl NodeFinder {
                 let mut query_submit = resp.lost_focus() && ui.input().key_down(Key::Enter);
 
                 Frame::default().margin(vec2(10.0, 10.0)).show(ui, |ui| {
-                    for archetype in GraphNodeType::all_types() {
-                        let archetype_name = archetype.type_label();
-                        if archetype_name.contains(self.query.as_str()) {
+                    if self.query.is_empty() {
+                        // Group nodes by category, in order of first appearance
+                        let categories = GraphNodeType::all_types()
+                            .into_group_map_by(|archetype| archetype.category());
+                        let category_order = GraphNodeType::all_types()
+                            .map(|archetype| archetype.category())
+                            .unique();
+                        for category in category_order {
+                            CollapsingHeader::new(category)
+                                .default_open(true)
+                                .show(ui, |ui| {
+                                    for archetype in &categories[category] {
+                                        if ui
+                                            .selectable_label(false, archetype.type_label())
+                                            .on_hover_text(archetype.description())
+                                            .clicked()
+                                        {
+                                            submitted_archetype = Some(*archetype);
+                                        }
+                                    }
+                                });
+                        }
+                    } else {
+                        for archetype in Self::search(&self.query) {
                             if query_submit {
                                 submitted_archetype = Some(archetype);
                                 query_submit = false;
                             }
-                            if ui.selectable_label(false, &archetype_name).clicked() {
+                            if ui
+                                .selectable_label(false, archetype.type_label())
+                                .on_hover_text(archetype.description())
+                                .clicked()
+                            {
                                 submitted_archetype = Some(archetype);
                             }
                         }
@@ -1,7 +1,10 @@
 use crate::prelude::*;
 use std::path::PathBuf;
 
-use super::{editor_state::EditorState, node_finder::NodeFinder};
+use super::{
+    editor_state::{EditorState, OrbitCamera},
+    node_finder::NodeFinder,
+};
 use crate::prelude::graph::{Graph, InputId, NodeId};
 use serde::{Deserialize, Serialize};
 
@@ -10,34 +13,186 @@ struct SerializedEditorState {
     pub graph: Graph,
     pub active_node: Option<NodeId>,
     pub egui_memory: egui::Memory,
+    /// The evaluated mesh at the time of saving, if the user chose to bake it.
+    #[serde(default)]
+    pub baked_mesh: Option<MeshBlob>,
+    /// The viewport camera at the time of saving. Missing in older files.
+    #[serde(default)]
+    pub camera: Option<OrbitCamera>,
+}
+
+/// A mesh stored as a compact binary blob of little-endian values. The layout
+/// is: vertex count, vertex positions (3 floats each), face count and, for
+/// each face, its vertex count followed by its vertex indices. All counts and
+/// indices are u32.
+///
+/// This only stores the geometry. Enough to display the mesh without having
+/// to evaluate the graph.
+struct MeshBlob(Vec<u8>);
+
+impl MeshBlob {
+    pub fn from_mesh(mesh: &HalfEdgeMesh) -> Self {
+        let mut bytes = Vec::new();
+        let push_u32 = |bytes: &mut Vec<u8>, x: u32| bytes.extend(x.to_le_bytes());
+
+        let mut imap = HashMap::<VertexId, u32>::new();
+        push_u32(&mut bytes, mesh.iter_vertices().count() as u32);
+        for (idx, (v_id, v)) in mesh.iter_vertices().enumerate() {
+            imap.insert(v_id, idx as u32);
+            for x in v.position.to_array() {
+                bytes.extend(x.to_le_bytes());
+            }
+        }
+
+        push_u32(&mut bytes, mesh.iter_faces().count() as u32);
+        for (face_id, _) in mesh.iter_faces() {
+            let vertices = mesh.face_vertices(face_id);
+            push_u32(&mut bytes, vertices.len() as u32);
+            for v in vertices {
+                push_u32(&mut bytes, imap[&v]);
+            }
+        }
+
+        MeshBlob(bytes)
+    }
+
+    pub fn to_mesh(&self) -> Result<HalfEdgeMesh> {
+        let mut words = self.0.chunks_exact(4).map(|w| [w[0], w[1], w[2], w[3]]);
+        let mut next_word = || words.next().ok_or_else(|| anyhow!("Truncated mesh blob"));
+
+        let num_vertices = u32::from_le_bytes(next_word()?);
+        let positions = (0..num_vertices)
+            .map(|_| -> Result<Vec3> {
+                Ok(Vec3::new(
+                    f32::from_le_bytes(next_word()?),
+                    f32::from_le_bytes(next_word()?),
+                    f32::from_le_bytes(next_word()?),
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let num_faces = u32::from_le_bytes(next_word()?);
+        let polygons = (0..num_faces)
+            .map(|_| -> Result<Vec<usize>> {
+                let len = u32::from_le_bytes(next_word()?);
+                (0..len)
+                    .map(|_| -> Result<usize> { Ok(u32::from_le_bytes(next_word()?) as usize) })
+                    .collect()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        HalfEdgeMesh::build_from_polygons(&positions, &polygons)
+    }
+}
+
+// NOTE: The blob is serialized as bytes instead of a sequence, so that ron
+// writes it as a base64 string instead of a very long list of numbers.
+impl Serialize for MeshBlob {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for MeshBlob {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BlobVisitor;
+        impl<'de> serde::de::Visitor<'de> for BlobVisitor {
+            type Value = MeshBlob;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "a byte array")
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<MeshBlob, E> {
+                Ok(MeshBlob(v.to_vec()))
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<MeshBlob, E> {
+                Ok(MeshBlob(v))
+            }
+        }
+        deserializer.deserialize_byte_buf(BlobVisitor)
+    }
 }
 
 impl SerializedEditorState {
-    pub fn from_state(editor_state: &EditorState, egui_ctx: &egui::CtxRef) -> Self {
+    pub fn from_state(
+        editor_state: &EditorState,
+        egui_ctx: &egui::CtxRef,
+        baked_mesh: Option<&HalfEdgeMesh>,
+    ) -> Self {
         SerializedEditorState {
             graph: editor_state.graph.clone(),
             active_node: editor_state.active_node.clone(),
             egui_memory: egui_ctx.memory().clone(),
+            baked_mesh: baked_mesh.map(MeshBlob::from_mesh),
+            camera: Some(editor_state.camera),
         }
     }
 
-    pub fn to_state(self, egui_ctx: &egui::CtxRef) -> EditorState {
-        let mut state = EditorState::new(); 
+    pub fn to_state(self, egui_ctx: &egui::CtxRef) -> Result<EditorState> {
+        let mut state = EditorState::new();
         state.graph = self.graph;
         state.active_node = self.active_node;
+        // The baked mesh is only a cache. If it can't be read, the graph can
+        // still be loaded and evaluated.
+        state.baked_mesh = match self.baked_mesh.map(|blob| blob.to_mesh()).transpose() {
+            Ok(mesh) => mesh,
+            Err(err) => {
+                println!("Ignoring invalid baked mesh: {}", err);
+                None
+            }
+        };
+        match self.camera {
+            Some(camera) => state.camera = camera,
+            None => state.frame_all_op = true,
+        }
         *egui_ctx.memory() = self.egui_memory;
-        state
+        Ok(state)
     }
 }
 
-pub fn save(editor_state: &EditorState, egui_ctx: &egui::CtxRef, path: PathBuf) -> Result<()> {
+/// Saves the editor state to `path`. When `baked_mesh` is set, the mesh is
+/// embedded in the file, so it can be displayed right after loading even if
+/// the graph can't be evaluated.
+pub fn save(
+    editor_state: &EditorState,
+    egui_ctx: &egui::CtxRef,
+    path: PathBuf,
+    baked_mesh: Option<&HalfEdgeMesh>,
+) -> Result<()> {
     let writer = std::io::BufWriter::new(std::fs::File::create(path)?);
-    ron::ser::to_writer(writer, &SerializedEditorState::from_state(editor_state, egui_ctx))?;
+    ron::ser::to_writer(
+        writer,
+        &SerializedEditorState::from_state(editor_state, egui_ctx, baked_mesh),
+    )?;
     Ok(())
 }
 
 pub fn load(egui_ctx: &egui::CtxRef, path: PathBuf) -> Result<EditorState> {
     let reader = std::io::BufReader::new(std::fs::File::open(path)?);
-    let state : SerializedEditorState = ron::de::from_reader(reader)?;
-    Ok(state.to_state(egui_ctx))
+    let state: SerializedEditorState = ron::de::from_reader(reader)?;
+    state.to_state(egui_ctx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_load_corrupt_baked_mesh() {
+        let mesh = crate::mesh::halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let mut blob = MeshBlob::from_mesh(&mesh);
+        blob.0.truncate(blob.0.len() / 2);
+
+        let serialized = SerializedEditorState {
+            graph: Graph::new(),
+            active_node: None,
+            egui_memory: egui::Memory::default(),
+            baked_mesh: Some(blob),
+            camera: None,
+        };
+        let state = serialized.to_state(&egui::CtxRef::default()).unwrap();
+        assert!(state.baked_mesh.is_none());
+    }
 }
@@ -1,5 +1,5 @@
 use slotmap::SlotMap;
-use glam::Vec3;
+use glam::{Vec3, Vec4};
 use std::{any::Any, collections::HashMap};
 use serde::{Serialize, Deserialize};
 
@@ -21,6 +21,7 @@ pub enum DataType {
     Selection,
     Mesh,
     Enum,
+    Color,
     // The path to a (possibly new) file where export contents will be saved to
     NewFile,
 }
@@ -48,6 +49,8 @@ pub enum InputParamValue {
     NewFile {
         path: Option<std::path::PathBuf>,
     },
+    /// An RGBA color, stored in linear space
+    Color(Vec4),
 }
 
 /// There are three kinds of input params
@@ -127,6 +130,23 @@ pub struct Graph {
     // Connects the input of a node, to the output of its predecessor that
     // produces it
     connections: HashMap<InputId, OutputId>,
+    /// Changes whenever the graph is edited. See [`GraphGeneration`].
+    #[serde(skip)]
+    generation: GraphGeneration,
+}
+
+/// Identifies a version of a graph, to tell whether it has changed without
+/// comparing its contents. Every edit gives the graph a new generation, and so
+/// does creating or loading a graph, so two unrelated graphs never share one.
+/// Clones keep the generation of the original.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphGeneration(u64);
+
+impl Default for GraphGeneration {
+    fn default() -> Self {
+        static NEXT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        GraphGeneration(NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
+    }
 }
 
 pub enum InputDescriptor {
@@ -136,6 +156,7 @@ pub enum InputDescriptor {
     Scalar { default: f32, min: f32, max: f32 },
     Enum { values: Vec<String> },
     NewFile,
+    Color { default: Vec4 },
 }
 
 pub struct OutputDescriptor(DataType);
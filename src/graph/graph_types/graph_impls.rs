@@ -66,8 +66,9 @@ impl Graph {
                         id,
                         typ: DataType::Enum,
                         value: InputParamValue::Enum {
+                            // Default to the first option, if there is one.
+                            selection: if values.is_empty() { None } else { Some(0) },
                             values,
-                            selection: None,
                         },
                         metadata: smallvec![],
                         kind: ConstantOnly,
@@ -81,6 +82,14 @@ impl Graph {
                         kind: ConstantOnly,
                         node: node_id,
                     },
+                    InputDescriptor::Color { default } => InputParam {
+                        id,
+                        typ: DataType::Color,
+                        value: InputParamValue::Color(default),
+                        metadata: smallvec![],
+                        kind: ConnectionOrConstant,
+                        node: node_id,
+                    },
                 });
                 (input_name, input_id)
             })
@@ -101,6 +110,7 @@ impl Graph {
 
         self[node_id].inputs = inputs;
         self[node_id].outputs = outputs;
+        self.mark_changed();
         node_id
     }
 
@@ -116,9 +126,11 @@ impl Graph {
             self.outputs.remove(output);
         }
         self.nodes.remove(node_id);
+        self.mark_changed();
     }
 
     pub fn remove_connection(&mut self, input_id: InputId) -> Option<OutputId> {
+        self.mark_changed();
         self.connections.remove(&input_id)
     }
 
@@ -128,6 +140,19 @@ impl Graph {
 
     pub fn add_connection(&mut self, output: OutputId, input: InputId) {
         self.connections.insert(input, output);
+        self.mark_changed();
+    }
+
+    /// The current version of the graph. See [`GraphGeneration`].
+    pub fn generation(&self) -> GraphGeneration {
+        self.generation
+    }
+
+    /// Gives the graph a new generation. The methods that edit the graph call
+    /// this already, but code changing the values of parameters through
+    /// indexing must call it too.
+    pub fn mark_changed(&mut self) {
+        self.generation = GraphGeneration::default();
     }
 
     pub fn iter_connections(&self) -> impl Iterator<Item = (InputId, OutputId)> + '_ {
@@ -138,6 +163,60 @@ impl Graph {
         self.connections.get(&input).map(|x| *x)
     }
 
+    /// Returns the nodes connected to any of the inputs of the given node.
+    pub fn node_dependencies(&self, node_id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self[node_id]
+            .input_ids()
+            .filter_map(|input| self.connection(input))
+            .map(|output| self.get_output(output).node())
+    }
+
+    /// Checks the graph for cycles, which would make evaluation loop forever.
+    /// When a cycle is found, returns the nodes that form it, in the order in
+    /// which they depend on each other.
+    pub fn graph_has_cycle(&self) -> Option<Vec<NodeId>> {
+        #[derive(Clone, Copy, PartialEq, Eq)]
+        enum Mark {
+            InProgress,
+            Done,
+        }
+
+        // Depth-first search over the dependencies of each node. Finding a
+        // node that is still in progress means we went back to a node in the
+        // current path.
+        fn visit(
+            graph: &Graph,
+            node_id: NodeId,
+            marks: &mut HashMap<NodeId, Mark>,
+            path: &mut Vec<NodeId>,
+        ) -> Option<Vec<NodeId>> {
+            match marks.get(&node_id) {
+                Some(Mark::Done) => return None,
+                Some(Mark::InProgress) => {
+                    let start = path.iter().position(|n| *n == node_id)?;
+                    return Some(path[start..].to_vec());
+                }
+                None => {}
+            }
+
+            marks.insert(node_id, Mark::InProgress);
+            path.push(node_id);
+            for dependency in graph.node_dependencies(node_id) {
+                if let Some(cycle) = visit(graph, dependency, marks, path) {
+                    return Some(cycle);
+                }
+            }
+            path.pop();
+            marks.insert(node_id, Mark::Done);
+            None
+        }
+
+        let mut marks = HashMap::new();
+        let mut path = vec![];
+        self.iter_nodes()
+            .find_map(|node_id| visit(self, node_id, &mut marks, &mut path))
+    }
+
     pub fn any_param_type(&self, param: AnyParameterId) -> Result<DataType> {
         match param {
             AnyParameterId::Input(input) => self.inputs.get(input).map(|x| x.typ),
@@ -200,3 +279,58 @@ impl Node {
         self.is_executable
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::graph::node_types::GraphNodeType;
+
+    /// Connects the mesh output of `from` to the mesh input of `to`.
+    fn connect(graph: &mut Graph, from: NodeId, to: NodeId) {
+        let output = graph[from].get_output("out_mesh").unwrap();
+        let input = graph[to].get_input("in_mesh").unwrap();
+        graph.add_connection(output, input);
+    }
+
+    #[test]
+    pub fn test_graph_generation() {
+        let mut graph = Graph::new();
+        assert_ne!(graph.generation(), Graph::new().generation());
+
+        let before = graph.generation();
+        let make_box = graph.add_node(GraphNodeType::MakeBox.to_descriptor());
+        let decimate = graph.add_node(GraphNodeType::Decimate.to_descriptor());
+        assert_ne!(graph.generation(), before);
+
+        let before = graph.generation();
+        assert_eq!(graph.clone().generation(), before);
+        connect(&mut graph, make_box, decimate);
+        assert_ne!(graph.generation(), before);
+    }
+
+    #[test]
+    pub fn test_graph_has_cycle() {
+        let mut graph = Graph::new();
+        let make_box = graph.add_node(GraphNodeType::MakeBox.to_descriptor());
+        let a = graph.add_node(GraphNodeType::Decimate.to_descriptor());
+        let b = graph.add_node(GraphNodeType::Decimate.to_descriptor());
+        connect(&mut graph, make_box, a);
+        connect(&mut graph, a, b);
+        assert_eq!(graph.graph_has_cycle(), None);
+
+        // Feeding the last node back into the first one closes a cycle
+        let mut graph = Graph::new();
+        let a = graph.add_node(GraphNodeType::Decimate.to_descriptor());
+        let b = graph.add_node(GraphNodeType::Decimate.to_descriptor());
+        connect(&mut graph, a, b);
+        connect(&mut graph, b, a);
+        let cycle = graph.graph_has_cycle().unwrap();
+        assert_eq!(cycle.len(), 2);
+        assert!(cycle.contains(&a) && cycle.contains(&b));
+        // Each node depends on the next one, and the last on the first
+        for (i, node) in cycle.iter().enumerate() {
+            let next = cycle[(i + 1) % cycle.len()];
+            assert!(graph.node_dependencies(*node).any(|n| n == next));
+        }
+    }
+}
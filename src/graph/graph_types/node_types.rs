@@ -1,17 +1,51 @@
 use super::*;
 use strum::IntoEnumIterator;
+use crate::prelude::Axis;
+use crate::prelude::halfedge::boolean::BooleanOp;
+use crate::prelude::halfedge::deform::{JitterDirection, ShrinkwrapMode};
+use crate::prelude::halfedge::subdivision::{BoundaryMode, SubdivisionScheme};
 
 #[derive(Clone, Copy, strum_macros::EnumIter)]
 pub enum GraphNodeType {
     MakeBox,
     MakeQuad,
+    MakeGrid,
+    MakeLine,
+    MakeUVSphere,
+    MakeTorus,
     BevelEdges,
     ExtrudeFaces,
     ChamferVertices,
     MakeVector,
     VectorMath,
     MergeMeshes,
+    Revolve,
+    SnapToGrid,
+    Mirror,
+    Transform,
+    Spherize,
+    Twist,
+    Taper,
+    Bend,
+    Jitter,
+    Displace,
+    Smooth,
+    Shrinkwrap,
+    Subdivide,
+    Boolean,
+    Decimate,
     ExportObj,
+    ExportGltf,
+}
+
+/// The operations supported by the VectorMath node. The string
+/// representation is what gets shown in the node's dropdown.
+#[derive(Clone, Copy, Debug, strum_macros::EnumIter, strum_macros::EnumString, strum_macros::AsRefStr)]
+pub enum VectorOp {
+    #[strum(serialize = "ADD")]
+    Add,
+    #[strum(serialize = "SUB")]
+    Sub,
 }
 
 macro_rules! in_vector {
@@ -57,6 +91,12 @@ macro_rules! out_vector {
     };
 }
 
+macro_rules! out_selection {
+    ($name:expr) => {
+        ($name.to_owned(), OutputDescriptor(DataType::Selection))
+    };
+}
+
 macro_rules! in_selection {
     ($name:expr) => {
         ($name.to_owned(), InputDescriptor::Selection)
@@ -70,6 +110,14 @@ macro_rules! in_file {
 }
 
 macro_rules! in_enum {
+    ($name:expr, enum $typ:ty) => {
+        (
+            $name.to_owned(),
+            InputDescriptor::Enum {
+                values: <$typ>::iter().map(|x| x.as_ref().to_owned()).collect(),
+            },
+        )
+    };
     ($name:expr, $( $values:expr ),+) => {
         ($name.to_owned(), InputDescriptor::Enum { values: vec![$( $values.to_owned() ),+] })
     };
@@ -102,6 +150,54 @@ impl GraphNodeType {
                 outputs: vec![out_mesh!("out_mesh")],
                 is_executable: false,
             },
+            GraphNodeType::MakeGrid => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_vector!("center", Vec3::ZERO),
+                    in_vector!("size", Vec3::ONE),
+                    in_scalar!("divisions_x", 4.0, 1.0, 128.0),
+                    in_scalar!("divisions_z", 4.0, 1.0, 128.0),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::MakeLine => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_vector!("start", Vec3::ZERO),
+                    in_vector!("end", Vec3::Y),
+                    in_scalar!("segments", 1.0, 1.0, 32.0),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::MakeUVSphere => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_vector!("center", Vec3::ZERO),
+                    in_scalar!("radius", 1.0, 0.0, 10.0),
+                    in_scalar!("segments", 16.0, 3.0, 64.0),
+                    in_scalar!("rings", 8.0, 2.0, 32.0),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::MakeTorus => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_vector!("center", Vec3::ZERO),
+                    in_scalar!("major_radius", 1.0, 0.0, 10.0),
+                    in_scalar!("minor_radius", 0.25, 0.0, 5.0),
+                    in_scalar!("major_segments", 24.0, 3.0, 128.0),
+                    in_scalar!("minor_segments", 12.0, 3.0, 64.0),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
             GraphNodeType::BevelEdges => NodeDescriptor {
                 op_name,
                 label,
@@ -121,7 +217,7 @@ impl GraphNodeType {
                     in_selection!("faces"),
                     in_scalar!("amount", 0.0, 0.0, 1.0),
                 ],
-                outputs: vec![out_mesh!("out_mesh")],
+                outputs: vec![out_mesh!("out_mesh"), out_selection!("extruded_faces")],
                 is_executable: false,
             },
             GraphNodeType::ChamferVertices => NodeDescriptor {
@@ -132,7 +228,7 @@ impl GraphNodeType {
                     in_selection!("vertices"),
                     in_scalar!("amount", 0.0, 0.0, 1.0),
                 ],
-                outputs: vec![out_mesh!("out_mesh")],
+                outputs: vec![out_mesh!("out_mesh"), out_selection!("chamfer_faces")],
                 is_executable: false,
             },
             GraphNodeType::MakeVector => NodeDescriptor {
@@ -146,7 +242,7 @@ impl GraphNodeType {
                 op_name,
                 label,
                 inputs: vec![
-                    in_enum!("vec_op", "ADD", "SUB"),
+                    in_enum!("vec_op", enum VectorOp),
                     in_vector!("A", Vec3::ZERO),
                     in_vector!("B", Vec3::ZERO),
                 ],
@@ -163,6 +259,195 @@ impl GraphNodeType {
                 outputs: vec![out_mesh!("out_mesh")],
                 is_executable: false,
             },
+            GraphNodeType::Revolve => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("profile"),
+                    in_vector!("origin", Vec3::ZERO),
+                    in_vector!("axis", Vec3::Y),
+                    in_scalar!(
+                        "angle",
+                        std::f32::consts::TAU,
+                        -std::f32::consts::TAU,
+                        std::f32::consts::TAU
+                    ),
+                    in_scalar!("steps", 16.0, 1.0, 64.0),
+                    in_scalar!("height", 0.0, -10.0, 10.0),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::SnapToGrid => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_vector!("cell", Vec3::splat(0.1)),
+                    in_scalar!("merge_distance", 0.0, 0.0, 0.1),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Mirror => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_enum!("axis", enum Axis),
+                    in_vector!("origin", Vec3::ZERO),
+                    in_scalar!("weld_threshold", 0.001, 0.0, 0.1),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Transform => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_vector!("translation", Vec3::ZERO),
+                    in_vector!("rotation", Vec3::ZERO),
+                    in_vector!("scale", Vec3::ONE),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Spherize => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_scalar!("factor", 1.0, 0.0, 1.0),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Twist => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_enum!("axis", enum Axis),
+                    in_scalar!(
+                        "angle_per_unit",
+                        0.5,
+                        -std::f32::consts::PI,
+                        std::f32::consts::PI
+                    ),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Taper => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_enum!("axis", enum Axis),
+                    in_scalar!("factor", 0.5, -1.0, 1.0),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Bend => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_enum!("axis", enum Axis),
+                    in_scalar!(
+                        "angle",
+                        std::f32::consts::FRAC_PI_2,
+                        -std::f32::consts::TAU,
+                        std::f32::consts::TAU
+                    ),
+                    in_scalar!("start", 0.0, -10.0, 10.0),
+                    in_scalar!("end", 1.0, -10.0, 10.0),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Jitter => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_scalar!("amount", 0.1, 0.0, 1.0),
+                    in_scalar!("seed", 0.0, 0.0, 1000.0),
+                    in_enum!("direction", enum JitterDirection),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Displace => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_scalar!("frequency", 1.0, 0.0, 10.0),
+                    in_scalar!("amplitude", 0.1, -1.0, 1.0),
+                    in_scalar!("seed", 0.0, 0.0, 1000.0),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Smooth => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_scalar!("passes", 10.0, 1.0, 100.0),
+                    in_scalar!("lambda", 0.33, 0.0, 1.0),
+                    in_scalar!("mu", -0.34, -1.0, 0.0),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Shrinkwrap => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_mesh!("target"),
+                    in_enum!("mode", enum ShrinkwrapMode),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Subdivide => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_enum!("scheme", enum SubdivisionScheme),
+                    in_scalar!("iterations", 1.0, 1.0, 5.0),
+                    in_enum!("boundary", enum BoundaryMode),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Boolean => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("A"),
+                    in_mesh!("B"),
+                    in_enum!("operation", enum BooleanOp),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
+            GraphNodeType::Decimate => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("in_mesh"),
+                    in_scalar!("threshold", 0.05, 0.0, 1.0),
+                ],
+                outputs: vec![out_mesh!("out_mesh")],
+                is_executable: false,
+            },
             GraphNodeType::ExportObj => NodeDescriptor {
                 op_name,
                 label,
@@ -173,6 +458,16 @@ impl GraphNodeType {
                 outputs: vec![],
                 is_executable: true,
             },
+            GraphNodeType::ExportGltf => NodeDescriptor {
+                op_name,
+                label,
+                inputs: vec![
+                    in_mesh!("mesh"),
+                    in_file!("export_path"),
+                ],
+                outputs: vec![],
+                is_executable: true,
+            },
         }
     }
 
@@ -184,29 +479,199 @@ impl GraphNodeType {
         match self {
             GraphNodeType::MakeBox => "Box",
             GraphNodeType::MakeQuad => "Quad",
+            GraphNodeType::MakeGrid => "Grid",
+            GraphNodeType::MakeLine => "Line",
+            GraphNodeType::MakeUVSphere => "UV Sphere",
+            GraphNodeType::MakeTorus => "Torus",
             GraphNodeType::BevelEdges => "Bevel edges",
             GraphNodeType::ExtrudeFaces => "Extrude faces",
             GraphNodeType::ChamferVertices => "Chamfer vertices",
             GraphNodeType::MakeVector => "Vector",
             GraphNodeType::VectorMath => "Vector math",
             GraphNodeType::MergeMeshes => "Merge meshes",
+            GraphNodeType::Revolve => "Revolve",
+            GraphNodeType::SnapToGrid => "Snap to grid",
+            GraphNodeType::Mirror => "Mirror",
+            GraphNodeType::Transform => "Transform",
+            GraphNodeType::Spherize => "Spherize",
+            GraphNodeType::Twist => "Twist",
+            GraphNodeType::Taper => "Taper",
+            GraphNodeType::Bend => "Bend",
+            GraphNodeType::Jitter => "Jitter",
+            GraphNodeType::Displace => "Noise displace",
+            GraphNodeType::Smooth => "Smooth",
+            GraphNodeType::Shrinkwrap => "Shrinkwrap",
+            GraphNodeType::Subdivide => "Subdivide",
+            GraphNodeType::Boolean => "Boolean",
+            GraphNodeType::Decimate => "Decimate",
             GraphNodeType::ExportObj => "OBJ Export",
+            GraphNodeType::ExportGltf => "glTF Export",
+        }
+    }
+
+    /// The category this node is listed under in the node finder.
+    pub fn category(&self) -> &'static str {
+        match self {
+            GraphNodeType::MakeBox => "Primitives",
+            GraphNodeType::MakeQuad => "Primitives",
+            GraphNodeType::MakeGrid => "Primitives",
+            GraphNodeType::MakeLine => "Primitives",
+            GraphNodeType::MakeUVSphere => "Primitives",
+            GraphNodeType::MakeTorus => "Primitives",
+            GraphNodeType::BevelEdges => "Edit",
+            GraphNodeType::ExtrudeFaces => "Edit",
+            GraphNodeType::ChamferVertices => "Edit",
+            GraphNodeType::MergeMeshes => "Edit",
+            GraphNodeType::Revolve => "Edit",
+            GraphNodeType::SnapToGrid => "Edit",
+            GraphNodeType::MakeVector => "Math",
+            GraphNodeType::VectorMath => "Math",
+            GraphNodeType::Mirror => "Edit",
+            GraphNodeType::Transform => "Deform",
+            GraphNodeType::Spherize => "Deform",
+            GraphNodeType::Twist => "Deform",
+            GraphNodeType::Taper => "Deform",
+            GraphNodeType::Bend => "Deform",
+            GraphNodeType::Jitter => "Deform",
+            GraphNodeType::Displace => "Deform",
+            GraphNodeType::Smooth => "Deform",
+            GraphNodeType::Shrinkwrap => "Deform",
+            GraphNodeType::Subdivide => "Edit",
+            GraphNodeType::Boolean => "Edit",
+            GraphNodeType::Decimate => "Edit",
+            GraphNodeType::ExportObj => "Export",
+            GraphNodeType::ExportGltf => "Export",
         }
     }
 
+    /// A short explanation of what the node does, shown as a tooltip in the
+    /// graph editor and the node finder.
+    pub fn description(&self) -> &'static str {
+        match self {
+            GraphNodeType::MakeBox => "Creates a box with the given origin and size",
+            GraphNodeType::MakeQuad => "Creates a single quad facing towards the normal",
+            GraphNodeType::MakeGrid => {
+                "Creates a flat grid of quads on the XZ plane, split into the given \
+                 number of divisions along each axis"
+            }
+            GraphNodeType::MakeLine => {
+                "Creates a line between two points, made of a given number of segments. \
+                 Use it as a profile for the Revolve node"
+            }
+            GraphNodeType::MakeUVSphere => {
+                "Creates a sphere made of rings of quads, like the lines of latitude \
+                 and longitude on a globe, with triangles around the poles"
+            }
+            GraphNodeType::MakeTorus => {
+                "Creates a torus made of quads. The major radius goes from the center \
+                 to the middle of the tube, and the minor radius is the radius of the tube"
+            }
+            GraphNodeType::BevelEdges => "Bevels the given edges by a given distance amount",
+            GraphNodeType::ExtrudeFaces => {
+                "Extrudes the given faces by a given distance amount. Also outputs the \
+                 extruded faces, to keep editing them"
+            }
+            GraphNodeType::ChamferVertices => {
+                "Chamfers the given vertices by a given distance amount. Also outputs the \
+                 faces created in place of the vertices"
+            }
+            GraphNodeType::MakeVector => "Builds a vector from its x, y and z components",
+            GraphNodeType::VectorMath => "Adds or subtracts two vectors",
+            GraphNodeType::MergeMeshes => "Combines two meshes into a single one",
+            GraphNodeType::Revolve => {
+                "Sweeps a profile line around an axis, optionally moving it along the axis \
+                 to make a screw"
+            }
+            GraphNodeType::SnapToGrid => {
+                "Rounds the vertex positions to a grid, optionally merging the vertices \
+                 that end up close to each other"
+            }
+            GraphNodeType::Mirror => {
+                "Adds a mirrored copy of the mesh across the plane perpendicular to the axis. \
+                 Vertices on the plane are welded"
+            }
+            GraphNodeType::Transform => {
+                "Scales, rotates and then moves the mesh. The rotation is given as \
+                 angles around the X, Y and Z axes, in degrees"
+            }
+            GraphNodeType::Spherize => "Pushes the vertices towards the mesh's bounding sphere",
+            GraphNodeType::Twist => "Rotates the mesh around an axis, more the farther along it",
+            GraphNodeType::Taper => {
+                "Scales the mesh perpendicular to an axis, more the farther along it"
+            }
+            GraphNodeType::Bend => "Curves a section of the mesh into an arc",
+            GraphNodeType::Jitter => {
+                "Moves each vertex by a random offset. The same seed always gives the \
+                 same result"
+            }
+            GraphNodeType::Displace => {
+                "Moves each vertex along its normal by the value of a smooth noise \
+                 function. Higher frequencies give finer detail"
+            }
+            GraphNodeType::Smooth => {
+                "Smooths the mesh without shrinking it. Set mu to 0 for plain Laplacian \
+                 smoothing"
+            }
+            GraphNodeType::Shrinkwrap => "Moves the vertices onto the surface of a target mesh",
+            GraphNodeType::Subdivide => {
+                "Subdivides the mesh. Catmull-Clark splits every face into quads and Loop splits \
+                 every triangle into four, smoothing the result. Linear splits like Catmull-Clark \
+                 without smoothing"
+            }
+            GraphNodeType::Boolean => {
+                "Combines two closed meshes: Union keeps what's inside either of them, \
+                 Difference removes B from A and Intersection keeps what's inside both"
+            }
+            GraphNodeType::Decimate => {
+                "Collapses the edges shorter than the threshold, merging their endpoints"
+            }
+            GraphNodeType::ExportObj => "Saves the mesh to a Wavefront OBJ file",
+            GraphNodeType::ExportGltf => {
+                "Saves the mesh to a glTF file. Paths ending in .glb get a single binary file, \
+                 other paths a .gltf document with a separate .bin buffer"
+            }
+        }
+    }
+
+    /// Returns the archetype with the given op_name, if any. Used to find the
+    /// archetype of the nodes in a graph, which only store their op_name.
+    pub fn from_op_name(op_name: &str) -> Option<GraphNodeType> {
+        Self::all_types().find(|archetype| archetype.op_name() == op_name)
+    }
+
     /// The op_name is used by the graph compiler in graph_compiler.rs to select
     /// which PolyASM instructions to emit.
     pub fn op_name(&self) -> &'static str {
         match self {
             GraphNodeType::MakeBox => "MakeBox",
             GraphNodeType::MakeQuad => "MakeQuad",
+            GraphNodeType::MakeGrid => "MakeGrid",
+            GraphNodeType::MakeLine => "MakeLine",
+            GraphNodeType::MakeUVSphere => "MakeUVSphere",
+            GraphNodeType::MakeTorus => "MakeTorus",
             GraphNodeType::BevelEdges => "BevelEdges",
             GraphNodeType::ExtrudeFaces => "ExtrudeFaces",
             GraphNodeType::ChamferVertices => "ChamferVertices",
             GraphNodeType::MakeVector => "MakeVector",
             GraphNodeType::VectorMath => "VectorMath",
             GraphNodeType::MergeMeshes => "MergeMeshes",
+            GraphNodeType::Revolve => "Revolve",
+            GraphNodeType::SnapToGrid => "SnapToGrid",
+            GraphNodeType::Mirror => "Mirror",
+            GraphNodeType::Transform => "Transform",
+            GraphNodeType::Spherize => "Spherize",
+            GraphNodeType::Twist => "Twist",
+            GraphNodeType::Taper => "Taper",
+            GraphNodeType::Bend => "Bend",
+            GraphNodeType::Jitter => "Jitter",
+            GraphNodeType::Displace => "Displace",
+            GraphNodeType::Smooth => "Smooth",
+            GraphNodeType::Shrinkwrap => "Shrinkwrap",
+            GraphNodeType::Subdivide => "Subdivide",
+            GraphNodeType::Boolean => "Boolean",
+            GraphNodeType::Decimate => "Decimate",
             GraphNodeType::ExportObj => "ExportObj",
+            GraphNodeType::ExportGltf => "ExportGltf",
         }
     }
 }
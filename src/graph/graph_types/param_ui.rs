@@ -4,18 +4,27 @@ use egui::any::AnyMap;
 use egui::*;
 
 impl InputParam {
-    pub fn value_widget(&mut self, name: &str, ui: &mut Ui) {
+    /// Draws the widget to edit the value of this parameter. Returns whether
+    /// the value was changed.
+    pub fn value_widget(&mut self, name: &str, ui: &mut Ui) -> bool {
+        let mut changed = false;
         match &mut self.value {
             InputParamValue::Vector(vector) => {
                 ui.label(name);
 
                 ui.horizontal(|ui| {
-                    ui.label("x");
-                    ui.add(egui::DragValue::new(&mut vector.x).speed(0.1));
-                    ui.label("y");
-                    ui.add(egui::DragValue::new(&mut vector.y).speed(0.1));
-                    ui.label("z");
-                    ui.add(egui::DragValue::new(&mut vector.z).speed(0.1));
+                    ui.label("X");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut vector.x).speed(0.1))
+                        .changed();
+                    ui.label("Y");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut vector.y).speed(0.1))
+                        .changed();
+                    ui.label("Z");
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut vector.z).speed(0.1))
+                        .changed();
                 });
             }
             InputParamValue::Scalar(scalar) => {
@@ -35,20 +44,28 @@ impl InputParam {
                 }
                 ui.horizontal(|ui| {
                     ui.label(name);
-                    ui.add(Slider::new(scalar, min..=max));
+                    changed |= ui.add(Slider::new(scalar, min..=max)).changed();
                 });
             }
             InputParamValue::Selection { text, selection } => {
-                if ui.text_edit_singleline(text).changed() {
-                    *selection = text
-                        .split(",")
-                        .map(|x| {
-                            u32::from_str_radix(x, 10)
-                                .map_err(|_| anyhow::anyhow!("Cannot parse number"))
-                        })
-                        .collect::<Result<Vec<_>>>()
-                        .ok();
-                }
+                ui.horizontal(|ui| {
+                    ui.label(name);
+                    if ui.text_edit_singleline(text).changed() {
+                        changed = true;
+                        *selection = text
+                            .split(",")
+                            .map(|x| {
+                                u32::from_str_radix(x, 10)
+                                    .map_err(|_| anyhow::anyhow!("Cannot parse number"))
+                            })
+                            .collect::<Result<Vec<_>>>()
+                            .ok();
+                    }
+                    match selection {
+                        Some(selection) => ui.label(format!("({})", selection.len())),
+                        None => ui.label("(invalid)"),
+                    };
+                });
             }
             InputParamValue::None => {
                 ui.label(name);
@@ -63,7 +80,9 @@ impl InputParam {
                     .selected_text(selected)
                     .show_ui(ui, |ui| {
                         for (idx, value) in values.iter().enumerate() {
-                            ui.selectable_value(selection, Some(idx as u32), value);
+                            changed |= ui
+                                .selectable_value(selection, Some(idx as u32), value)
+                                .changed();
                         }
                     });
             }
@@ -72,6 +91,7 @@ impl InputParam {
                 ui.horizontal(|ui| {
                     if ui.button("Select").clicked() {
                         *path = rfd::FileDialog::new().save_file();
+                        changed = true;
                     }
                     if let Some(ref path) = path {
                         ui.label(
@@ -85,6 +105,17 @@ impl InputParam {
                     }
                 });
             }
+            InputParamValue::Color(color) => {
+                ui.horizontal(|ui| {
+                    ui.label(name);
+                    let mut rgba = Rgba::from_rgba_premultiplied(color.x, color.y, color.z, color.w);
+                    if ui.color_edit_button_rgba(&mut rgba).changed() {
+                        *color = Vec4::new(rgba.r(), rgba.g(), rgba.b(), rgba.a());
+                        changed = true;
+                    }
+                });
+            }
         }
+        changed
     }
 }
@@ -74,26 +74,56 @@ pub enum PolyAsmInstruction {
         center: MemAddr<Vec3>,
         normal: MemAddr<Vec3>,
         right: MemAddr<Vec3>,
-        size: MemAddr<Vec2>,
+        size: MemAddr<Vec3>,
         out_mesh: MemAddr<HalfEdgeMesh>,
     },
     ChamferVertices {
-        vertices: MemAddr<Vec<u32>>,
+        vertices: MemAddr<Selection>,
         amount: MemAddr<f32>,
         in_mesh: MemAddr<HalfEdgeMesh>,
         out_mesh: MemAddr<HalfEdgeMesh>,
+        out_faces: MemAddr<Selection>,
+    },
+    MakeGrid {
+        center: MemAddr<Vec3>,
+        size: MemAddr<Vec3>,
+        divisions_x: MemAddr<f32>,
+        divisions_z: MemAddr<f32>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    MakeLine {
+        start: MemAddr<Vec3>,
+        end: MemAddr<Vec3>,
+        segments: MemAddr<f32>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    MakeUVSphere {
+        center: MemAddr<Vec3>,
+        radius: MemAddr<f32>,
+        segments: MemAddr<f32>,
+        rings: MemAddr<f32>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    MakeTorus {
+        center: MemAddr<Vec3>,
+        major_radius: MemAddr<f32>,
+        minor_radius: MemAddr<f32>,
+        major_segments: MemAddr<f32>,
+        minor_segments: MemAddr<f32>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
     },
     BevelEdges {
-        edges: MemAddr<Vec<u32>>,
+        edges: MemAddr<Selection>,
         amount: MemAddr<f32>,
         in_mesh: MemAddr<HalfEdgeMesh>,
         out_mesh: MemAddr<HalfEdgeMesh>,
     },
     ExtrudeFaces {
-        faces: MemAddr<Vec<u32>>,
+        faces: MemAddr<Selection>,
         amount: MemAddr<f32>,
         in_mesh: MemAddr<HalfEdgeMesh>,
         out_mesh: MemAddr<HalfEdgeMesh>,
+        out_faces: MemAddr<Selection>,
     },
     MakeVector {
         x: MemAddr<f32>,
@@ -116,16 +146,122 @@ pub enum PolyAsmInstruction {
         b: MemAddr<HalfEdgeMesh>,
         out_mesh: MemAddr<HalfEdgeMesh>,
     },
+    Revolve {
+        profile: MemAddr<HalfEdgeMesh>,
+        origin: MemAddr<Vec3>,
+        axis: MemAddr<Vec3>,
+        angle: MemAddr<f32>,
+        steps: MemAddr<f32>,
+        height: MemAddr<f32>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    SnapToGrid {
+        cell: MemAddr<Vec3>,
+        merge_distance: MemAddr<f32>,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Mirror {
+        axis: Axis,
+        origin: MemAddr<Vec3>,
+        weld_threshold: MemAddr<f32>,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Transform {
+        translation: MemAddr<Vec3>,
+        rotation: MemAddr<Vec3>,
+        scale: MemAddr<Vec3>,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Spherize {
+        factor: MemAddr<f32>,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Twist {
+        axis: Axis,
+        angle_per_unit: MemAddr<f32>,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Taper {
+        axis: Axis,
+        factor: MemAddr<f32>,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Bend {
+        axis: Axis,
+        angle: MemAddr<f32>,
+        start: MemAddr<f32>,
+        end: MemAddr<f32>,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Jitter {
+        amount: MemAddr<f32>,
+        seed: MemAddr<f32>,
+        direction: halfedge::deform::JitterDirection,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Displace {
+        frequency: MemAddr<f32>,
+        amplitude: MemAddr<f32>,
+        seed: MemAddr<f32>,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Smooth {
+        passes: MemAddr<f32>,
+        lambda: MemAddr<f32>,
+        mu: MemAddr<f32>,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Shrinkwrap {
+        mode: halfedge::deform::ShrinkwrapMode,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        target: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Subdivide {
+        scheme: halfedge::subdivision::SubdivisionScheme,
+        iterations: MemAddr<f32>,
+        boundary: halfedge::subdivision::BoundaryMode,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Boolean {
+        op: halfedge::boolean::BooleanOp,
+        a: MemAddr<HalfEdgeMesh>,
+        b: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
+    Decimate {
+        threshold: MemAddr<f32>,
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        out_mesh: MemAddr<HalfEdgeMesh>,
+    },
     ExportObj {
         in_mesh: MemAddr<HalfEdgeMesh>,
         export_path: MemAddr<std::path::PathBuf>,
-    }
+    },
+    ExportGltf {
+        in_mesh: MemAddr<HalfEdgeMesh>,
+        export_path: MemAddr<std::path::PathBuf>,
+    },
 }
 
 pub struct PolyAsmProgram {
     instructions: Vec<PolyAsmInstruction>,
     output_register: Option<MemAddr<HalfEdgeMesh>>,
     memory: hecs::World,
+    /// When set, edit operations record debug marks on the meshes they
+    /// produce. See [`HalfEdgeMesh::add_debug_vertex`].
+    debug_marks: bool,
 }
 
 impl PolyAsmProgram {
@@ -135,9 +271,22 @@ impl PolyAsmProgram {
             instructions: vec![],
             output_register: None,
             memory: world,
+            debug_marks: false,
         }
     }
 
+    /// Enables recording debug marks during execution. Disabled by default.
+    pub fn set_debug_marks(&mut self, enabled: bool) {
+        self.debug_marks = enabled;
+    }
+
+    /// Clears the marks left by previous operations on a mesh that is about
+    /// to be edited, so only the marks of the last operation are displayed.
+    fn reset_debug_marks(&self, mesh: &mut HalfEdgeMesh) {
+        mesh.clear_debug_marks();
+        mesh.set_debug_marks_enabled(self.debug_marks);
+    }
+
     pub fn mem_alloc_raw<T: Send + Sync + 'static>(&mut self, value: T) -> RawMemAddr {
         self.memory.spawn((value,))
     }
@@ -185,7 +334,14 @@ impl PolyAsmProgram {
         self.instructions.push(op);
     }
 
-    pub fn execute_instruction(&mut self, instr: PolyAsmInstruction) -> Result<()> {
+    /// Executes a single instruction. The `progress` of the instruction is
+    /// reported by the operations that can take a long time, which also stop
+    /// early when it asks to cancel.
+    pub fn execute_instruction(
+        &mut self,
+        instr: PolyAsmInstruction,
+        progress: Progress,
+    ) -> Result<()> {
         match &instr {
             PolyAsmInstruction::MakeCube {
                 origin: center,
@@ -207,7 +363,9 @@ impl PolyAsmProgram {
                 let center = self.mem_fetch(*center)?;
                 let normal = self.mem_fetch(*normal)?;
                 let right = self.mem_fetch(*right)?;
-                let size = self.mem_fetch(*size)?;
+                // The quad is flat, so only the first two components of the
+                // size vector are meaningful.
+                let size = self.mem_fetch(*size)?.truncate();
                 self.mem_store(
                     *out_mesh,
                     halfedge::primitives::Quad::build(center, normal, right, size),
@@ -219,24 +377,93 @@ impl PolyAsmProgram {
                 amount,
                 in_mesh,
                 out_mesh,
+                out_faces,
             } => {
                 let vertices = self.mem_fetch(*vertices)?;
                 let amount = self.mem_fetch(*amount)?;
                 let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
 
-                result.clear_debug();
-                let vs = result.iter_vertices().map(|x| x.0).collect::<Vec<_>>();
-                for vertex in vertices {
-                    let v_id = vs
-                        .get(vertex as usize)
-                        .cloned()
-                        .ok_or_else(|| anyhow!("Invalid index: {}", vertex))?;
-
-                    halfedge::edit_ops::chamfer_vertex(&mut result, v_id, amount)?;
-                }
+                self.reset_debug_marks(&mut result);
+                let vertices = vertices.resolve_vertices(&result)?;
+                let new_faces =
+                    halfedge::edit_ops::chamfer_vertices(&mut result, &vertices, amount)?;
+                self.mem_store(*out_faces, Selection::Faces(result.id(), new_faces))?;
                 self.mem_store(*out_mesh, result)?;
                 self.output_register = Some(*out_mesh);
             }
+            PolyAsmInstruction::MakeGrid {
+                center,
+                size,
+                divisions_x,
+                divisions_z,
+                out_mesh,
+            } => {
+                let center = self.mem_fetch(*center)?;
+                // The grid lies on the XZ plane, so the Y component of the
+                // size vector is ignored.
+                let size = self.mem_fetch(*size)?;
+                let divisions_x = self.mem_fetch(*divisions_x)?.round().max(1.0) as usize;
+                let divisions_z = self.mem_fetch(*divisions_z)?.round().max(1.0) as usize;
+                let mesh = halfedge::primitives::Grid::build(
+                    center,
+                    Vec2::new(size.x, size.z),
+                    divisions_x,
+                    divisions_z,
+                );
+                self.mem_store(*out_mesh, mesh)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::MakeLine {
+                start,
+                end,
+                segments,
+                out_mesh,
+            } => {
+                let start = self.mem_fetch(*start)?;
+                let end = self.mem_fetch(*end)?;
+                let segments = self.mem_fetch(*segments)?.round().max(1.0) as usize;
+                let mesh = halfedge::primitives::Line::build(start, end, segments);
+                self.mem_store(*out_mesh, mesh)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::MakeUVSphere {
+                center,
+                radius,
+                segments,
+                rings,
+                out_mesh,
+            } => {
+                let center = self.mem_fetch(*center)?;
+                let radius = self.mem_fetch(*radius)?;
+                let segments = self.mem_fetch(*segments)?.round().max(3.0) as usize;
+                let rings = self.mem_fetch(*rings)?.round().max(2.0) as usize;
+                let mesh = halfedge::primitives::UVSphere::build(center, radius, segments, rings);
+                self.mem_store(*out_mesh, mesh)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::MakeTorus {
+                center,
+                major_radius,
+                minor_radius,
+                major_segments,
+                minor_segments,
+                out_mesh,
+            } => {
+                let center = self.mem_fetch(*center)?;
+                let major_radius = self.mem_fetch(*major_radius)?;
+                let minor_radius = self.mem_fetch(*minor_radius)?;
+                let major_segments = self.mem_fetch(*major_segments)?.round().max(3.0) as usize;
+                let minor_segments = self.mem_fetch(*minor_segments)?.round().max(3.0) as usize;
+                let mesh = halfedge::primitives::Torus::build(
+                    center,
+                    major_radius,
+                    minor_radius,
+                    major_segments,
+                    minor_segments,
+                );
+                self.mem_store(*out_mesh, mesh)?;
+                self.output_register = Some(*out_mesh);
+            }
             PolyAsmInstruction::BevelEdges {
                 edges,
                 amount,
@@ -247,16 +474,8 @@ impl PolyAsmProgram {
                 let amount = self.mem_fetch(*amount)?;
                 let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
 
-                result.clear_debug();
-                let hs = result.iter_halfedges().map(|x| x.0).collect::<Vec<_>>();
-                let edges_to_bevel = edges
-                    .iter()
-                    .map(|idx| {
-                        hs.get(*idx as usize)
-                            .cloned()
-                            .ok_or_else(|| anyhow!("Invalid index: {}", idx))
-                    })
-                    .collect::<Result<Vec<_>>>()?;
+                self.reset_debug_marks(&mut result);
+                let edges_to_bevel = edges.resolve_halfedges(&result)?;
                 halfedge::edit_ops::bevel_edges(&mut result, &edges_to_bevel, amount)?;
 
                 self.mem_store(*out_mesh, result)?;
@@ -267,23 +486,18 @@ impl PolyAsmProgram {
                 amount,
                 in_mesh,
                 out_mesh,
+                out_faces,
             } => {
                 let faces = self.mem_fetch(*faces)?;
                 let amount = self.mem_fetch(*amount)?;
                 let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
 
-                result.clear_debug();
-                let fs = result.iter_faces().map(|x| x.0).collect::<Vec<_>>();
-                let faces_to_extrude = faces
-                    .iter()
-                    .map(|idx| {
-                        fs.get(*idx as usize)
-                            .cloned()
-                            .ok_or_else(|| anyhow!("Invalid index: {}", idx))
-                    })
-                    .collect::<Result<Vec<_>>>()?;
+                self.reset_debug_marks(&mut result);
+                let faces_to_extrude = faces.resolve_faces(&result)?;
                 halfedge::edit_ops::extrude_faces(&mut result, &faces_to_extrude, amount)?;
 
+                // The extruded faces keep their ids
+                self.mem_store(*out_faces, Selection::Faces(result.id(), faces_to_extrude))?;
                 self.mem_store(*out_mesh, result)?;
                 self.output_register = Some(*out_mesh);
             }
@@ -310,26 +524,321 @@ impl PolyAsmProgram {
                     let mesh_b = &*self.mem_fetch_ref(*b)?;
 
                     let mut result = mesh_a.clone();
-                    result.clear_debug();
+                    self.reset_debug_marks(&mut result);
                     result.merge_with(mesh_b);
                     result
                 };
                 self.mem_store(*out_mesh, result)?;
                 self.output_register = Some(*out_mesh);
             }
+            PolyAsmInstruction::Revolve {
+                profile,
+                origin,
+                axis,
+                angle,
+                steps,
+                height,
+                out_mesh,
+            } => {
+                let origin = self.mem_fetch(*origin)?;
+                let axis = self.mem_fetch(*axis)?;
+                let angle = self.mem_fetch(*angle)?;
+                let steps = self.mem_fetch(*steps)?.round().max(1.0) as u32;
+                let height = self.mem_fetch(*height)?;
+                let points = self
+                    .mem_fetch_ref(*profile)?
+                    .polyline_points()
+                    .map_err(|err| anyhow!("Revolve: Invalid profile. {}", err))?;
+
+                let mut result =
+                    halfedge::edit_ops::screw(&points, origin, axis, angle, steps, height)?;
+                self.reset_debug_marks(&mut result);
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::SnapToGrid {
+                cell,
+                merge_distance,
+                in_mesh,
+                out_mesh,
+            } => {
+                let cell = self.mem_fetch(*cell)?;
+                let merge_distance = self.mem_fetch(*merge_distance)?;
+                let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
+
+                self.reset_debug_marks(&mut result);
+                halfedge::edit_ops::snap_to_grid(&mut result, cell);
+                // A merge distance of zero disables merging
+                if merge_distance > 0.0 {
+                    halfedge::edit_ops::merge_by_distance(&mut result, merge_distance)?;
+                }
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Mirror {
+                axis,
+                origin,
+                weld_threshold,
+                in_mesh,
+                out_mesh,
+            } => {
+                let origin = self.mem_fetch(*origin)?;
+                let weld_threshold = self.mem_fetch(*weld_threshold)?;
+                let mut result = halfedge::edit_ops::mirror(
+                    &*self.mem_fetch_ref(*in_mesh)?,
+                    origin,
+                    axis.to_vec3(),
+                    weld_threshold,
+                )?;
+
+                self.reset_debug_marks(&mut result);
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Transform {
+                translation,
+                rotation,
+                scale,
+                in_mesh,
+                out_mesh,
+            } => {
+                let translation = self.mem_fetch(*translation)?;
+                // The rotation is given as euler angles, in degrees
+                let rotation = self.mem_fetch(*rotation)?;
+                let rotation = Quat::from_euler(
+                    glam::EulerRot::XYZ,
+                    rotation.x.to_radians(),
+                    rotation.y.to_radians(),
+                    rotation.z.to_radians(),
+                );
+                let scale = self.mem_fetch(*scale)?;
+                let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
+
+                self.reset_debug_marks(&mut result);
+                halfedge::deform::transform_trs(&mut result, translation, rotation, scale)?;
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Spherize {
+                factor,
+                in_mesh,
+                out_mesh,
+            } => {
+                let factor = self.mem_fetch(*factor)?;
+                let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
+
+                self.reset_debug_marks(&mut result);
+                halfedge::deform::spherize(&mut result, factor)?;
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Twist {
+                axis,
+                angle_per_unit,
+                in_mesh,
+                out_mesh,
+            } => {
+                let angle_per_unit = self.mem_fetch(*angle_per_unit)?;
+                let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
+
+                self.reset_debug_marks(&mut result);
+                halfedge::deform::twist(&mut result, *axis, angle_per_unit);
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Taper {
+                axis,
+                factor,
+                in_mesh,
+                out_mesh,
+            } => {
+                let factor = self.mem_fetch(*factor)?;
+                let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
+
+                self.reset_debug_marks(&mut result);
+                halfedge::deform::taper(&mut result, *axis, factor);
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Bend {
+                axis,
+                angle,
+                start,
+                end,
+                in_mesh,
+                out_mesh,
+            } => {
+                let angle = self.mem_fetch(*angle)?;
+                let bounds = (self.mem_fetch(*start)?, self.mem_fetch(*end)?);
+                let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
+
+                self.reset_debug_marks(&mut result);
+                halfedge::deform::bend(&mut result, *axis, angle, bounds)?;
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Jitter {
+                amount,
+                seed,
+                direction,
+                in_mesh,
+                out_mesh,
+            } => {
+                let amount = self.mem_fetch(*amount)?;
+                let seed = self.mem_fetch(*seed)?.round().max(0.0) as u64;
+                let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
+
+                self.reset_debug_marks(&mut result);
+                halfedge::deform::jitter_vertices(&mut result, amount, seed, *direction);
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Displace {
+                frequency,
+                amplitude,
+                seed,
+                in_mesh,
+                out_mesh,
+            } => {
+                let frequency = self.mem_fetch(*frequency)?;
+                let amplitude = self.mem_fetch(*amplitude)?;
+                let seed = self.mem_fetch(*seed)?.round().max(0.0) as u32;
+                let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
+
+                self.reset_debug_marks(&mut result);
+                halfedge::deform::displace_noise(&mut result, frequency, amplitude, seed);
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Smooth {
+                passes,
+                lambda,
+                mu,
+                in_mesh,
+                out_mesh,
+            } => {
+                let passes = self.mem_fetch(*passes)?.round().max(0.0) as u32;
+                let lambda = self.mem_fetch(*lambda)?;
+                let mu = self.mem_fetch(*mu)?;
+                let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
+
+                self.reset_debug_marks(&mut result);
+                halfedge::deform::taubin_smooth(&mut result, lambda, mu, passes)?;
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Shrinkwrap {
+                mode,
+                in_mesh,
+                target,
+                out_mesh,
+            } => {
+                let result = {
+                    // Extra scope required to not keep refs alive
+                    let target = &*self.mem_fetch_ref(*target)?;
+                    let mut result = (*self.mem_fetch_ref(*in_mesh)?).clone();
+
+                    self.reset_debug_marks(&mut result);
+                    halfedge::deform::shrinkwrap(&mut result, target, *mode)?;
+                    result
+                };
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Subdivide {
+                scheme,
+                iterations,
+                boundary,
+                in_mesh,
+                out_mesh,
+            } => {
+                use halfedge::subdivision::{self, SubdivisionScheme};
+                let iterations = self.mem_fetch(*iterations)?.round().max(0.0) as u32;
+                let mut result = {
+                    let mesh = &*self.mem_fetch_ref(*in_mesh)?;
+                    match scheme {
+                        SubdivisionScheme::CatmullClark => {
+                            subdivision::catmull_clark_subdivide_with_progress(
+                                mesh, iterations, *boundary, progress,
+                            )?
+                        }
+                        SubdivisionScheme::Loop => subdivision::loop_subdivide_with_progress(
+                            mesh, iterations, *boundary, progress,
+                        )?,
+                        SubdivisionScheme::Linear => {
+                            subdivision::linear_subdivide_with_progress(mesh, iterations, progress)?
+                        }
+                    }
+                };
+
+                self.reset_debug_marks(&mut result);
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Boolean { op, a, b, out_mesh } => {
+                let mut result = {
+                    // Extra scope required to not keep refs alive
+                    let mesh_a = &*self.mem_fetch_ref(*a)?;
+                    let mesh_b = &*self.mem_fetch_ref(*b)?;
+                    halfedge::boolean::boolean_with_progress(mesh_a, mesh_b, *op, progress)?
+                };
+                self.reset_debug_marks(&mut result);
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
+            PolyAsmInstruction::Decimate {
+                threshold,
+                in_mesh,
+                out_mesh,
+            } => {
+                let threshold = self.mem_fetch(*threshold)?;
+                let mut result = {
+                    let mesh = &*self.mem_fetch_ref(*in_mesh)?;
+                    halfedge::edit_ops::decimate_short_edges_with_progress(
+                        mesh, threshold, progress,
+                    )?
+                };
+                self.reset_debug_marks(&mut result);
+                self.mem_store(*out_mesh, result)?;
+                self.output_register = Some(*out_mesh);
+            }
             PolyAsmInstruction::ExportObj { in_mesh, export_path } => {
                 let mesh = &*self.mem_fetch_ref(*in_mesh)?;
                 let export_path = self.mem_fetch(*export_path)?;
                 mesh.to_wavefront_obj(export_path)?;
             }
+            PolyAsmInstruction::ExportGltf {
+                in_mesh,
+                export_path,
+            } => {
+                let mesh = &*self.mem_fetch_ref(*in_mesh)?;
+                let export_path = self.mem_fetch(*export_path)?;
+                halfedge::io::gltf::export(mesh, &export_path)?;
+            }
         }
         Ok(())
     }
 
-    pub fn execute(mut self) -> Result<HalfEdgeMesh> {
+    pub fn execute(self) -> Result<HalfEdgeMesh> {
+        self.execute_with_progress(&halfedge::progress::no_progress)
+    }
+
+    /// Same as [`PolyAsmProgram::execute`], but reports the progress of the
+    /// whole program, and can be cancelled. Each instruction takes an equal
+    /// share of the progress. See [`Progress`].
+    pub fn execute_with_progress(mut self, progress: Progress) -> Result<HalfEdgeMesh> {
         let instructions = self.instructions.clone();
-        for instruction in instructions.into_iter() {
-            self.execute_instruction(instruction)?;
+        let num_instructions = instructions.len() as f32;
+        for (i, instruction) in instructions.into_iter().enumerate() {
+            let instruction_progress = halfedge::progress::scaled(
+                progress,
+                i as f32 / num_instructions,
+                (i + 1) as f32 / num_instructions,
+            );
+            // Checks for cancellation between instructions, since most of
+            // them don't report any progress.
+            halfedge::progress::report(&instruction_progress, 0.0)?;
+            self.execute_instruction(instruction, &instruction_progress)?;
         }
 
         if let Some(output_register) = self.output_register {
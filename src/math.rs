@@ -25,3 +25,182 @@ impl ToVec<glam::Vec3> for Vec3Ord {
         glam::Vec3::new(self.0[0].0, self.0[1].0, self.0[2].0)
     }
 }
+
+/// One of the three coordinate axes.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    Hash,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::AsRefStr,
+)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+impl Axis {
+    /// Returns the unit vector pointing along this axis.
+    pub fn to_vec3(self) -> glam::Vec3 {
+        match self {
+            Axis::X => glam::Vec3::X,
+            Axis::Y => glam::Vec3::Y,
+            Axis::Z => glam::Vec3::Z,
+        }
+    }
+
+    /// Returns the next axis in (X, Y, Z) order, wrapping around from Z to X.
+    pub fn next(self) -> Axis {
+        match self {
+            Axis::X => Axis::Y,
+            Axis::Y => Axis::Z,
+            Axis::Z => Axis::X,
+        }
+    }
+
+    /// Returns the two axes perpendicular to this one, in an order such that
+    /// they form a right-handed frame with this axis: (Y, Z) for X, (Z, X)
+    /// for Y and (X, Y) for Z.
+    pub fn perpendicular(self) -> (Axis, Axis) {
+        (self.next(), self.next().next())
+    }
+
+    /// Returns the coordinate of `p` along this axis.
+    pub fn component(self, p: glam::Vec3) -> f32 {
+        match self {
+            Axis::X => p.x,
+            Axis::Y => p.y,
+            Axis::Z => p.z,
+        }
+    }
+
+    /// Projects `p` onto this axis, i.e. keeps only its coordinate along the
+    /// axis and zeroes the other two.
+    pub fn project_onto(self, p: glam::Vec3) -> glam::Vec3 {
+        self.to_vec3() * self.component(p)
+    }
+
+    /// Projects `p` onto the plane through the origin perpendicular to this
+    /// axis, i.e. zeroes its coordinate along the axis.
+    pub fn project_off(self, p: glam::Vec3) -> glam::Vec3 {
+        p - self.project_onto(p)
+    }
+
+    /// Mirrors `p` across the plane through the origin perpendicular to this
+    /// axis, i.e. negates its coordinate along the axis.
+    pub fn mirror(self, p: glam::Vec3) -> glam::Vec3 {
+        p - 2.0 * self.project_onto(p)
+    }
+}
+
+/// Scrambles the bits of `x`, like the SplitMix64 generator does. Nearby
+/// inputs give unrelated outputs, so hashing an id together with a seed gives
+/// a pseudo-random number that's stable across runs.
+pub fn hash_u64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Maps a hash, as returned by [`hash_u64`], to a float in the [0, 1) range.
+pub fn hash_to_unit_f32(hash: u64) -> f32 {
+    // Floats have 24 bits of precision
+    (hash >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Evaluates 3D gradient noise, in the style of Perlin noise, at `p`. The
+/// result is a smooth function of `p` in the [-1, 1] range, which is zero at
+/// points with integer coordinates. Different seeds give unrelated noise.
+pub fn gradient_noise(p: glam::Vec3, seed: u64) -> f32 {
+    // Perlin's gradients: The directions towards the edges of a cube
+    const GRADIENTS: [[f32; 3]; 12] = [
+        [1.0, 1.0, 0.0],
+        [-1.0, 1.0, 0.0],
+        [1.0, -1.0, 0.0],
+        [-1.0, -1.0, 0.0],
+        [1.0, 0.0, 1.0],
+        [-1.0, 0.0, 1.0],
+        [1.0, 0.0, -1.0],
+        [-1.0, 0.0, -1.0],
+        [0.0, 1.0, 1.0],
+        [0.0, -1.0, 1.0],
+        [0.0, 1.0, -1.0],
+        [0.0, -1.0, -1.0],
+    ];
+
+    let cell = p.floor();
+    let offset = p - cell;
+    let corner = |dx: f32, dy: f32, dz: f32| {
+        let hash = [cell.x + dx, cell.y + dy, cell.z + dz]
+            .iter()
+            .fold(hash_u64(seed), |hash, c| hash_u64(hash ^ *c as i64 as u64));
+        let gradient = glam::Vec3::from(GRADIENTS[(hash % 12) as usize]);
+        gradient.dot(offset - glam::Vec3::new(dx, dy, dz))
+    };
+
+    // Quintic interpolation, so the noise has continuous derivatives
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let lerp = |a: f32, b: f32, t: f32| a + (b - a) * t;
+    let (u, v, w) = (fade(offset.x), fade(offset.y), fade(offset.z));
+    lerp(
+        lerp(
+            lerp(corner(0.0, 0.0, 0.0), corner(1.0, 0.0, 0.0), u),
+            lerp(corner(0.0, 1.0, 0.0), corner(1.0, 1.0, 0.0), u),
+            v,
+        ),
+        lerp(
+            lerp(corner(0.0, 0.0, 1.0), corner(1.0, 0.0, 1.0), u),
+            lerp(corner(0.0, 1.0, 1.0), corner(1.0, 1.0, 1.0), u),
+            v,
+        ),
+        w,
+    )
+    .clamp(-1.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glam::Vec3;
+
+    #[test]
+    fn test_axis_helpers() {
+        let p = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(Axis::Y.component(p), 2.0);
+        assert_eq!(Axis::Y.project_onto(p), Vec3::new(0.0, 2.0, 0.0));
+        assert_eq!(Axis::Y.project_off(p), Vec3::new(1.0, 0.0, 3.0));
+        assert_eq!(Axis::Z.mirror(p), Vec3::new(1.0, 2.0, -3.0));
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            let (a, b) = axis.perpendicular();
+            assert_eq!(a.to_vec3().cross(b.to_vec3()), axis.to_vec3());
+        }
+    }
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash_u64(42), hash_u64(42));
+        assert_ne!(hash_u64(42), hash_u64(43));
+        for i in 0..100 {
+            let x = hash_to_unit_f32(hash_u64(i));
+            assert!((0.0..1.0).contains(&x));
+        }
+        assert!(hash_to_unit_f32(u64::MAX) < 1.0);
+    }
+
+    #[test]
+    fn test_gradient_noise() {
+        assert_eq!(gradient_noise(Vec3::new(1.0, -2.0, 3.0), 7), 0.0);
+        let p = Vec3::new(0.3, 1.7, -2.2);
+        assert_eq!(gradient_noise(p, 7), gradient_noise(p, 7));
+        assert_ne!(gradient_noise(p, 7), gradient_noise(p, 8));
+        // The noise is smooth
+        let delta = gradient_noise(p + Vec3::X * 1e-3, 7) - gradient_noise(p, 7);
+        assert!(delta.abs() < 1e-2);
+    }
+}
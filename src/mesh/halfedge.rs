@@ -26,6 +26,47 @@ pub mod edit_ops;
 /// Import / Export of HalfEdgeMesh data structure to Wavefront OBJ files
 pub mod wavefront_obj;
 
+/// Import / Export of HalfEdgeMesh data structure to other file formats
+pub mod io;
+
+/// Texture coordinate generation, like spherical or cylindrical projections
+pub mod uv;
+
+/// Convex hull generation from point sets, like for collision proxies
+pub mod convex_hull;
+
+/// Boolean operations (union, difference, intersection) between meshes
+pub mod boolean;
+
+/// Deformers that move the vertices of a mesh, like spherize, twist or bend
+pub mod deform;
+
+/// Progress reporting and cancellation for long-running operations
+pub mod progress;
+pub use progress::{Cancelled, Progress};
+
+/// Limits on the size of the meshes produced by operations
+pub mod limits;
+
+/// Subdivision surfaces, like Catmull-Clark
+pub mod subdivision;
+
+/// Ray intersection tests against the faces of a mesh
+pub mod raycast;
+pub use raycast::RayHit;
+
+/// Read-only queries that summarize information about the mesh
+pub mod queries;
+pub use queries::{MeshStats, ValidationError};
+
+/// Sets of mesh elements that edit operations work on
+pub mod selection;
+pub use selection::Selection;
+
+/// A read-only interface to meshes, for code that doesn't need to edit them
+pub mod mesh_query;
+pub use mesh_query::MeshQuery;
+
 /// HalfEdge meshes are a type of linked list. This means it is sometimes
 /// impossible to ensure some algorithms will terminate when the mesh is
 /// malformed. To ensure the code never goes into an infinite loop, this max
@@ -88,6 +129,57 @@ pub struct HalfEdgeMesh {
 
     debug_edges: HashMap<HalfEdgeId, DebugMark>,
     debug_vertices: HashMap<VertexId, DebugMark>,
+    /// Debug marks are only recorded when this is set. Disabled by default,
+    /// so that the marks added by edit operations don't accumulate when
+    /// nobody is going to look at them.
+    debug_marks_enabled: bool,
+
+    /// The material assigned to each face. Faces not present here use the
+    /// default material, 0.
+    face_materials: HashMap<FaceId, u32>,
+
+    /// An optional per-vertex color attribute, in linear RGB.
+    vertex_colors: HashMap<VertexId, Vec3>,
+
+    /// An optional texture coordinate attribute. UVs are stored per corner,
+    /// that is, per halfedge, and refer to the halfedge's source vertex. This
+    /// lets a vertex have different UVs on each of its faces, as in seams.
+    halfedge_uvs: HashMap<HalfEdgeId, Vec2>,
+
+    /// The crease weight of the edges, from 0.0 (smooth) to 1.0 (sharp),
+    /// used by subdivision. Stored for both halfedges of each edge. Edges not
+    /// present here are smooth.
+    edge_creases: HashMap<HalfEdgeId, f32>,
+
+    /// Acceleration structure for raycasts. Must be invalidated whenever the
+    /// mesh is modified.
+    bvh_cache: raycast::BvhCache,
+
+    /// Shared with the clones of this mesh. See [`MeshId`].
+    id: MeshId,
+}
+
+/// Identifies a mesh along with the copies made of it by `clone`, which keep
+/// the same element ids. Meshes created in any other way, including
+/// [`HalfEdgeMesh::duplicate`], get a new one. This tells apart element ids
+/// coming from unrelated meshes, which can otherwise look valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MeshId(u64);
+
+impl Default for MeshId {
+    fn default() -> Self {
+        static NEXT_ID: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        MeshId(NEXT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed))
+    }
+}
+
+/// Maps the ids of a mesh to the ids of the corresponding elements in
+/// another mesh. See [`HalfEdgeMesh::duplicate`].
+#[derive(Debug, Clone, Default)]
+pub struct IdMap {
+    pub vertices: HashMap<VertexId, VertexId>,
+    pub halfedges: HashMap<HalfEdgeId, HalfEdgeId>,
+    pub faces: HashMap<FaceId, FaceId>,
 }
 
 pub type SVec<T> = SmallVec<[T; 4]>;
@@ -416,49 +508,7 @@ impl HalfEdgeMesh {
             mesh[v].halfedge = Some(h);
         }
 
-        // Construct the boundary halfedges. Right now, the boundary consists of
-        // incomplete edges, i.e. half edges that do not have a twin. Leaving it
-        // like this would complicate some kinds of traversal because we can't
-        // rely on halfedges always having a twin. We will instead create
-        // boundary half edges: That is, twin halfedges that do not point to any
-        // face. The boundary halfedges are linked following a circle around the
-        // closed boundary. It's easier to imagine this by thinking of a hole in
-        // the mesh, but it works just as well if you think about the "outside"
-        // of a quad grid as a hole, as the loop would go all around the quad
-
-        // Clone to avoid double-borrow issues
-        // TODO: Again, this could be optimized. Don't care for now.
-        let halfedges: Vec<HalfEdgeId> = mesh.iter_halfedges().map(|(h, _)| h).collect();
-
-        for &h0 in halfedges.iter() {
-            let mut boundary_halfedges = Vec::<HalfEdgeId>::new();
-            if mesh[h0].twin.is_none() {
-                let mut h_it = h0;
-                loop {
-                    let t = mesh.alloc_halfedge(HalfEdge::default());
-                    boundary_halfedges.push(t);
-                    mesh[h_it].twin = Some(t);
-                    mesh[t].twin = Some(h_it);
-                    mesh[t].vertex = Some(mesh.at_halfedge(h_it).next().vertex().end());
-
-                    // Look for the next outgoing halfedge for this vertex
-                    // that's in the boundary
-                    h_it = mesh.at_halfedge(h_it).next().end();
-                    while h_it != h0 && mesh[h_it].twin.is_some() {
-                        // Twin-next cycles around the outgoing halfedges of a vertex
-                        h_it = mesh.at_halfedge(h_it).twin().next().end();
-                    }
-
-                    if h_it == h0 {
-                        break;
-                    }
-                }
-            }
-
-            for (&b_h, &b_h_next) in boundary_halfedges.iter().rev().circular_tuple_windows() {
-                mesh[b_h].next = Some(b_h_next);
-            }
-        }
+        mesh.add_boundary_halfedges();
 
         // Cycle the halfedge pointers for vertices again. Original code says it
         // makes this to make "traversal easier" :shrug:
@@ -499,6 +549,51 @@ impl HalfEdgeMesh {
         Ok(mesh)
     }
 
+    /// Constructs the boundary halfedges. Before this, the boundary consists of
+    /// incomplete edges, i.e. half edges that do not have a twin. Leaving it
+    /// like this would complicate some kinds of traversal because we can't
+    /// rely on halfedges always having a twin. We will instead create
+    /// boundary half edges: That is, twin halfedges that do not point to any
+    /// face. The boundary halfedges are linked following a circle around the
+    /// closed boundary. It's easier to imagine this by thinking of a hole in
+    /// the mesh, but it works just as well if you think about the "outside"
+    /// of a quad grid as a hole, as the loop would go all around the quad
+    pub(super) fn add_boundary_halfedges(&mut self) {
+        // Clone to avoid double-borrow issues
+        // TODO: Again, this could be optimized. Don't care for now.
+        let halfedges: Vec<HalfEdgeId> = self.iter_halfedges().map(|(h, _)| h).collect();
+
+        for &h0 in halfedges.iter() {
+            let mut boundary_halfedges = Vec::<HalfEdgeId>::new();
+            if self[h0].twin.is_none() {
+                let mut h_it = h0;
+                loop {
+                    let t = self.alloc_halfedge(HalfEdge::default());
+                    boundary_halfedges.push(t);
+                    self[h_it].twin = Some(t);
+                    self[t].twin = Some(h_it);
+                    self[t].vertex = Some(self.at_halfedge(h_it).next().vertex().end());
+
+                    // Look for the next outgoing halfedge for this vertex
+                    // that's in the boundary
+                    h_it = self.at_halfedge(h_it).next().end();
+                    while h_it != h0 && self[h_it].twin.is_some() {
+                        // Twin-next cycles around the outgoing halfedges of a vertex
+                        h_it = self.at_halfedge(h_it).twin().next().end();
+                    }
+
+                    if h_it == h0 {
+                        break;
+                    }
+                }
+            }
+
+            for (&b_h, &b_h_next) in boundary_halfedges.iter().rev().circular_tuple_windows() {
+                self[b_h].next = Some(b_h_next);
+            }
+        }
+    }
+
     /// Reverses the direction of the halfedges in a face.
     /// NOTE: This breaks manifoldness. Do not do it unless you know what you're doing.
     fn flip_face(&mut self, face_id: FaceId) {
@@ -549,6 +644,15 @@ impl HalfEdgeMesh {
         self.halfedges.iter().map(|(idx, h)| (HalfEdgeId(idx), h))
     }
 
+    /// Iterates the edges of the mesh, that is, pairs of twin halfedges.
+    /// Returns one of the two halfedges for each edge. Halfedges without a
+    /// twin are returned on their own.
+    pub fn iter_edges(&self) -> impl Iterator<Item = HalfEdgeId> + '_ {
+        self.iter_halfedges()
+            .filter(|(h, halfedge)| halfedge.twin.map(|twin| *h < twin).unwrap_or(true))
+            .map(|(h, _)| h)
+    }
+
     /// Sets the position for a given vertex
     pub fn set_vertex_position(&mut self, vertex: VertexId, position: Vec3) {
         self.vertex_mut(vertex).unwrap().position = position;
@@ -567,36 +671,46 @@ impl HalfEdgeMesh {
 
     /// Adds a new vertex to the mesh, disconnected from everything else. Returns its handle.
     fn alloc_vertex(&mut self, position: Vec3, halfedge: Option<HalfEdgeId>) -> VertexId {
+        self.bvh_cache.invalidate();
         VertexId(self.vertices.insert(Vertex { position, halfedge }))
     }
 
     /// Adds a new face to the mesh, disconnected from everything else. Returns its handle.
     fn alloc_face(&mut self, halfedge: Option<HalfEdgeId>) -> FaceId {
+        self.bvh_cache.invalidate();
         FaceId(self.faces.insert(Face { halfedge }))
     }
 
     /// Removes a face from the mesh. This does not attempt to preserve mesh
     /// connectivity and should only be used as part of internal operations.
     fn remove_face(&mut self, face: FaceId) {
+        self.bvh_cache.invalidate();
         self.faces.remove(face.0);
+        self.face_materials.remove(&face);
     }
 
     /// Removes a halfedge from the mesh. This does not attempt to preserve mesh
     /// connectivity and should only be used as part of internal operations.
     fn remove_halfedge(&mut self, halfedge: HalfEdgeId) {
+        self.bvh_cache.invalidate();
         self.halfedges.remove(halfedge.0);
         self.debug_edges.remove(&halfedge);
+        self.halfedge_uvs.remove(&halfedge);
+        self.edge_creases.remove(&halfedge);
     }
 
     /// Removes a vertex from the mesh. This does not attempt to preserve mesh
     /// connectivity and should only be used as part of internal operations.
     fn remove_vertex(&mut self, vertex: VertexId) {
+        self.bvh_cache.invalidate();
         self.vertices.remove(vertex.0);
         self.debug_vertices.remove(&vertex);
+        self.vertex_colors.remove(&vertex);
     }
 
     /// Adds a new vertex to the mesh, disconnected from everything else. Returns its handle.
     fn alloc_halfedge(&mut self, halfedge: HalfEdge) -> HalfEdgeId {
+        self.bvh_cache.invalidate();
         HalfEdgeId(self.halfedges.insert(halfedge))
     }
 
@@ -604,7 +718,12 @@ impl HalfEdgeMesh {
         self.debug_vertices.get(&vertex).cloned()
     }
 
+    /// Adds a debug mark to the vertex. Does nothing unless debug marks are
+    /// enabled for this mesh.
     pub fn add_debug_vertex(&mut self, vertex: VertexId, mark: DebugMark) {
+        if !self.debug_marks_enabled {
+            return;
+        }
         self.debug_vertices.insert(vertex, mark);
     }
 
@@ -612,7 +731,12 @@ impl HalfEdgeMesh {
         self.debug_edges.get(&edge).cloned()
     }
 
+    /// Adds a debug mark to the halfedge. Does nothing unless debug marks are
+    /// enabled for this mesh.
     pub fn add_debug_halfedge(&mut self, h: HalfEdgeId, mark: DebugMark) {
+        if !self.debug_marks_enabled {
+            return;
+        }
         self.debug_edges.insert(h, mark);
     }
 
@@ -624,7 +748,76 @@ impl HalfEdgeMesh {
         self.debug_vertices.iter()
     }
 
-    pub fn clear_debug(&mut self) {
+    /// Returns the material id of a face. Faces use material 0 by default.
+    pub fn face_material(&self, face: FaceId) -> u32 {
+        self.face_materials.get(&face).cloned().unwrap_or(0)
+    }
+
+    /// Assigns a material id to the given face.
+    pub fn set_face_material(&mut self, face: FaceId, material: u32) {
+        self.face_materials.insert(face, material);
+    }
+
+    /// Returns the color of a vertex, if the mesh has a color attribute and
+    /// it was set for this vertex.
+    pub fn vertex_color(&self, vertex: VertexId) -> Option<Vec3> {
+        self.vertex_colors.get(&vertex).cloned()
+    }
+
+    /// Sets the color of a vertex, in linear RGB.
+    pub fn set_vertex_color(&mut self, vertex: VertexId, color: Vec3) {
+        self.vertex_colors.insert(vertex, color);
+    }
+
+    /// Returns whether any of the vertices in this mesh has a color.
+    pub fn has_vertex_colors(&self) -> bool {
+        !self.vertex_colors.is_empty()
+    }
+
+    /// Returns the UV of the face corner at the source vertex of `halfedge`,
+    /// if it was set.
+    pub fn halfedge_uv(&self, halfedge: HalfEdgeId) -> Option<Vec2> {
+        self.halfedge_uvs.get(&halfedge).cloned()
+    }
+
+    /// Sets the UV of the face corner at the source vertex of `halfedge`.
+    pub fn set_halfedge_uv(&mut self, halfedge: HalfEdgeId, uv: Vec2) {
+        self.halfedge_uvs.insert(halfedge, uv);
+    }
+
+    /// Returns whether any of the face corners in this mesh has a UV.
+    pub fn has_uvs(&self) -> bool {
+        !self.halfedge_uvs.is_empty()
+    }
+
+    /// Returns the crease weight of the edge `halfedge` belongs to. Edges are
+    /// smooth, i.e. 0.0, by default.
+    pub fn edge_crease(&self, halfedge: HalfEdgeId) -> f32 {
+        self.edge_creases.get(&halfedge).cloned().unwrap_or(0.0)
+    }
+
+    /// Sets the crease weight of the edge `halfedge` belongs to, which is
+    /// clamped to the [0, 1] range. A weight of 1.0 makes subdivision keep
+    /// the edge sharp.
+    pub fn set_edge_crease(&mut self, halfedge: HalfEdgeId, crease: f32) {
+        let crease = crease.clamp(0.0, 1.0);
+        self.edge_creases.insert(halfedge, crease);
+        if let Some(twin) = self[halfedge].twin {
+            self.edge_creases.insert(twin, crease);
+        }
+    }
+
+    /// Enables or disables recording debug marks. Existing marks are kept.
+    pub fn set_debug_marks_enabled(&mut self, enabled: bool) {
+        self.debug_marks_enabled = enabled;
+    }
+
+    pub fn debug_marks_enabled(&self) -> bool {
+        self.debug_marks_enabled
+    }
+
+    /// Removes all the debug marks in the mesh.
+    pub fn clear_debug_marks(&mut self) {
         self.debug_edges.clear();
         self.debug_vertices.clear();
     }
@@ -642,6 +835,11 @@ impl HalfEdgeMesh {
         face_vertices.iter().fold(Vec3::ZERO, |v1, v2| v1 + *v2) / face_vertices.len() as f32
     }
 
+    /// Returns the identity of this mesh. See [`MeshId`].
+    pub fn id(&self) -> MeshId {
+        self.id
+    }
+
     pub fn vertex_exists(&self, vertex: VertexId) -> bool {
         self.vertex(vertex).is_some()
     }
@@ -649,6 +847,23 @@ impl HalfEdgeMesh {
     /// Merges this halfedge mesh with another one. No additional connectivity
     /// data is generated between the two.
     pub fn merge_with(&mut self, mesh_b: &HalfEdgeMesh) {
+        self.merge_with_id_map(mesh_b);
+    }
+
+    /// Returns an independent copy of this mesh, with freshly allocated ids,
+    /// along with a mapping from the ids of this mesh to the ids of the copy.
+    pub fn duplicate(&self) -> (HalfEdgeMesh, IdMap) {
+        let mut copy = HalfEdgeMesh {
+            debug_marks_enabled: self.debug_marks_enabled,
+            ..Default::default()
+        };
+        let id_map = copy.merge_with_id_map(self);
+        (copy, id_map)
+    }
+
+    /// Same as [`HalfEdgeMesh::merge_with`], but returns a mapping from the
+    /// ids of `mesh_b` to the ids of the elements added to this mesh.
+    pub fn merge_with_id_map(&mut self, mesh_b: &HalfEdgeMesh) -> IdMap {
         let mut vmap = HashMap::<VertexId, VertexId>::new();
         let mut hmap = HashMap::<HalfEdgeId, HalfEdgeId>::new();
         let mut fmap = HashMap::<FaceId, FaceId>::new();
@@ -657,6 +872,9 @@ impl HalfEdgeMesh {
         // setting any of their pointers and store their ids in a mapping.
         for (vertex_id, vertex) in mesh_b.iter_vertices() {
             vmap.insert(vertex_id, self.alloc_vertex(vertex.position, None));
+            if let Some(&color) = mesh_b.vertex_colors.get(&vertex_id) {
+                self.vertex_colors.insert(vmap[&vertex_id], color);
+            }
         }
         for (face_id, _) in mesh_b.iter_faces() {
             fmap.insert(face_id, self.alloc_face(None));
@@ -684,8 +902,17 @@ impl HalfEdgeMesh {
             if let Some(h) = face.halfedge {
                 self[fmap[&face_id]].halfedge = Some(hmap[&h])
             }
+            if let Some(&material) = mesh_b.face_materials.get(&face_id) {
+                self.face_materials.insert(fmap[&face_id], material);
+            }
         }
         for (halfedge_id, halfedge) in mesh_b.iter_halfedges() {
+            if let Some(&uv) = mesh_b.halfedge_uvs.get(&halfedge_id) {
+                self.halfedge_uvs.insert(hmap[&halfedge_id], uv);
+            }
+            if let Some(&crease) = mesh_b.edge_creases.get(&halfedge_id) {
+                self.edge_creases.insert(hmap[&halfedge_id], crease);
+            }
             if let Some(twin) = halfedge.twin {
                 self[hmap[&halfedge_id]].twin = Some(hmap[&twin]);
             }
@@ -699,6 +926,12 @@ impl HalfEdgeMesh {
                 self[hmap[&halfedge_id]].face = Some(fmap[&face]);
             }
         }
+
+        IdMap {
+            vertices: vmap,
+            halfedges: hmap,
+            faces: fmap,
+        }
     }
 
     // Returns the normal of the face. The first three vertices are used to
@@ -715,6 +948,130 @@ impl HalfEdgeMesh {
 
         v01.cross(v12).normalize()
     }
+
+    /// Computes smooth per-vertex normals as the normalized sum of the normals
+    /// of the faces around each vertex, weighted by the angle of each face at
+    /// the vertex. Unlike weighting by area, this gives good results on meshes
+    /// with very uneven face sizes. Vertices that are not part of any face are
+    /// not present in the returned map.
+    pub fn compute_vertex_normals(&self) -> HashMap<VertexId, Vec3> {
+        let mut normals = HashMap::<VertexId, Vec3>::new();
+        for (face_id, _) in self.iter_faces() {
+            let normal = self.face_normal(face_id);
+            // Degenerate faces have no well-defined normal
+            if !normal.is_finite() {
+                continue;
+            }
+            let vertices = self.face_vertices(face_id);
+            for (&prev, &v, &next) in vertices.iter().circular_tuple_windows() {
+                let angle = corner_angle(
+                    self.vertex_position(prev) - self.vertex_position(v),
+                    self.vertex_position(next) - self.vertex_position(v),
+                );
+                *normals.entry(v).or_insert(Vec3::ZERO) += normal * angle;
+            }
+        }
+        for normal in normals.values_mut() {
+            *normal = normal.normalize_or_zero();
+        }
+        normals
+    }
+
+    /// Computes the smooth normal of a single vertex, like
+    /// [`HalfEdgeMesh::compute_vertex_normals`] does for the whole mesh.
+    /// Vertices that are not part of any face get a zero normal.
+    pub fn vertex_normal(&self, v: VertexId) -> Result<Vec3> {
+        let position = self.vertex_position(v);
+        let mut normal = Vec3::ZERO;
+        for h in self.at_vertex(v).outgoing_halfedges()? {
+            let face = match self[h].face {
+                Some(face) => face,
+                None => continue,
+            };
+            let face_normal = self.face_normal(face);
+            if !face_normal.is_finite() {
+                continue;
+            }
+            let next = self.at_halfedge(h).dst_vertex().try_end()?;
+            let prev = self.at_halfedge(h).previous().vertex().try_end()?;
+            let angle = corner_angle(
+                self.vertex_position(prev) - position,
+                self.vertex_position(next) - position,
+            );
+            normal += face_normal * angle;
+        }
+        Ok(normal.normalize_or_zero())
+    }
+
+    /// Computes per-vertex tangents for normal mapping, following the same
+    /// idea as Mikktspace: The tangent and bitangent of each triangle are the
+    /// directions of increasing U and V, which get accumulated for each vertex
+    /// and then made orthogonal to the vertex normal.
+    ///
+    /// The w component stores the handedness, so the bitangent can be
+    /// reconstructed as `normal.cross(tangent) * w`. Vertices that are not part
+    /// of any face with UVs, or have degenerate UVs, get an arbitrary tangent
+    /// perpendicular to their normal.
+    pub fn compute_tangents(&self) -> HashMap<VertexId, Vec4> {
+        let normals = self.compute_vertex_normals();
+        let mut tangents = HashMap::<VertexId, Vec3>::new();
+        let mut bitangents = HashMap::<VertexId, Vec3>::new();
+
+        for (face_id, _) in self.iter_faces() {
+            let corners = self
+                .face_edges(face_id)
+                .iter()
+                .map(|&h| Some((self.at_halfedge(h).vertex().end(), self.halfedge_uv(h)?)))
+                .collect::<Option<SVec<_>>>();
+            // Faces without UVs don't contribute
+            let corners = match corners {
+                Some(corners) => corners,
+                None => continue,
+            };
+
+            // Fan triangulation, same as in `generate_buffers`
+            let (v0, uv0) = corners[0];
+            for (&(v1, uv1), &(v2, uv2)) in corners[1..].iter().tuple_windows() {
+                let e1 = self.vertex_position(v1) - self.vertex_position(v0);
+                let e2 = self.vertex_position(v2) - self.vertex_position(v0);
+                let d1 = uv1 - uv0;
+                let d2 = uv2 - uv0;
+
+                let det = d1.x * d2.y - d2.x * d1.y;
+                if det.abs() < 1e-12 {
+                    continue;
+                }
+                let tangent = (e1 * d2.y - e2 * d1.y) / det;
+                let bitangent = (e2 * d1.x - e1 * d2.x) / det;
+
+                for v in [v0, v1, v2] {
+                    *tangents.entry(v).or_insert(Vec3::ZERO) += tangent;
+                    *bitangents.entry(v).or_insert(Vec3::ZERO) += bitangent;
+                }
+            }
+        }
+
+        self.iter_vertices()
+            .map(|(v, _)| {
+                let n = normals.get(&v).cloned().unwrap_or(Vec3::Y);
+                let t = tangents.get(&v).cloned().unwrap_or(Vec3::ZERO);
+                let b = bitangents.get(&v).cloned().unwrap_or(Vec3::ZERO);
+
+                // Gram-Schmidt orthogonalization
+                let t = (t - n * n.dot(t)).normalize_or_zero();
+                if t == Vec3::ZERO {
+                    // Any vector perpendicular to the normal will do. Pick the
+                    // axis least aligned with it, to avoid precision issues.
+                    let axis = if n.x.abs() < 0.9 { Vec3::X } else { Vec3::Y };
+                    let t = (axis - n * n.dot(axis)).normalize_or_zero();
+                    (v, t.extend(1.0))
+                } else {
+                    let w = if n.cross(t).dot(b) < 0.0 { -1.0 } else { 1.0 };
+                    (v, t.extend(w))
+                }
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -773,4 +1130,66 @@ pub mod test {
 
         dbg!(hem.generate_buffers());
     }
+
+    #[test]
+    pub fn test_duplicate() {
+        let mut hem = HalfEdgeMesh::default();
+        let (a, b, c, d) = quad_abcd();
+        let q = hem.add_quad(a, b, c, d);
+        let v = hem.at_halfedge(q).vertex().end();
+
+        let (mut copy, id_map) = hem.duplicate();
+        let v_copy = id_map.vertices[&v];
+        assert_eq!(copy.vertex_position(v_copy), hem.vertex_position(v));
+        assert_eq!(id_map.halfedges.len(), hem.iter_halfedges().count());
+
+        copy.update_vertex_position(v_copy, |pos| pos + Vec3::Y);
+        copy.add_quad(a, b, c, d);
+
+        assert_eq!(hem.vertex_position(v), a);
+        assert_eq!(hem.iter_faces().count(), 1);
+        assert_eq!(copy.iter_faces().count(), 2);
+    }
+
+    #[test]
+    pub fn test_compute_tangents() {
+        // A quad on the XZ plane, facing up
+        let mut mesh = primitives::Quad::build(Vec3::ZERO, Vec3::Y, Vec3::X, Vec2::ONE);
+        let corners = mesh.iter_halfedges().map(|(h, _)| h).collect_vec();
+
+        // U grows along X and V along Z
+        for &h in corners.iter() {
+            let pos = mesh.vertex_position(mesh.at_halfedge(h).vertex().end());
+            mesh.set_halfedge_uv(h, Vec2::new(pos.x, pos.z));
+        }
+        for (_, tangent) in mesh.compute_tangents() {
+            assert!(tangent.truncate().distance(Vec3::X) < 1e-5, "{:?}", tangent);
+            // The bitangent, +Z, is opposite to normal x tangent
+            assert_eq!(tangent.w, -1.0);
+        }
+
+        // Mirroring the texture along U flips the tangent and the handedness
+        for &h in corners.iter() {
+            let pos = mesh.vertex_position(mesh.at_halfedge(h).vertex().end());
+            mesh.set_halfedge_uv(h, Vec2::new(-pos.x, pos.z));
+        }
+        for (_, tangent) in mesh.compute_tangents() {
+            assert!(
+                tangent.truncate().distance(-Vec3::X) < 1e-5,
+                "{:?}",
+                tangent
+            );
+            assert_eq!(tangent.w, 1.0);
+        }
+    }
+}
+
+/// The angle between two edges leaving the same corner of a face, or zero when
+/// any of them is degenerate.
+fn corner_angle(a: Vec3, b: Vec3) -> f32 {
+    let (a, b) = (a.normalize_or_zero(), b.normalize_or_zero());
+    if a == Vec3::ZERO || b == Vec3::ZERO {
+        return 0.0;
+    }
+    a.dot(b).clamp(-1.0, 1.0).acos()
 }
@@ -0,0 +1,536 @@
+use crate::prelude::*;
+
+/// The operation performed by [`boolean`]. The string representation is what
+/// gets shown in the UI.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::AsRefStr,
+)]
+pub enum BooleanOp {
+    /// Keeps everything that's inside either of the meshes.
+    #[strum(serialize = "Union")]
+    Union,
+    /// Keeps what's inside the first mesh, but not inside the second.
+    #[strum(serialize = "Difference")]
+    Difference,
+    /// Keeps only what's inside both meshes.
+    #[strum(serialize = "Intersection")]
+    Intersection,
+}
+
+/// Tolerance used to classify points with respect to a plane.
+const PLANE_EPSILON: f32 = 1e-5;
+
+/// Vertices closer than this get welded together when building the result.
+const WELD_EPSILON: f32 = 1e-4;
+
+#[derive(Clone, Copy, Debug)]
+struct Plane {
+    normal: Vec3,
+    w: f32,
+}
+
+impl Plane {
+    fn from_points(a: Vec3, b: Vec3, c: Vec3) -> Option<Plane> {
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        if normal == Vec3::ZERO {
+            return None;
+        }
+        Some(Plane {
+            normal,
+            w: normal.dot(a),
+        })
+    }
+
+    fn flip(&mut self) {
+        self.normal = -self.normal;
+        self.w = -self.w;
+    }
+}
+
+/// A convex polygon, with its vertices in counter-clockwise order when seen
+/// from the front of its plane.
+#[derive(Clone, Debug)]
+struct Polygon {
+    vertices: SVec<Vec3>,
+    plane: Plane,
+}
+
+impl Polygon {
+    fn flip(&mut self) {
+        self.vertices.reverse();
+        self.plane.flip();
+    }
+}
+
+/// Where a polygon falls with respect to a plane
+const COPLANAR: u8 = 0;
+const FRONT: u8 = 1;
+const BACK: u8 = 2;
+const SPANNING: u8 = 3;
+
+/// Outputs for [`split_polygon`], one list for each classification.
+#[derive(Default)]
+struct SplitResult {
+    coplanar_front: Vec<Polygon>,
+    coplanar_back: Vec<Polygon>,
+    front: Vec<Polygon>,
+    back: Vec<Polygon>,
+}
+
+/// Splits `polygon` by `plane`, if needed. Each resulting piece is placed in
+/// the list corresponding to its position with respect to the plane.
+/// Coplanar polygons go to the front or back list depending on their
+/// orientation.
+fn split_polygon(plane: &Plane, polygon: Polygon, out: &mut SplitResult) {
+    let types = polygon
+        .vertices
+        .iter()
+        .map(|v| {
+            let t = plane.normal.dot(*v) - plane.w;
+            if t < -PLANE_EPSILON {
+                BACK
+            } else if t > PLANE_EPSILON {
+                FRONT
+            } else {
+                COPLANAR
+            }
+        })
+        .collect::<SVec<_>>();
+    let polygon_type = types.iter().fold(COPLANAR, |acc, t| acc | t);
+
+    match polygon_type {
+        COPLANAR => {
+            if plane.normal.dot(polygon.plane.normal) > 0.0 {
+                out.coplanar_front.push(polygon)
+            } else {
+                out.coplanar_back.push(polygon)
+            }
+        }
+        FRONT => out.front.push(polygon),
+        BACK => out.back.push(polygon),
+        _ => {
+            debug_assert_eq!(polygon_type, SPANNING);
+            let mut front = SVec::new();
+            let mut back = SVec::new();
+            let n = polygon.vertices.len();
+            for i in 0..n {
+                let j = (i + 1) % n;
+                let (ti, tj) = (types[i], types[j]);
+                let (vi, vj) = (polygon.vertices[i], polygon.vertices[j]);
+                if ti != BACK {
+                    front.push(vi);
+                }
+                if ti != FRONT {
+                    back.push(vi);
+                }
+                if (ti | tj) == SPANNING {
+                    let t = (plane.w - plane.normal.dot(vi)) / plane.normal.dot(vj - vi);
+                    let v = vi.lerp(vj, t);
+                    front.push(v);
+                    back.push(v);
+                }
+            }
+            if front.len() >= 3 {
+                out.front.push(Polygon {
+                    vertices: front,
+                    plane: polygon.plane,
+                });
+            }
+            if back.len() >= 3 {
+                out.back.push(Polygon {
+                    vertices: back,
+                    plane: polygon.plane,
+                });
+            }
+        }
+    }
+}
+
+/// A node in a BSP tree. Each node stores the polygons lying on its splitting
+/// plane, and the front and back subtrees contain the polygons at each side.
+#[derive(Default)]
+struct BspNode {
+    plane: Option<Plane>,
+    front: Option<Box<BspNode>>,
+    back: Option<Box<BspNode>>,
+    polygons: Vec<Polygon>,
+}
+
+impl BspNode {
+    fn new(polygons: Vec<Polygon>) -> Self {
+        let mut node = BspNode::default();
+        node.build(polygons);
+        node
+    }
+
+    /// Converts solid space to empty space and vice versa.
+    fn invert(&mut self) {
+        for polygon in self.polygons.iter_mut() {
+            polygon.flip();
+        }
+        if let Some(plane) = self.plane.as_mut() {
+            plane.flip();
+        }
+        if let Some(front) = self.front.as_mut() {
+            front.invert();
+        }
+        if let Some(back) = self.back.as_mut() {
+            back.invert();
+        }
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    /// Removes the parts of `polygons` that are inside the solid represented
+    /// by this tree.
+    fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
+        let plane = match self.plane {
+            Some(plane) => plane,
+            None => return polygons,
+        };
+
+        let mut split = SplitResult::default();
+        for polygon in polygons {
+            split_polygon(&plane, polygon, &mut split);
+        }
+        let mut front = split.front;
+        front.append(&mut split.coplanar_front);
+        let mut back = split.back;
+        back.append(&mut split.coplanar_back);
+
+        let mut front = match &self.front {
+            Some(node) => node.clip_polygons(front),
+            None => front,
+        };
+        let back = match &self.back {
+            Some(node) => node.clip_polygons(back),
+            // Polygons behind a leaf are inside the solid
+            None => vec![],
+        };
+        front.extend(back);
+        front
+    }
+
+    /// Removes all the polygons in this tree that are inside `other`.
+    fn clip_to(&mut self, other: &BspNode) {
+        self.polygons = other.clip_polygons(std::mem::take(&mut self.polygons));
+        if let Some(front) = self.front.as_mut() {
+            front.clip_to(other);
+        }
+        if let Some(back) = self.back.as_mut() {
+            back.clip_to(other);
+        }
+    }
+
+    fn all_polygons(&self) -> Vec<Polygon> {
+        let mut polygons = self.polygons.clone();
+        if let Some(front) = &self.front {
+            polygons.extend(front.all_polygons());
+        }
+        if let Some(back) = &self.back {
+            polygons.extend(back.all_polygons());
+        }
+        polygons
+    }
+
+    /// Inserts the polygons in the tree, creating new nodes as needed. The
+    /// plane of the first polygon is used to split the rest.
+    fn build(&mut self, polygons: Vec<Polygon>) {
+        if polygons.is_empty() {
+            return;
+        }
+        let plane = *self.plane.get_or_insert(polygons[0].plane);
+
+        let mut split = SplitResult::default();
+        for polygon in polygons {
+            split_polygon(&plane, polygon, &mut split);
+        }
+        self.polygons.append(&mut split.coplanar_front);
+        self.polygons.append(&mut split.coplanar_back);
+
+        if !split.front.is_empty() {
+            self.front
+                .get_or_insert_with(Default::default)
+                .build(split.front);
+        }
+        if !split.back.is_empty() {
+            self.back
+                .get_or_insert_with(Default::default)
+                .build(split.back);
+        }
+    }
+}
+
+/// Converts the faces of a mesh to triangles for the BSP tree. Faces are
+/// triangulated because the BSP algorithm requires planar convex polygons.
+fn mesh_to_polygons(mesh: &HalfEdgeMesh) -> Vec<Polygon> {
+    let mut polygons = vec![];
+    for (face_id, _) in mesh.iter_faces() {
+        let positions = mesh
+            .face_vertices(face_id)
+            .iter()
+            .map(|v| mesh.vertex_position(*v))
+            .collect::<SVec<_>>();
+        let a = positions[0];
+        for (&b, &c) in positions[1..].iter().tuple_windows() {
+            // Degenerate triangles don't contribute any area
+            if let Some(plane) = Plane::from_points(a, b, c) {
+                polygons.push(Polygon {
+                    vertices: smallvec::smallvec![a, b, c],
+                    plane,
+                });
+            }
+        }
+    }
+    polygons
+}
+
+/// Merges points closer than [`WELD_EPSILON`] into a single vertex. Points
+/// are bucketed in a grid with cells of that size, and each new point is
+/// compared against the points in its cell and the neighboring ones, so
+/// points close to each other always merge, even when they fall on different
+/// sides of a cell boundary.
+#[derive(Default)]
+struct Welder {
+    grid: HashMap<[i64; 3], SVec<u32>>,
+    positions: Vec<Vec3>,
+}
+
+impl Welder {
+    fn cell(p: Vec3) -> [i64; 3] {
+        (p / WELD_EPSILON).floor().to_array().map(|x| x as i64)
+    }
+
+    /// Returns the index of the vertex at `p`, creating it if there is no
+    /// vertex close enough.
+    fn weld(&mut self, p: Vec3) -> u32 {
+        let [x, y, z] = Self::cell(p);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    if let Some(ids) = self.grid.get(&[x + dx, y + dy, z + dz]) {
+                        for &id in ids {
+                            if self.positions[id as usize].distance_squared(p)
+                                <= WELD_EPSILON * WELD_EPSILON
+                            {
+                                return id;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        let id = self.positions.len() as u32;
+        self.positions.push(p);
+        self.grid.entry([x, y, z]).or_default().push(id);
+        id
+    }
+}
+
+/// Splitting a polygon creates new vertices on its edges, which the polygons
+/// on the other side of those edges don't have. These are called
+/// T-junctions, and leave cracks between the polygons. This inserts, on every
+/// edge of every face, all the vertices lying on it, so the faces on both
+/// sides of an edge share the same vertices.
+fn repair_t_junctions(positions: &[Vec3], faces: &mut [Vec<u32>]) {
+    // Vertices sorted by their x coordinate, to quickly find the ones that
+    // may lie on each edge.
+    let x_of = |v: &u32| positions[*v as usize].x;
+    let mut by_x = (0..positions.len() as u32).collect_vec();
+    by_x.sort_by(|a, b| x_of(a).total_cmp(&x_of(b)));
+    let xs = by_x.iter().map(x_of).collect_vec();
+
+    for face in faces.iter_mut() {
+        let mut repaired = Vec::with_capacity(face.len());
+        for (&a, &b) in face.iter().circular_tuple_windows() {
+            repaired.push(a);
+            let (pa, pb) = (positions[a as usize], positions[b as usize]);
+            let edge = pb - pa;
+            let len_sq = edge.length_squared();
+            let max_x = pa.x.max(pb.x) + WELD_EPSILON;
+            let first = xs.partition_point(|x| *x < pa.x.min(pb.x) - WELD_EPSILON);
+
+            let mut on_edge = SVec::<(f32, u32)>::new();
+            for (&x, &v) in xs[first..].iter().zip(&by_x[first..]) {
+                if x > max_x {
+                    break;
+                }
+                if v == a || v == b {
+                    continue;
+                }
+                let p = positions[v as usize];
+                let t = (p - pa).dot(edge) / len_sq;
+                if t > 0.0
+                    && t < 1.0
+                    && (pa + edge * t).distance_squared(p) <= WELD_EPSILON * WELD_EPSILON
+                {
+                    on_edge.push((t, v));
+                }
+            }
+            on_edge.sort_by(|(t1, _), (t2, _)| t1.total_cmp(t2));
+            repaired.extend(on_edge.iter().map(|(_, v)| *v));
+        }
+        *face = repaired;
+    }
+}
+
+/// Builds a mesh out of the resulting polygon soup. Nearby vertices are
+/// welded and T-junctions are repaired, so that polygons on both sides of
+/// the intersection seam, and of the splits made by the BSP tree, get
+/// connected.
+fn polygons_to_mesh(polygons: &[Polygon]) -> Result<HalfEdgeMesh> {
+    let mut welder = Welder::default();
+    let mut faces = vec![];
+
+    for polygon in polygons {
+        let mut face = SVec::<u32>::new();
+        for v in polygon.vertices.iter() {
+            let idx = welder.weld(*v);
+            // Welding can collapse consecutive vertices
+            if face.last() != Some(&idx) {
+                face.push(idx);
+            }
+        }
+        if face.len() > 1 && face.first() == face.last() {
+            face.pop();
+        }
+        if face.len() >= 3 && face.iter().duplicates().next().is_none() {
+            faces.push(face.to_vec());
+        }
+    }
+
+    repair_t_junctions(&welder.positions, &mut faces);
+    faces.retain(|face| face.iter().duplicates().next().is_none());
+
+    HalfEdgeMesh::build_from_polygons(&welder.positions, &faces)
+}
+
+/// Computes a boolean operation between two meshes, which should be closed.
+///
+/// This uses BSP trees: Each mesh is converted into a tree of its (triangulated)
+/// faces, which is then used to clip away the parts of the other mesh that
+/// should not be kept. Faces crossing the other mesh get split along the
+/// intersection curve. The remaining pieces are then welded back together
+/// into a new mesh.
+///
+/// NOTE: The result may contain more faces than strictly necessary, since
+/// split faces are not merged back together.
+pub fn boolean(a: &HalfEdgeMesh, b: &HalfEdgeMesh, op: BooleanOp) -> Result<HalfEdgeMesh> {
+    boolean_with_progress(a, b, op, &progress::no_progress)
+}
+
+/// Same as [`boolean`], but reports its progress and can be cancelled. See
+/// [`Progress`].
+pub fn boolean_with_progress(
+    a: &HalfEdgeMesh,
+    b: &HalfEdgeMesh,
+    op: BooleanOp,
+    progress: Progress,
+) -> Result<HalfEdgeMesh> {
+    progress::report(progress, 0.0)?;
+    let mut a = BspNode::new(mesh_to_polygons(a));
+    progress::report(progress, 0.2)?;
+    let mut b = BspNode::new(mesh_to_polygons(b));
+    progress::report(progress, 0.4)?;
+
+    match op {
+        BooleanOp::Union => {
+            a.clip_to(&b);
+            b.clip_to(&a);
+            b.invert();
+            b.clip_to(&a);
+            b.invert();
+            a.build(b.all_polygons());
+        }
+        BooleanOp::Difference => {
+            a.invert();
+            a.clip_to(&b);
+            b.clip_to(&a);
+            b.invert();
+            b.clip_to(&a);
+            b.invert();
+            a.build(b.all_polygons());
+            a.invert();
+        }
+        BooleanOp::Intersection => {
+            a.invert();
+            b.clip_to(&a);
+            b.invert();
+            a.clip_to(&b);
+            b.clip_to(&a);
+            a.build(b.all_polygons());
+            a.invert();
+        }
+    }
+
+    progress::report(progress, 0.8)?;
+
+    let polygons = a.all_polygons();
+    if polygons.is_empty() {
+        bail!("The boolean operation resulted in an empty mesh");
+    }
+    let result = polygons_to_mesh(&polygons)?;
+    progress::report(progress, 1.0)?;
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Two unit cubes, the second one moved by half a unit along every axis,
+    /// so they overlap in a cube of side 0.5 and no faces are coplanar.
+    fn overlapping_boxes() -> (HalfEdgeMesh, HalfEdgeMesh) {
+        (
+            primitives::Box::build(Vec3::ZERO, Vec3::ONE),
+            primitives::Box::build(Vec3::splat(0.5), Vec3::ONE),
+        )
+    }
+
+    fn assert_closed_manifold(mesh: &HalfEdgeMesh) {
+        assert!(mesh.stats().is_closed);
+        assert!(mesh.is_manifold());
+        assert_eq!(mesh.validate(), Ok(()));
+    }
+
+    #[test]
+    pub fn test_boolean_union() {
+        let (a, b) = overlapping_boxes();
+        let result = boolean(&a, &b, BooleanOp::Union).unwrap();
+        assert_closed_manifold(&result);
+        assert!((result.volume() - 1.875).abs() < 1e-3);
+    }
+
+    #[test]
+    pub fn test_boolean_difference() {
+        let (a, b) = overlapping_boxes();
+        let result = boolean(&a, &b, BooleanOp::Difference).unwrap();
+        assert_closed_manifold(&result);
+        assert!((result.volume() - 0.875).abs() < 1e-3);
+    }
+
+    #[test]
+    pub fn test_boolean_intersection() {
+        let (a, b) = overlapping_boxes();
+        let result = boolean(&a, &b, BooleanOp::Intersection).unwrap();
+        assert_closed_manifold(&result);
+        assert!((result.volume() - 0.125).abs() < 1e-3);
+    }
+
+    #[test]
+    pub fn test_weld_across_cells() {
+        let mut welder = Welder::default();
+        // Both points are within the weld distance, but on different cells
+        let a = welder.weld(Vec3::splat(WELD_EPSILON * 0.99));
+        let b = welder.weld(Vec3::splat(WELD_EPSILON * 1.01));
+        assert_eq!(a, b);
+        let c = welder.weld(Vec3::splat(WELD_EPSILON * 3.0));
+        assert_ne!(a, c);
+    }
+}
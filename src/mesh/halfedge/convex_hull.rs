@@ -0,0 +1,184 @@
+use crate::prelude::*;
+
+/// A triangle of the hull being built, as indices into the point list. The
+/// vertices are in counter-clockwise order when looking from outside.
+#[derive(Clone, Copy)]
+struct HullFace {
+    indices: [usize; 3],
+    normal: Vec3,
+    /// Distance from the origin to the face's plane, along the normal.
+    offset: f32,
+}
+
+impl HullFace {
+    fn new(points: &[Vec3], indices: [usize; 3]) -> Self {
+        let [a, b, c] = indices.map(|i| points[i]);
+        let normal = (b - a).cross(c - a).normalize();
+        HullFace {
+            indices,
+            normal,
+            offset: normal.dot(a),
+        }
+    }
+
+    /// Signed distance from the point to the face's plane. Positive means the
+    /// point is in front of the face, i.e. outside of the hull.
+    fn distance(&self, p: Vec3) -> f32 {
+        self.normal.dot(p) - self.offset
+    }
+
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.indices;
+        [(a, b), (b, c), (c, a)]
+    }
+}
+
+/// Returns the index of the point maximizing `f`.
+fn farthest(points: &[Vec3], f: impl Fn(Vec3) -> f32) -> (usize, f32) {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, p)| (i, f(*p)))
+        .fold(
+            (0, f32::NEG_INFINITY),
+            |best, x| if x.1 > best.1 { x } else { best },
+        )
+}
+
+/// Computes the convex hull of a set of points, as a closed triangle mesh.
+///
+/// This uses an incremental algorithm: Starting from a tetrahedron, each point
+/// outside the current hull removes all the faces it can see and gets
+/// connected to the boundary (horizon) of the removed region. Points that end
+/// up inside the hull are not part of the resulting mesh.
+///
+/// Returns an error when the points don't span a volume, i.e. they are all
+/// coplanar, collinear or coincident.
+pub fn convex_hull(points: &[Vec3]) -> Result<HalfEdgeMesh> {
+    if points.len() < 4 {
+        bail!(
+            "The convex hull needs at least 4 points, got {}",
+            points.len()
+        );
+    }
+
+    // Tolerance used in all the geometric tests, relative to the size of the
+    // point set.
+    let (min, max) = points.iter().fold(
+        (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+        |(min, max), p| (min.min(*p), max.max(*p)),
+    );
+    let eps = (max - min).max_element() * 1e-5;
+
+    // Build an initial tetrahedron, picking points as far apart as possible.
+    let i0 = 0;
+    let p0 = points[i0];
+    let (i1, d1) = farthest(points, |p| p.distance(p0));
+    if d1 <= eps {
+        bail!("Cannot compute the convex hull: All points are coincident");
+    }
+    let p1 = points[i1];
+    let dir = (p1 - p0).normalize();
+    let (i2, d2) = farthest(points, |p| (p - p0).cross(dir).length());
+    if d2 <= eps {
+        bail!("Cannot compute the convex hull: All points are collinear");
+    }
+    let p2 = points[i2];
+    let normal = (p1 - p0).cross(p2 - p0).normalize();
+    let (i3, d3) = farthest(points, |p| (p - p0).dot(normal).abs());
+    if d3 <= eps {
+        bail!("Cannot compute the convex hull: All points are coplanar");
+    }
+
+    // Orient the faces of the tetrahedron so they point away from the
+    // remaining vertex.
+    let mut faces = if (points[i3] - p0).dot(normal) > 0.0 {
+        vec![[i0, i2, i1], [i0, i1, i3], [i1, i2, i3], [i2, i0, i3]]
+    } else {
+        vec![[i0, i1, i2], [i0, i3, i1], [i1, i3, i2], [i2, i3, i0]]
+    }
+    .into_iter()
+    .map(|indices| HullFace::new(points, indices))
+    .collect_vec();
+
+    for (i, &p) in points.iter().enumerate() {
+        if [i0, i1, i2, i3].contains(&i) {
+            continue;
+        }
+
+        let (visible, hidden): (Vec<HullFace>, Vec<HullFace>) =
+            faces.into_iter().partition(|face| face.distance(p) > eps);
+        faces = hidden;
+        if visible.is_empty() {
+            // The point is inside the hull
+            continue;
+        }
+
+        // The horizon is formed by the edges of visible faces whose twin is
+        // not in a visible face.
+        let visible_edges: HashSet<(usize, usize)> =
+            visible.iter().flat_map(|face| face.edges()).collect();
+        for &(a, b) in visible_edges.iter() {
+            if !visible_edges.contains(&(b, a)) {
+                faces.push(HullFace::new(points, [a, b, i]));
+            }
+        }
+    }
+
+    // Keep only the points that are part of the hull, and remap the indices.
+    let mut index_map = HashMap::<usize, usize>::new();
+    let mut positions = vec![];
+    let polygons = faces
+        .iter()
+        .map(|face| {
+            face.indices.map(|i| {
+                *index_map.entry(i).or_insert_with(|| {
+                    positions.push(points[i]);
+                    positions.len() - 1
+                })
+            })
+        })
+        .collect_vec();
+
+    HalfEdgeMesh::build_from_polygons(&positions, &polygons)
+}
+
+impl HalfEdgeMesh {
+    /// Returns the convex hull of this mesh's vertices. See [`convex_hull`].
+    pub fn convex_hull_of_self(&self) -> Result<HalfEdgeMesh> {
+        let points = self.iter_vertices().map(|(_, v)| v.position).collect_vec();
+        convex_hull(&points)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_cube_hull() {
+        let mut points = vec![];
+        for x in [-1.0, 1.0] {
+            for y in [-1.0, 1.0] {
+                for z in [-1.0, 1.0] {
+                    points.push(Vec3::new(x, y, z));
+                }
+            }
+        }
+        // An interior point, which should not be part of the hull
+        points.push(Vec3::ZERO);
+
+        let hull = convex_hull(&points).unwrap();
+        let stats = hull.stats();
+        assert_eq!(stats.num_vertices, 8);
+        assert_eq!(stats.num_faces, 12);
+        assert!(stats.is_closed);
+        assert!(hull.iter_faces().all(|(f, _)| hull.num_face_edges(f) == 3));
+    }
+
+    #[test]
+    pub fn test_coplanar_hull() {
+        let points = [Vec3::ZERO, Vec3::X, Vec3::Z, Vec3::X + Vec3::Z];
+        assert!(convex_hull(&points).is_err());
+    }
+}
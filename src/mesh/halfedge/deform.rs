@@ -0,0 +1,770 @@
+use crate::prelude::*;
+
+/// Pushes the vertices of the mesh towards its bounding sphere, that is, the
+/// sphere centered at the mesh's centroid passing through its farthest
+/// vertex. Each vertex is moved along the direction from the centroid, by the
+/// given `factor`: 0.0 leaves the mesh untouched, and 1.0 places all the
+/// vertices on the sphere.
+pub fn spherize(mesh: &mut HalfEdgeMesh, factor: f32) -> Result<()> {
+    let center = mesh
+        .centroid()
+        .ok_or_else(|| anyhow!("Cannot spherize an empty mesh"))?;
+    let radius = mesh
+        .iter_vertices()
+        .map(|(_, v)| v.position.distance(center))
+        .fold(0.0, f32::max);
+
+    let vertices = mesh.iter_vertices().map(|(v, _)| v).collect_vec();
+    for v in vertices {
+        mesh.update_vertex_position(v, |pos| {
+            let dir = (pos - center).normalize_or_zero();
+            // A vertex at the centroid has no direction to be pushed towards.
+            if dir == Vec3::ZERO {
+                return pos;
+            }
+            pos.lerp(center + dir * radius, factor)
+        });
+    }
+    Ok(())
+}
+
+/// Rotates each vertex around `axis`, which passes through the origin, by an
+/// angle proportional to the vertex's coordinate along the axis. Vertices at
+/// the origin stay in place, and a vertex one unit away along the axis gets
+/// rotated by `angle_per_unit` radians.
+pub fn twist(mesh: &mut HalfEdgeMesh, axis: Axis, angle_per_unit: f32) {
+    twist_with_dir(mesh, axis.to_vec3(), angle_per_unit, 0.0);
+}
+
+/// Same as [`twist`], but around an arbitrary `axis` direction through the
+/// origin, and twisting the whole mesh by a total `angle`: The vertices with
+/// the lowest coordinate along `axis` stay in place, the ones with the highest
+/// get rotated by `angle` radians, and the rest by an angle proportional to
+/// their coordinate in between. Positive angles rotate counter-clockwise when
+/// looking from the tip of `axis` towards the origin.
+pub fn twist_along(mesh: &mut HalfEdgeMesh, axis: Vec3, angle: f32) -> Result<()> {
+    let axis = axis
+        .try_normalize()
+        .ok_or_else(|| anyhow!("Twist axis can't be zero"))?;
+    let (min_t, max_t) = mesh
+        .iter_vertices()
+        .map(|(_, vertex)| vertex.position.dot(axis))
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), t| {
+            (min.min(t), max.max(t))
+        });
+    // A flat mesh has nothing to twist along the axis
+    if max_t - min_t < 1e-6 {
+        return Ok(());
+    }
+    twist_with_dir(mesh, axis, angle / (max_t - min_t), min_t);
+    Ok(())
+}
+
+/// Shared by [`twist`] and [`twist_along`]. Vertices with a coordinate of
+/// `start` along the normalized `axis` stay in place.
+fn twist_with_dir(mesh: &mut HalfEdgeMesh, axis: Vec3, angle_per_unit: f32, start: f32) {
+    let vertices = mesh.iter_vertices().map(|(v, _)| v).collect_vec();
+    for v in vertices {
+        mesh.update_vertex_position(v, |pos| {
+            let angle = (pos.dot(axis) - start) * angle_per_unit;
+            Quat::from_axis_angle(axis, angle) * pos
+        });
+    }
+}
+
+/// Scales the coordinates of each vertex perpendicular to `axis`, which
+/// passes through the origin, proportionally to its coordinate along the
+/// axis. The scale is `1.0 + factor * t`, where `t` is the coordinate along
+/// the axis, so positive factors widen the mesh in the positive direction of
+/// the axis and narrow it in the negative one.
+pub fn taper(mesh: &mut HalfEdgeMesh, axis: Axis, factor: f32) {
+    let vertices = mesh.iter_vertices().map(|(v, _)| v).collect_vec();
+    for v in vertices {
+        mesh.update_vertex_position(v, |pos| {
+            let t = axis.component(pos);
+            axis.project_onto(pos) + axis.project_off(pos) * (1.0 + factor * t)
+        });
+    }
+}
+
+/// Bends the mesh around `axis` into an arc subtending `angle` radians.
+///
+/// Only the section of the mesh whose coordinate along `axis` falls within
+/// `bounds` gets curved. The arc length of that section is kept equal to its
+/// original length, so the mesh doesn't stretch. Parts of the mesh before the
+/// start of the bounds are left untouched, and the parts after the end are
+/// rotated and moved to continue straight from the end of the arc.
+///
+/// The bend happens in the plane formed by `axis` and the next axis in
+/// (X, Y, Z) order, i.e. bending along X curves the mesh towards +Y, along Y
+/// towards +Z, and along Z towards +X. Negative angles bend in the opposite
+/// direction. The pivot, i.e. the center of the arc, is at the start of the
+/// bounds along `axis`, displaced by the arc radius along the bend direction
+/// from the origin.
+pub fn bend(mesh: &mut HalfEdgeMesh, axis: Axis, angle: f32, bounds: (f32, f32)) -> Result<()> {
+    bend_with_dirs(mesh, axis.to_vec3(), axis.next().to_vec3(), angle, bounds)
+}
+
+/// Same as [`bend`], but along an arbitrary `axis` direction. The bounds are
+/// measured along the normalized `axis`.
+///
+/// The mesh bends towards [`bend_direction`] of the axis, which extends the
+/// convention of [`bend`]: Along X the mesh curves towards +Y, along Y towards
+/// +Z and along Z towards +X, and other axes get the closest direction
+/// perpendicular to them.
+pub fn bend_along(
+    mesh: &mut HalfEdgeMesh,
+    axis: Vec3,
+    angle: f32,
+    bounds: (f32, f32),
+) -> Result<()> {
+    let axis = axis
+        .try_normalize()
+        .ok_or_else(|| anyhow!("Bend axis can't be zero"))?;
+    bend_with_dirs(mesh, axis, bend_direction(axis), angle, bounds)
+}
+
+/// The direction towards which [`bend_along`] curves a mesh bent along the
+/// normalized `axis`. This is the axis with its coordinates rotated, i.e.
+/// (z, x, y), made perpendicular to it. That maps X to Y, Y to Z and Z to X,
+/// like [`Axis::next`]. For the few axes where that is degenerate, any
+/// perpendicular direction is used.
+pub fn bend_direction(axis: Vec3) -> Vec3 {
+    let rotated = Vec3::new(axis.z, axis.x, axis.y);
+    let dir = (rotated - axis * axis.dot(rotated)).normalize_or_zero();
+    if dir != Vec3::ZERO {
+        return dir;
+    }
+    // Pick the world axis least aligned with `axis`, to avoid precision issues
+    let other = if axis.x.abs() < 0.9 { Vec3::X } else { Vec3::Y };
+    (other - axis * axis.dot(other)).normalize()
+}
+
+/// Shared by [`bend`] and [`bend_along`]. `axis` and `bend_dir` must be
+/// normalized and perpendicular to each other.
+fn bend_with_dirs(
+    mesh: &mut HalfEdgeMesh,
+    axis: Vec3,
+    bend_dir: Vec3,
+    angle: f32,
+    bounds: (f32, f32),
+) -> Result<()> {
+    let (start, end) = bounds;
+    let length = end - start;
+    if length <= 0.0 {
+        bail!("Invalid bend bounds: {:?}", bounds);
+    }
+    // A straight bend would have an infinite radius
+    if angle.abs() < 1e-6 {
+        return Ok(());
+    }
+
+    let radius = length / angle;
+
+    let vertices = mesh.iter_vertices().map(|(v, _)| v).collect_vec();
+    for v in vertices {
+        mesh.update_vertex_position(v, |pos| {
+            let t = pos.dot(axis);
+            let u = pos.dot(bend_dir);
+            let rest = pos - axis * t - bend_dir * u;
+
+            let t_clamped = t.clamp(start, end);
+            let theta = (t_clamped - start) / length * angle;
+            // Distance past the bounds, which continues along the tangent
+            let extra = t - t_clamped;
+
+            let (sin, cos) = theta.sin_cos();
+            let new_t = start + (radius - u) * sin + extra * cos;
+            let new_u = radius - (radius - u) * cos + extra * sin;
+            rest + axis * new_t + bend_dir * new_u
+        });
+    }
+    Ok(())
+}
+
+/// Applies an affine transform to the position of every vertex. When the
+/// transform mirrors the mesh, i.e. its determinant is negative, the faces are
+/// flipped too, so their normals keep pointing the same way relative to the
+/// surface.
+pub fn transform(mesh: &mut HalfEdgeMesh, matrix: Mat4) -> Result<()> {
+    let vertices = mesh.iter_vertices().map(|(v, _)| v).collect_vec();
+    for v in vertices {
+        mesh.update_vertex_position(v, |pos| matrix.transform_point3(pos));
+    }
+    if matrix.determinant() < 0.0 {
+        edit_ops::flip_all_faces(mesh)?;
+    }
+    Ok(())
+}
+
+/// Same as [`transform`], with the transform given as a scale, followed by a
+/// rotation and then a translation.
+pub fn transform_trs(
+    mesh: &mut HalfEdgeMesh,
+    translation: Vec3,
+    rotation: Quat,
+    scale: Vec3,
+) -> Result<()> {
+    transform(
+        mesh,
+        Mat4::from_scale_rotation_translation(scale, rotation, translation),
+    )
+}
+
+/// The direction in which [`jitter_vertices`] moves each vertex. The string
+/// representation is what gets shown in the node's dropdown.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::AsRefStr,
+)]
+pub enum JitterDirection {
+    /// Moves each vertex to a random point in a ball around it.
+    #[strum(serialize = "Any direction")]
+    Any,
+    /// Moves each vertex forward or backward along its normal.
+    #[strum(serialize = "Along normal")]
+    AlongNormal,
+}
+
+/// Moves each vertex by a pseudo-random offset of length up to `amount`. The
+/// offsets only depend on the `seed` and the ids of the vertices, so jittering
+/// the same mesh again gives the same result.
+pub fn jitter_vertices(
+    mesh: &mut HalfEdgeMesh,
+    amount: f32,
+    seed: u64,
+    direction: JitterDirection,
+) {
+    let normals = match direction {
+        JitterDirection::Any => HashMap::new(),
+        JitterDirection::AlongNormal => mesh.compute_vertex_normals(),
+    };
+    let vertices = mesh.iter_vertices().map(|(v, _)| v).collect_vec();
+    for v in vertices {
+        let mut hash = crate::math::hash_u64(seed ^ crate::math::hash_u64(v.idx() as u64));
+        let mut random = || {
+            hash = crate::math::hash_u64(hash);
+            crate::math::hash_to_unit_f32(hash) * 2.0 - 1.0
+        };
+        let offset = match direction {
+            // Rejection sampling, so the points are uniformly distributed in
+            // the ball instead of the cube around it.
+            JitterDirection::Any => loop {
+                let offset = Vec3::new(random(), random(), random());
+                if offset.length_squared() <= 1.0 {
+                    break offset;
+                }
+            },
+            JitterDirection::AlongNormal => {
+                normals.get(&v).cloned().unwrap_or(Vec3::ZERO) * random()
+            }
+        };
+        mesh.update_vertex_position(v, |pos| pos + offset * amount);
+    }
+}
+
+/// Moves each vertex along its normal by `amplitude` times the value of a
+/// smooth noise function at the vertex's position. Higher `frequency` values
+/// make the noise change faster across the surface. See
+/// [`crate::math::gradient_noise`].
+pub fn displace_noise(mesh: &mut HalfEdgeMesh, frequency: f32, amplitude: f32, seed: u32) {
+    let normals = mesh.compute_vertex_normals();
+    let vertices = mesh.iter_vertices().map(|(v, _)| v).collect_vec();
+    for v in vertices {
+        let normal = normals.get(&v).cloned().unwrap_or(Vec3::ZERO);
+        mesh.update_vertex_position(v, |pos| {
+            let noise = crate::math::gradient_noise(pos * frequency, seed as u64);
+            pos + normal * noise * amplitude
+        });
+    }
+}
+
+/// Returns the neighbors of each vertex that should be smoothed. Vertices on
+/// the boundary of the mesh, or not connected to any edge, are left out, so
+/// they stay in place.
+fn smoothing_neighbors(mesh: &HalfEdgeMesh) -> Result<Vec<(VertexId, SVec<VertexId>)>> {
+    let mut result = Vec::new();
+    for (v, _) in mesh.iter_vertices() {
+        let outgoing = match mesh.at_vertex(v).outgoing_halfedges() {
+            Ok(outgoing) if !outgoing.is_empty() => outgoing,
+            _ => continue,
+        };
+        let mut neighbors = SVec::new();
+        let mut is_boundary = false;
+        for h in outgoing {
+            let twin = mesh.at_halfedge(h).twin().try_end()?;
+            is_boundary |= mesh[h].face.is_none() || mesh[twin].face.is_none();
+            neighbors.push(mesh.at_halfedge(h).dst_vertex().try_end()?);
+        }
+        if !is_boundary {
+            result.push((v, neighbors));
+        }
+    }
+    Ok(result)
+}
+
+/// Moves each vertex by `factor` times the vector going from it to the
+/// average of its neighbors. All vertices are moved simultaneously.
+fn laplacian_step(mesh: &mut HalfEdgeMesh, neighbors: &[(VertexId, SVec<VertexId>)], factor: f32) {
+    let new_positions = neighbors
+        .iter()
+        .map(|(v, ns)| {
+            let pos = mesh.vertex_position(*v);
+            let avg = ns
+                .iter()
+                .fold(Vec3::ZERO, |sum, n| sum + mesh.vertex_position(*n))
+                / ns.len() as f32;
+            (*v, pos + (avg - pos) * factor)
+        })
+        .collect_vec();
+    for (v, pos) in new_positions {
+        mesh.set_vertex_position(v, pos);
+    }
+}
+
+/// Smooths the mesh by moving each vertex towards the average position of
+/// its neighbors, by `factor`, `iterations` times. Vertices on the boundary
+/// of the mesh stay in place.
+///
+/// This makes closed meshes shrink with every iteration. Use
+/// [`taubin_smooth`] to avoid that.
+pub fn laplacian_smooth(mesh: &mut HalfEdgeMesh, factor: f32, iterations: u32) -> Result<()> {
+    let neighbors = smoothing_neighbors(mesh)?;
+    for _ in 0..iterations {
+        laplacian_step(mesh, &neighbors, factor);
+    }
+    Ok(())
+}
+
+/// Smooths the mesh without shrinking it, using Taubin's method: Each pass
+/// is a Laplacian smoothing step with a positive `lambda` factor, which
+/// shrinks the mesh, followed by one with a negative `mu` factor, which
+/// inflates it back. For this to work, `mu` should be slightly larger in
+/// magnitude than `lambda`, like 0.33 and -0.34. With a `mu` of 0.0 this is
+/// the same as [`laplacian_smooth`]. Vertices on the boundary of the mesh
+/// stay in place.
+pub fn taubin_smooth(mesh: &mut HalfEdgeMesh, lambda: f32, mu: f32, passes: u32) -> Result<()> {
+    let neighbors = smoothing_neighbors(mesh)?;
+    for _ in 0..passes {
+        laplacian_step(mesh, &neighbors, lambda);
+        laplacian_step(mesh, &neighbors, mu);
+    }
+    Ok(())
+}
+
+/// The way [`shrinkwrap`] moves vertices onto the target surface. The string
+/// representation is what gets shown in the node's dropdown.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::AsRefStr,
+)]
+pub enum ShrinkwrapMode {
+    /// Moves each vertex to the closest point on the target's surface.
+    #[strum(serialize = "Nearest point")]
+    NearestSurfacePoint,
+    /// Moves each vertex along its normal, in either direction, until it
+    /// hits the target's surface. Vertices whose normal line doesn't hit the
+    /// target stay in place.
+    #[strum(serialize = "Project along normal")]
+    ProjectAlongNormal,
+}
+
+/// Triangulates the faces of the mesh as fans, returning the positions of
+/// the vertices of each triangle.
+fn mesh_triangles(mesh: &HalfEdgeMesh) -> Vec<[Vec3; 3]> {
+    let mut triangles = vec![];
+    for (face, _) in mesh.iter_faces() {
+        let positions = mesh
+            .face_vertices(face)
+            .iter()
+            .map(|v| mesh.vertex_position(*v))
+            .collect::<SVec<_>>();
+        for (&b, &c) in positions[1..].iter().tuple_windows() {
+            triangles.push([positions[0], b, c]);
+        }
+    }
+    triangles
+}
+
+/// Returns the point of the triangle closest to `p`. From "Real-Time
+/// Collision Detection", by Christer Ericson.
+fn closest_point_on_triangle(p: Vec3, [a, b, c]: [Vec3; 3]) -> Vec3 {
+    let (ab, ac, ap) = (b - a, c - a, p - a);
+    let (d1, d2) = (ab.dot(ap), ac.dot(ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = p - b;
+    let (d3, d4) = (ab.dot(bp), ac.dot(bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = p - c;
+    let (d5, d6) = (ab.dot(cp), ac.dot(cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denom = 1.0 / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
+
+/// Moves the vertices of the mesh onto the surface of `target`. See
+/// [`ShrinkwrapMode`] for the available ways to project the vertices.
+///
+/// NOTE: When moving to the nearest surface point, every vertex is checked
+/// against every face of the target, so it can get slow for big meshes.
+pub fn shrinkwrap(
+    mesh: &mut HalfEdgeMesh,
+    target: &HalfEdgeMesh,
+    mode: ShrinkwrapMode,
+) -> Result<()> {
+    let triangles = mesh_triangles(target);
+    if triangles.is_empty() {
+        bail!("Cannot shrinkwrap to a mesh without faces");
+    }
+
+    let normals = match mode {
+        ShrinkwrapMode::NearestSurfacePoint => HashMap::new(),
+        ShrinkwrapMode::ProjectAlongNormal => mesh.compute_vertex_normals(),
+    };
+
+    let vertices = mesh.iter_vertices().map(|(v, _)| v).collect_vec();
+    for v in vertices {
+        let pos = mesh.vertex_position(v);
+        let new_pos = match mode {
+            ShrinkwrapMode::NearestSurfacePoint => triangles
+                .iter()
+                .map(|tri| closest_point_on_triangle(pos, *tri))
+                .min_by(|p1, p2| {
+                    pos.distance_squared(*p1)
+                        .total_cmp(&pos.distance_squared(*p2))
+                }),
+            ShrinkwrapMode::ProjectAlongNormal => {
+                let normal = normals.get(&v).cloned().unwrap_or(Vec3::ZERO);
+                if normal == Vec3::ZERO {
+                    continue;
+                }
+                [target.raycast(pos, normal), target.raycast(pos, -normal)]
+                    .into_iter()
+                    .flatten()
+                    .min_by(|h1, h2| h1.distance.total_cmp(&h2.distance))
+                    .map(|hit| hit.position)
+            }
+        };
+        if let Some(new_pos) = new_pos {
+            mesh.update_vertex_position(v, |_| new_pos);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    pub fn test_twist_box() {
+        // A tall box going from y = 0 to y = 2
+        let original = halfedge::primitives::Box::build(Vec3::Y, Vec3::new(1.0, 2.0, 1.0));
+        let mut mesh = original.clone();
+
+        // 90 degrees in total, along the height of the box
+        twist(&mut mesh, Axis::Y, FRAC_PI_2 / 2.0);
+
+        let rotation = Quat::from_axis_angle(Vec3::Y, FRAC_PI_2);
+        for (v, vertex) in original.iter_vertices() {
+            let twisted = mesh.vertex_position(v);
+            if (vertex.position.y - 2.0).abs() < 1e-5 {
+                // The top face is rotated by 90 degrees
+                assert!(twisted.distance(rotation * vertex.position) < 1e-5);
+            } else {
+                // The bottom face stays in place
+                assert!(twisted.distance(vertex.position) < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_twist_along() {
+        let axis = Vec3::new(1.0, 1.0, 0.0).normalize();
+        let original = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::new(1.0, 2.0, 3.0));
+        let mut mesh = original.clone();
+        assert!(twist_along(&mut mesh, Vec3::ZERO, 1.0).is_err());
+        twist_along(&mut mesh, axis, FRAC_PI_2).unwrap();
+
+        let (min_t, max_t) = original
+            .iter_vertices()
+            .map(|(_, vertex)| vertex.position.dot(axis))
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), t| {
+                (min.min(t), max.max(t))
+            });
+        for (v, vertex) in original.iter_vertices() {
+            let (before, after) = (vertex.position, mesh.vertex_position(v));
+            // Vertices only rotate around the axis, by an angle growing along it
+            let t = before.dot(axis);
+            let angle = (t - min_t) / (max_t - min_t) * FRAC_PI_2;
+            let expected = Quat::from_axis_angle(axis, angle) * before;
+            assert!(after.distance(expected) < 1e-5, "{} != {}", after, expected);
+        }
+    }
+
+    #[test]
+    pub fn test_bend_along() {
+        // A long box along X, and the same box lying along an arbitrary axis
+        let original = halfedge::primitives::Box::build_subdivided(
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(4.0, 0.2, 0.2),
+            UVec3::new(8, 1, 1),
+        );
+        let axis = Vec3::new(1.0, 2.0, -0.5).normalize();
+        let bend_dir = bend_direction(axis);
+        assert!(axis.dot(bend_dir).abs() < 1e-5);
+        let frame = Mat4::from_cols(
+            axis.extend(0.0),
+            bend_dir.extend(0.0),
+            axis.cross(bend_dir).extend(0.0),
+            Vec4::W,
+        );
+        let mut rotated = original.clone();
+        transform(&mut rotated, frame).unwrap();
+
+        // Bending along the axis matches bending along X in the rotated frame
+        let mut expected = original.clone();
+        bend(&mut expected, Axis::X, FRAC_PI_2, (1.0, 3.0)).unwrap();
+        bend_along(&mut rotated, axis, FRAC_PI_2, (1.0, 3.0)).unwrap();
+        for (v, _) in original.iter_vertices() {
+            let expected = frame.transform_point3(expected.vertex_position(v));
+            assert!(rotated.vertex_position(v).distance(expected) < 1e-4);
+        }
+
+        // The convention matches the one of `bend` for the world axes
+        for axis in [Axis::X, Axis::Y, Axis::Z] {
+            assert_eq!(bend_direction(axis.to_vec3()), axis.next().to_vec3());
+        }
+    }
+
+    #[test]
+    pub fn test_bend_quarter_circle() {
+        // A long, thin box along the X axis, subdivided in segments
+        let (length, segments, h): (f32, usize, f32) = (4.0, 8, 0.1);
+        let mut positions = vec![];
+        for i in 0..=segments {
+            let x = i as f32 / segments as f32 * length;
+            positions.push(Vec3::new(x, -h, -h));
+            positions.push(Vec3::new(x, h, -h));
+            positions.push(Vec3::new(x, h, h));
+            positions.push(Vec3::new(x, -h, h));
+        }
+        let mut polygons = vec![vec![0, 1, 2, 3]];
+        for i in 0..segments {
+            for k in 0..4 {
+                let (a, b) = (4 * i, 4 * (i + 1));
+                let k2 = (k + 1) % 4;
+                polygons.push(vec![a + k, b + k, b + k2, a + k2]);
+            }
+        }
+        let last = 4 * segments;
+        polygons.push(vec![last + 3, last + 2, last + 1, last]);
+
+        let original = HalfEdgeMesh::build_from_polygons(&positions, &polygons).unwrap();
+        let mut mesh = original.clone();
+        bend(&mut mesh, Axis::X, FRAC_PI_2, (0.0, length)).unwrap();
+
+        let radius = length / FRAC_PI_2;
+        let center = Vec2::new(0.0, radius);
+        for (v, vertex) in original.iter_vertices() {
+            let (old, new) = (vertex.position, mesh.vertex_position(v));
+            // Every vertex ends up on a circle around the pivot, at the same
+            // distance it had from the center line
+            let dist = Vec2::new(new.x, new.y).distance(center);
+            assert!((dist - (radius - old.y)).abs() < 1e-4);
+            assert!((new.z - old.z).abs() < 1e-5);
+            // The end of the box points along +Y
+            if (old.x - length).abs() < 1e-5 {
+                assert!((new.x - (radius - old.y)).abs() < 1e-4);
+                assert!((new.y - radius).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_shrinkwrap_plane_to_sphere() {
+        // Approximate a unit sphere by the hull of points spread on its surface
+        let n = 200;
+        let points = (0..n)
+            .map(|i| {
+                let y = 1.0 - 2.0 * (i as f32 + 0.5) / n as f32;
+                let r = (1.0 - y * y).sqrt();
+                let theta = i as f32 * std::f32::consts::PI * (3.0 - 5.0f32.sqrt());
+                Vec3::new(r * theta.cos(), y, r * theta.sin())
+            })
+            .collect_vec();
+        let sphere = halfedge::convex_hull::convex_hull(&points).unwrap();
+
+        let plane = halfedge::primitives::Quad::build(
+            Vec3::new(0.0, 2.0, 0.0),
+            Vec3::Y,
+            Vec3::X,
+            Vec2::ONE,
+        );
+        let on_sphere = |p: Vec3| p.length() > 0.95 && p.length() < 1.0 + 1e-4;
+
+        let mut nearest = plane.clone();
+        shrinkwrap(&mut nearest, &sphere, ShrinkwrapMode::NearestSurfacePoint).unwrap();
+        for (_, vertex) in nearest.iter_vertices() {
+            assert!(on_sphere(vertex.position));
+        }
+
+        let mut projected = plane.clone();
+        shrinkwrap(&mut projected, &sphere, ShrinkwrapMode::ProjectAlongNormal).unwrap();
+        for (v, vertex) in projected.iter_vertices() {
+            let original = plane.vertex_position(v);
+            assert!(on_sphere(vertex.position));
+            // Vertices moved straight down, to the top half of the sphere
+            assert!(vertex.position.y > 0.0);
+            assert!((vertex.position.x - original.x).abs() < 1e-4);
+            assert!((vertex.position.z - original.z).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    pub fn test_taubin_smooth_keeps_volume() {
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let original = halfedge::subdivision::catmull_clark_subdivide(
+            &cube,
+            2,
+            halfedge::subdivision::BoundaryMode::KeepCorners,
+        )
+        .unwrap();
+        let volume = original.volume();
+
+        let mut laplacian = original.clone();
+        laplacian_smooth(&mut laplacian, 0.33, 20).unwrap();
+        let mut taubin = original.clone();
+        taubin_smooth(&mut taubin, 0.33, -0.34, 20).unwrap();
+
+        let laplacian_loss = (volume - laplacian.volume()).abs();
+        let taubin_loss = (volume - taubin.volume()).abs();
+        assert!(laplacian_loss > 0.2 * volume);
+        assert!(taubin_loss < 0.1 * laplacian_loss);
+    }
+
+    #[test]
+    pub fn test_laplacian_smooth_pins_boundary() {
+        // A 2x2 grid, with its only interior vertex raised above the plane
+        let quad = halfedge::primitives::Quad::build(Vec3::ZERO, Vec3::Y, Vec3::X, Vec2::ONE);
+        let mut grid = halfedge::subdivision::linear_subdivide(&quad, 1).unwrap();
+        let center = grid
+            .iter_vertices()
+            .find(|(_, v)| v.position.length() < 1e-5)
+            .unwrap()
+            .0;
+        grid.set_vertex_position(center, Vec3::Y);
+        let boundary = grid
+            .iter_vertices()
+            .filter(|(v, _)| *v != center)
+            .map(|(v, vertex)| (v, vertex.position))
+            .collect_vec();
+
+        laplacian_smooth(&mut grid, 1.0, 1).unwrap();
+        assert!(grid.vertex_position(center).length() < 1e-5);
+        for (v, pos) in boundary {
+            assert_eq!(grid.vertex_position(v), pos);
+        }
+    }
+
+    #[test]
+    pub fn test_transform_mirror() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        transform_trs(
+            &mut cube,
+            Vec3::new(0.0, 5.0, 0.0),
+            Quat::from_rotation_y(FRAC_PI_2),
+            Vec3::new(-1.0, 2.0, 1.0),
+        )
+        .unwrap();
+        let (min, max) = cube.bounding_box().unwrap();
+        assert!(min.distance(Vec3::new(-0.5, 4.0, -0.5)) < 1e-5);
+        assert!(max.distance(Vec3::new(0.5, 6.0, 0.5)) < 1e-5);
+        // The mirrored cube still faces outwards
+        assert!((cube.volume() - 2.0).abs() < 1e-4);
+        assert!(cube.non_manifold_vertices().is_empty());
+    }
+
+    #[test]
+    pub fn test_jitter_vertices() {
+        let original = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let jittered = |seed: u64, direction: JitterDirection| {
+            let mut mesh = original.clone();
+            jitter_vertices(&mut mesh, 0.1, seed, direction);
+            mesh.iter_vertices().map(|(_, v)| v.position).collect_vec()
+        };
+
+        let a = jittered(1, JitterDirection::Any);
+        assert_eq!(a, jittered(1, JitterDirection::Any));
+        assert_ne!(a, jittered(2, JitterDirection::Any));
+        for ((_, vertex), pos) in original.iter_vertices().zip(a) {
+            assert!(vertex.position.distance(pos) <= 0.1 + 1e-6);
+        }
+
+        // Cube corners move along the diagonal
+        for ((_, vertex), pos) in original
+            .iter_vertices()
+            .zip(jittered(1, JitterDirection::AlongNormal))
+        {
+            let offset = pos - vertex.position;
+            assert!(offset.cross(vertex.position).length() < 1e-5);
+        }
+    }
+
+    #[test]
+    pub fn test_displace_noise() {
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let mut displaced = cube.clone();
+        displace_noise(&mut displaced, 1.3, 0.2, 3);
+
+        let mut moved = 0;
+        for ((_, vertex), (_, moved_vertex)) in cube.iter_vertices().zip(displaced.iter_vertices())
+        {
+            // Cube corners move along the diagonal
+            let offset = moved_vertex.position - vertex.position;
+            assert!(offset.cross(vertex.position).length() < 1e-4);
+            assert!(offset.length() <= 0.2 + 1e-5);
+            if offset.length() > 1e-3 {
+                moved += 1;
+            }
+        }
+        assert!(moved > 0);
+    }
+}
@@ -9,25 +9,41 @@ use crate::prelude::*;
 /// Sometimes we need to keep this information to locate twins, and using
 /// `halfedge_to` won't work because we can't cycle the edges around a vertex
 /// fan until twins are assigned.
-type PairToHalfEdge = std::collections::HashMap<(VertexId, VertexId), HalfEdgeId>;
+pub(super) type PairToHalfEdge = std::collections::HashMap<(VertexId, VertexId), HalfEdgeId>;
 
 /// Given a list of vertices, forms a face with all of them. To call this
 /// function, the vertices should be in the right winding order and must all be
 /// part of the same boundary. If not, it will panic or may produce wrong results.
-fn add_face(
+pub(super) fn add_face(
     mesh: &mut HalfEdgeMesh,
     vertices: &[VertexId],
     pair_to_halfedge: &mut PairToHalfEdge,
 ) -> FaceId {
-    let mut halfedges = SVec::new();
-
     let f = mesh.alloc_face(None);
+    fill_face(mesh, f, vertices, pair_to_halfedge);
+    f
+}
+
+/// Same as [`add_face`], but instead of allocating a new face, the halfedges
+/// are assigned to an existing face `f`. The face's previous halfedges are no
+/// longer considered part of it, so they must be reassigned by the caller.
+fn fill_face(
+    mesh: &mut HalfEdgeMesh,
+    f: FaceId,
+    vertices: &[VertexId],
+    pair_to_halfedge: &mut PairToHalfEdge,
+) {
+    let mut halfedges = SVec::new();
 
     for (&v, &v2) in vertices.iter().circular_tuple_windows() {
         // Some vertices may already be connected by an edge. We should avoid
         // creating halfedges for those.
         let h = if let Some(&h) = pair_to_halfedge.get(&(v, v2)) {
-            // The halfedge may exist, but the face could've changed.
+            // The halfedge may exist, but the face could've changed. Its UV
+            // belongs to a corner of the old face, so it no longer applies.
+            if mesh[h].face != Some(f) {
+                mesh.halfedge_uvs.remove(&h);
+            }
             mesh[h].face = Some(f);
             h
         } else {
@@ -63,17 +79,29 @@ fn add_face(
             mesh[h_a_b].twin = Some(h_b_a);
         }
     }
-
-    f
 }
 
+/// Extrudes a single face, creating a copy of it displaced by
+/// `position_delta` and connecting both with a ring of side faces.
+///
+/// When `reuse_face` is set, the displaced face keeps the id of the original
+/// face, so it can be referenced across several extrusions. Otherwise, it
+/// gets a new id, and `face_id` is no longer valid after the operation.
+///
+/// # Returns
+/// The newly created side faces, and the displaced front face.
 pub fn extrude_face_connectivity(
     mesh: &mut HalfEdgeMesh,
     face_id: FaceId,
     position_delta: Vec3,
+    reuse_face: bool,
 ) -> (SVec<FaceId>, FaceId) {
     let vertices = mesh.at_face(face_id).vertices().unwrap();
     let halfedges = mesh.at_face(face_id).halfedges().unwrap();
+    let uvs = halfedges
+        .iter()
+        .map(|h| mesh.halfedge_uv(*h))
+        .collect::<SVec<_>>();
 
     let mut new_vertices = SVec::new();
     for &v in vertices.iter() {
@@ -106,10 +134,29 @@ pub fn extrude_face_connectivity(
         ));
     }
 
-    // TODO: Maybe reuse the old face?
-    let front_face = add_face(mesh, new_vertices.as_slice(), &mut pair_to_halfedge);
+    // The original halfedges now belong to the side faces, so the old face
+    // can either be reused for the front face or removed.
+    let front_face = if reuse_face {
+        fill_face(
+            mesh,
+            face_id,
+            new_vertices.as_slice(),
+            &mut pair_to_halfedge,
+        );
+        face_id
+    } else {
+        let front_face = add_face(mesh, new_vertices.as_slice(), &mut pair_to_halfedge);
+        mesh.remove_face(face_id);
+        front_face
+    };
 
-    mesh.faces.remove(face_id.0);
+    // The front face keeps the UVs of the original face. The side faces are
+    // left without UVs.
+    for ((&v1, &v2), uv) in new_vertices.iter().circular_tuple_windows().zip(uvs) {
+        if let Some(uv) = uv {
+            mesh.set_halfedge_uv(pair_to_halfedge[&(v1, v2)], uv);
+        }
+    }
 
     #[cfg(debug_assertions)]
     for halfedge in halfedges {
@@ -229,6 +276,9 @@ pub fn split_vertex(
 
     // Allocate *all* the new structures
     let w = mesh.alloc_vertex(v_pos + delta, None);
+    if let Some(color) = mesh.vertex_color(v) {
+        mesh.set_vertex_color(w, color);
+    }
     let h_v_w = mesh.alloc_halfedge(HalfEdge::default());
     let h_w_v = mesh.alloc_halfedge(HalfEdge::default());
     let h_l_w = mesh.alloc_halfedge(HalfEdge::default());
@@ -413,6 +463,17 @@ pub fn divide_edge(
     let f_r = mesh.at_halfedge(h_r).face().try_end().ok();
     let (v, w) = mesh.at_halfedge(h).src_dst_pair()?;
 
+    // UVs of the corners at both ends of the edge, on each side. The corner
+    // at the source of a halfedge holds its UV.
+    let uv_l = mesh.halfedge_uv(h_l).zip(
+        mesh.at_halfedge(h_l)
+            .next()
+            .try_end()
+            .ok()
+            .and_then(|h| mesh.halfedge_uv(h)),
+    );
+    let uv_r = mesh.halfedge_uv(h_r_next).zip(mesh.halfedge_uv(h_r));
+
     // Calculate the new vertex position
     let v_pos = mesh.vertex_position(v);
     let w_pos = mesh.vertex_position(w);
@@ -420,6 +481,12 @@ pub fn divide_edge(
 
     // Allocate new elements
     let x = mesh.alloc_vertex(pos, None);
+    // When only one endpoint has a color, the new vertex takes it as is.
+    match (mesh.vertex_color(v), mesh.vertex_color(w)) {
+        (Some(c_v), Some(c_w)) => mesh.set_vertex_color(x, c_v.lerp(c_w, interpolation_factor)),
+        (Some(c), None) | (None, Some(c)) => mesh.set_vertex_color(x, c),
+        (None, None) => {}
+    }
     let h_l_2 = mesh.alloc_halfedge(HalfEdge::default());
     let h_r_2 = mesh.alloc_halfedge(HalfEdge::default());
 
@@ -450,6 +517,86 @@ pub fn divide_edge(
     mesh[x].halfedge = Some(h_l);
     mesh[v].halfedge = Some(h_l_2);
 
+    // UVs: h_l_2 takes the corner at v, and the corners at x are
+    // interpolated along the edge.
+    if let Some((uv_v, uv_w)) = uv_l {
+        mesh.set_halfedge_uv(h_l_2, uv_v);
+        mesh.set_halfedge_uv(h_l, uv_v.lerp(uv_w, interpolation_factor));
+    }
+    if let Some((uv_v, uv_w)) = uv_r {
+        mesh.set_halfedge_uv(h_r_2, uv_v.lerp(uv_w, interpolation_factor));
+    }
+
+    Ok(x)
+}
+
+/// Divides an edge into `segments` pieces of equal length, by repeatedly
+/// calling [`divide_edge`]. Returns the new vertices, in order from the
+/// source of `h` to its destination. No vertices are created when
+/// `segments` is 1.
+///
+/// ## Id Stability
+/// Like in [`divide_edge`], the halfedge "h" will remain on the last segment,
+/// the one ending at the original destination of "h".
+pub fn subdivide_edge(
+    mesh: &mut HalfEdgeMesh,
+    h: HalfEdgeId,
+    segments: u32,
+) -> Result<SVec<VertexId>> {
+    if segments < 1 {
+        bail!("subdivide_edge: An edge can't be divided in less than one segment");
+    }
+    // After each division, `h` spans the remaining part of the edge, so
+    // we cut the next segment off its start.
+    (1..segments)
+        .map(|i| divide_edge(mesh, h, 1.0 / (segments - i + 1) as f32))
+        .collect()
+}
+
+/// Like [`divide_edge`], but places the new vertex on a smooth curve instead
+/// of the straight segment between the endpoints, so repeatedly dividing an
+/// edge rounds it out.
+///
+/// The curve is a Catmull-Rom spline through the endpoints, whose tangents
+/// are estimated from the edges that continue the divided edge the
+/// straightest past each endpoint. An endpoint where no edge continues
+/// forward, e.g. the end of a line, gets a tangent along the divided edge.
+///
+/// ## Id Stability
+/// Same as [`divide_edge`].
+pub fn divide_edge_smooth(mesh: &mut HalfEdgeMesh, h: HalfEdgeId, factor: f32) -> Result<VertexId> {
+    let (v, w) = mesh.at_halfedge(h).src_dst_pair()?;
+    let (p1, p2) = (mesh.vertex_position(v), mesh.vertex_position(w));
+
+    // Returns the position of the neighbor of `a` that continues the
+    // direction going from `b` to `a` the straightest, if any.
+    let continuation = |a: VertexId, b: VertexId| -> Result<Option<Vec3>> {
+        let (pa, pb) = (mesh.vertex_position(a), mesh.vertex_position(b));
+        let dir = (pa - pb).normalize_or_zero();
+        let mut best = None;
+        for out in mesh.at_vertex(a).outgoing_halfedges()? {
+            let pn = mesh.vertex_position(mesh.at_halfedge(out).dst_vertex().try_end()?);
+            let alignment = (pn - pa).normalize_or_zero().dot(dir);
+            if alignment > 0.0 && best.map(|(best, _)| alignment > best).unwrap_or(true) {
+                best = Some((alignment, pn));
+            }
+        }
+        Ok(best.map(|(_, pn)| pn))
+    };
+    let p0 = continuation(v, w)?.unwrap_or(p1 * 2.0 - p2);
+    let p3 = continuation(w, v)?.unwrap_or(p2 * 2.0 - p1);
+
+    // Cubic hermite interpolation, using the Catmull-Rom tangents
+    let (m1, m2) = ((p2 - p0) * 0.5, (p3 - p1) * 0.5);
+    let t = factor;
+    let (t2, t3) = (t * t, t * t * t);
+    let pos = p1 * (2.0 * t3 - 3.0 * t2 + 1.0)
+        + m1 * (t3 - 2.0 * t2 + t)
+        + p2 * (-2.0 * t3 + 3.0 * t2)
+        + m2 * (t3 - t2);
+
+    let x = divide_edge(mesh, h, factor)?;
+    mesh.set_vertex_position(x, pos);
     Ok(x)
 }
 
@@ -458,11 +605,9 @@ pub fn cut_face(mesh: &mut halfedge::HalfEdgeMesh, v: VertexId, w: VertexId) ->
         .at_vertex(v)
         .outgoing_halfedges()?
         .iter()
-        .map(|h| mesh.at_halfedge(*h).face().try_end())
-        .collect::<Result<SVec<FaceId>, TraversalError>>()?
-        .iter()
-        .find(|f| mesh.face_vertices(**f).contains(&w))
-        .cloned()
+        // NOTE: Boundary halfedges have no face, and are skipped.
+        .filter_map(|h| mesh[*h].face)
+        .find(|f| mesh.face_vertices(*f).contains(&w))
         .ok_or(anyhow!("cut_face: v and w must share a face"))?;
 
     if mesh.at_vertex(v).halfedge_to(w).try_end().is_ok() {
@@ -541,6 +686,93 @@ pub fn cut_face(mesh: &mut halfedge::HalfEdgeMesh, v: VertexId, w: VertexId) ->
     Ok(h_v_w)
 }
 
+/// Walks the ring of edges crossing the quads starting at `start`. The next
+/// edge in the ring is the opposite edge of the face of the current one, and
+/// the walk stops when it reaches a boundary, a face that is not a quad, or
+/// gets back to `start`.
+///
+/// Returns the halfedges of the ring, each one on the face the ring crosses
+/// after it, and whether the ring is closed.
+fn quad_edge_ring(
+    mesh: &HalfEdgeMesh,
+    start: HalfEdgeId,
+    visited: &mut HashSet<FaceId>,
+) -> Result<(SVec<HalfEdgeId>, bool)> {
+    let mut ring = SVec::new();
+    let mut h = start;
+    loop {
+        ring.push(h);
+        let face = match mesh.at_halfedge(h).face_or_boundary()? {
+            Some(face) if mesh.num_face_edges(face) == 4 => face,
+            _ => return Ok((ring, false)),
+        };
+        if !visited.insert(face) {
+            bail!("loop_cut: The edge ring crosses the same face twice")
+        }
+        h = mesh.at_halfedge(h).next().next().twin().try_end()?;
+        if h == start {
+            return Ok((ring, true));
+        }
+    }
+}
+
+/// Cuts the quads along the ring of edges that crosses `h`, inserting
+/// `num_cuts` evenly spaced edge loops. The ring is followed in both
+/// directions, and stops at the boundary of the mesh or at faces that are not
+/// quads. The edges of the ring touching those faces are still divided, so
+/// the result has no T-junctions.
+///
+/// # Returns
+/// The halfedges of the new edge loops, one loop after the other.
+pub fn loop_cut(
+    mesh: &mut HalfEdgeMesh,
+    h: HalfEdgeId,
+    num_cuts: usize,
+) -> Result<SVec<HalfEdgeId>> {
+    if num_cuts == 0 {
+        bail!("loop_cut: The number of cuts must be at least one")
+    }
+
+    let mut visited = HashSet::new();
+    let (mut ring, closed) = quad_edge_ring(mesh, h, &mut visited)?;
+    if !closed {
+        // Walking from the twin goes in the opposite direction, so the edges
+        // are flipped to be in the same order and orientation as the rest.
+        let twin = mesh.at_halfedge(h).twin().try_end()?;
+        let (backwards, _) = quad_edge_ring(mesh, twin, &mut visited)?;
+        ring = backwards
+            .iter()
+            .skip(1)
+            .rev()
+            .map(|h| mesh.at_halfedge(*h).twin().try_end())
+            .chain(ring.into_iter().map(Ok))
+            .collect::<Result<_, _>>()?;
+    }
+    let num_faces = if closed { ring.len() } else { ring.len() - 1 };
+    if num_faces == 0 {
+        bail!("loop_cut: The edge has no quads around it")
+    }
+
+    transaction(mesh, |mesh| {
+        // Each edge in the ring goes in the same direction as the previous
+        // one, so the new vertices at the same position in their lists get
+        // connected.
+        let new_vertices = ring
+            .iter()
+            .map(|h| subdivide_edge(mesh, *h, num_cuts as u32 + 1))
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut new_edges = SVec::new();
+        for cut in 0..num_cuts {
+            for (i, vertices) in new_vertices.iter().enumerate().take(num_faces) {
+                let next = &new_vertices[(i + 1) % new_vertices.len()];
+                new_edges.push(cut_face(mesh, vertices[cut], next[cut])?);
+            }
+        }
+        Ok(new_edges)
+    })
+}
+
 pub fn dissolve_vertex(mesh: &mut halfedge::HalfEdgeMesh, v: VertexId) -> Result<FaceId> {
     let outgoing = mesh.at_vertex(v).outgoing_halfedges()?;
 
@@ -586,9 +818,26 @@ pub fn dissolve_vertex(mesh: &mut halfedge::HalfEdgeMesh, v: VertexId) -> Result
     Ok(new_face)
 }
 
+/// Runs `op` on a working copy of `mesh`, and only replaces `mesh` with the
+/// result when `op` succeeds. Operations doing many mutations can fail
+/// halfway through, and this ensures they never leave the mesh half-edited.
+///
+/// NOTE: The copy is a plain clone instead of a [`HalfEdgeMesh::duplicate`],
+/// because the ids the caller passed to `op` must stay valid.
+fn transaction<T>(
+    mesh: &mut HalfEdgeMesh,
+    op: impl FnOnce(&mut HalfEdgeMesh) -> Result<T>,
+) -> Result<T> {
+    let mut working_copy = mesh.clone();
+    let result = op(&mut working_copy)?;
+    *mesh = working_copy;
+    Ok(result)
+}
+
 /// Chamfers a vertex. That is, for each outgoing edge of the vertex, a new
 /// vertex will be created. All the new vertices will be joined in a new face,
-/// and the original vertex will get removed.
+/// and the original vertex will get removed. On error, the mesh is left
+/// untouched.
 /// ## Id Stability
 /// This operation guarantees that the outgoing halfedge ids are preserved.
 /// Additionally, the returned vertex id vector has the newly created vertex ids
@@ -597,6 +846,72 @@ pub fn chamfer_vertex(
     mesh: &mut halfedge::HalfEdgeMesh,
     v: VertexId,
     interpolation_factor: f32,
+) -> Result<(FaceId, SVec<VertexId>)> {
+    transaction(mesh, |mesh| {
+        chamfer_vertex_in_place(mesh, v, interpolation_factor)
+    })
+}
+
+/// Chamfers each of the `vertices`, like [`chamfer_vertex`], and returns the
+/// new faces. Chamfering a vertex next to a previous chamfer face replaces
+/// that face with a new one, so only the faces that still exist at the end
+/// are returned. On error, the mesh is left untouched.
+pub fn chamfer_vertices(
+    mesh: &mut HalfEdgeMesh,
+    vertices: &[VertexId],
+    interpolation_factor: f32,
+) -> Result<Vec<FaceId>> {
+    if vertices.iter().unique().count() != vertices.len() {
+        bail!("chamfer_vertices: The same vertex was selected more than once")
+    }
+    if let Some(v) = vertices.iter().find(|v| mesh.vertex(**v).is_none()) {
+        bail!("chamfer_vertices: Vertex {:?} is not in the mesh", v)
+    }
+    transaction(mesh, |mesh| {
+        let mut new_faces = vec![];
+        for &v in vertices {
+            let (face, _) = chamfer_vertex_in_place(mesh, v, interpolation_factor)?;
+            new_faces.push(face);
+        }
+        new_faces.retain(|f| mesh.face(*f).is_some());
+        Ok(new_faces)
+    })
+}
+
+/// Bevels a vertex. Like [`chamfer_vertex`], a new vertex is created on each
+/// of the outgoing edges of `v`, and they are joined in a new face replacing
+/// `v`. The new vertices are placed at a distance `amount` from `v`, instead
+/// of at a fraction of each edge's length.
+///
+/// Fails if `amount` is not shorter than all the outgoing edges of `v`.
+pub fn bevel_vertex(mesh: &mut HalfEdgeMesh, v: VertexId, amount: f32) -> Result<FaceId> {
+    if amount <= 0.0 {
+        bail!("bevel_vertex: The amount must be positive")
+    }
+    let v_pos = mesh.vertex_position(v);
+    let mut directions = SVec::new();
+    for h in mesh.at_vertex(v).outgoing_halfedges()? {
+        let dst = mesh.at_halfedge(h).dst_vertex().try_end()?;
+        let edge = mesh.vertex_position(dst) - v_pos;
+        if edge.length() <= amount {
+            bail!("bevel_vertex: The amount is longer than one of the edges")
+        }
+        directions.push(edge.normalize());
+    }
+
+    let (face, new_vertices) = chamfer_vertex(mesh, v, 0.0)?;
+    for (w, dir) in new_vertices.iter().zip(directions) {
+        mesh.set_vertex_position(*w, v_pos + dir * amount);
+    }
+    Ok(face)
+}
+
+/// Same as [`chamfer_vertex`], but leaves the mesh half-edited on error. Used
+/// by operations that already run in a [`transaction`].
+fn chamfer_vertex_in_place(
+    mesh: &mut halfedge::HalfEdgeMesh,
+    v: VertexId,
+    interpolation_factor: f32,
 ) -> Result<(FaceId, SVec<VertexId>)> {
     let outgoing = mesh.at_vertex(v).outgoing_halfedges()?;
     let mut vertices = SVec::new();
@@ -611,6 +926,434 @@ pub fn chamfer_vertex(
     Ok((dissolve_vertex(mesh, v)?, vertices))
 }
 
+/// Recomputes the `next` pointers of the boundary halfedges (the ones with no
+/// face) ending at `vertices`, after faces around them have been removed.
+///
+/// Around a vertex, the outgoing halfedges with a face are chained together
+/// through their faces. A boundary halfedge continues at the end of the chain
+/// that starts at its twin, i.e. the next outgoing halfedge with no face when
+/// rotating around the vertex across faces. Edges with no face on either side
+/// form their own chain, so they connect back to their twin.
+fn relink_boundary(mesh: &mut HalfEdgeMesh, vertices: &HashSet<VertexId>) -> Result<()> {
+    let mut new_next = vec![];
+    for (h, halfedge) in mesh.iter_halfedges() {
+        if halfedge.face.is_some() {
+            continue;
+        }
+        let twin = mesh.at_halfedge(h).twin().try_end()?;
+        if !vertices.contains(&mesh.at_halfedge(twin).vertex().try_end()?) {
+            continue;
+        }
+        let mut out = twin;
+        let mut count = 0;
+        while mesh[out].face.is_some() {
+            count += 1;
+            if count > super::MAX_LOOP_ITERATIONS {
+                bail!("Max number of iterations reached. Is the mesh malformed?");
+            }
+            out = mesh.at_halfedge(out).previous().twin().try_end()?;
+        }
+        new_next.push((h, out));
+    }
+    for (h, next) in new_next {
+        mesh[h].next = Some(next);
+    }
+    Ok(())
+}
+
+/// Deletes the given faces, leaving holes in their place. Their halfedges
+/// become part of the boundary of the mesh.
+///
+/// Edges that end up with no face on either side are kept as loose edges when
+/// `keep_boundary_edges` is set. Otherwise, they are removed, and so are the
+/// vertices left with no edges.
+pub fn delete_faces(
+    mesh: &mut HalfEdgeMesh,
+    faces: &[FaceId],
+    keep_boundary_edges: bool,
+) -> Result<()> {
+    let mut halfedges = vec![];
+    for &face in faces.iter().unique() {
+        halfedges.extend(mesh.at_face(face).halfedges()?);
+    }
+    for &h in &halfedges {
+        mesh[h].face = None;
+    }
+    for &face in faces.iter().unique() {
+        mesh.remove_face(face);
+    }
+
+    let mut affected_vertices = HashSet::new();
+    for &h in &halfedges {
+        let (src, dst) = mesh.at_halfedge(h).src_dst_pair()?;
+        affected_vertices.insert(src);
+        affected_vertices.insert(dst);
+    }
+
+    if !keep_boundary_edges {
+        let mut removed = HashSet::new();
+        for &h in &halfedges {
+            let twin = mesh.at_halfedge(h).twin().try_end()?;
+            if mesh[twin].face.is_none() && !removed.contains(&h) {
+                removed.insert(h);
+                removed.insert(twin);
+            }
+        }
+        for &h in &removed {
+            mesh.remove_halfedge(h);
+        }
+
+        // Point the vertices to one of their remaining halfedges, if any
+        let mut outgoing = HashMap::<VertexId, HalfEdgeId>::new();
+        for (h, halfedge) in mesh.iter_halfedges() {
+            if let Some(v) = halfedge.vertex {
+                if affected_vertices.contains(&v) {
+                    outgoing.insert(v, h);
+                }
+            }
+        }
+        for &v in &affected_vertices {
+            match outgoing.get(&v) {
+                Some(&h) => mesh[v].halfedge = Some(h),
+                None => mesh.remove_vertex(v),
+            }
+        }
+        affected_vertices.retain(|v| outgoing.contains_key(v));
+    }
+
+    relink_boundary(mesh, &affected_vertices)
+}
+
+/// Fills the hole surrounded by the boundary loop containing
+/// `boundary_halfedge` with a single new face. The boundary halfedges become
+/// the halfedges of the face, so it's connected to its neighbors and
+/// oriented like them.
+///
+/// # Returns
+/// The new face.
+pub fn fill_hole(mesh: &mut HalfEdgeMesh, boundary_halfedge: HalfEdgeId) -> Result<FaceId> {
+    let halfedge = mesh
+        .halfedge(boundary_halfedge)
+        .ok_or_else(|| anyhow!("fill_hole: The halfedge is not in the mesh"))?;
+    if halfedge.face.is_some() {
+        bail!("fill_hole: The halfedge is not on a boundary")
+    }
+
+    let num_halfedges = mesh.iter_halfedges().count();
+    let mut boundary_loop = SVec::new();
+    let mut h = boundary_halfedge;
+    loop {
+        if boundary_loop.len() > num_halfedges {
+            bail!("fill_hole: The boundary loop does not close. Is the mesh malformed?")
+        }
+        boundary_loop.push(h);
+        h = mesh.at_halfedge(h).next().try_end()?;
+        if h == boundary_halfedge {
+            break;
+        }
+        if mesh[h].face.is_some() {
+            bail!("fill_hole: The boundary loop continues into a face. Is the mesh malformed?")
+        }
+    }
+    if boundary_loop.len() < 3 {
+        bail!("fill_hole: The hole needs at least three edges to be filled")
+    }
+
+    let face = mesh.alloc_face(Some(boundary_halfedge));
+    for h in boundary_loop {
+        mesh[h].face = Some(face);
+    }
+    Ok(face)
+}
+
+/// Connects two boundary loops of the same length with a ring of quads. Each
+/// loop must be the cyclically ordered list of the halfedges with no face
+/// surrounding a hole, as returned by [`HalfEdgeMesh::boundary_loops`].
+///
+/// The vertices of both loops are paired in the way that makes the new edges
+/// the shortest overall, which avoids twisting the bridge.
+///
+/// # Returns
+/// The new faces, one for each halfedge of `loop_a`, in the same order.
+pub fn bridge_loops(
+    mesh: &mut HalfEdgeMesh,
+    loop_a: &[HalfEdgeId],
+    loop_b: &[HalfEdgeId],
+) -> Result<SVec<FaceId>> {
+    if loop_a.len() != loop_b.len() {
+        bail!(
+            "bridge_loops: The loops have different lengths, {} and {}",
+            loop_a.len(),
+            loop_b.len()
+        )
+    }
+    if loop_a.is_empty() {
+        bail!("bridge_loops: The loops are empty")
+    }
+    let loop_vertices = |boundary_loop: &[HalfEdgeId]| -> Result<SVec<VertexId>> {
+        for (&h, &h_next) in boundary_loop.iter().circular_tuple_windows() {
+            let halfedge = mesh
+                .halfedge(h)
+                .ok_or_else(|| anyhow!("bridge_loops: Halfedge {:?} is not in the mesh", h))?;
+            if halfedge.face.is_some() {
+                bail!("bridge_loops: Halfedge {:?} is not on a boundary", h)
+            }
+            if halfedge.next != Some(h_next) {
+                bail!("bridge_loops: The halfedges don't form a loop")
+            }
+        }
+        boundary_loop
+            .iter()
+            .map(|h| Ok(mesh.at_halfedge(*h).vertex().try_end()?))
+            .collect()
+    };
+    let (vertices_a, vertices_b) = (loop_vertices(loop_a)?, loop_vertices(loop_b)?);
+    if vertices_a.iter().any(|v| vertices_b.contains(v)) {
+        bail!("bridge_loops: The loops share vertices")
+    }
+
+    // Boundary loops go around their holes in opposite directions when seen
+    // from across the bridge, so vertex `i` of a is paired with vertex
+    // `offset - i` of b.
+    let n = loop_a.len();
+    let paired = |offset: usize, i: usize| vertices_b[(offset + n - i % n) % n];
+    let offset = (0..n)
+        .min_by(|&o1, &o2| {
+            let length = |offset: usize| {
+                (0..n)
+                    .map(|i| {
+                        mesh.vertex_position(vertices_a[i])
+                            .distance(mesh.vertex_position(paired(offset, i)))
+                    })
+                    .sum::<f32>()
+            };
+            length(o1).total_cmp(&length(o2))
+        })
+        .expect("Loops are not empty");
+
+    // NOTE: It's important to initialize this structure, or some halfedges
+    // would get duplicated.
+    let mut pair_to_halfedge = PairToHalfEdge::new();
+    for (boundary_loop, vertices) in [(loop_a, &vertices_a), (loop_b, &vertices_b)] {
+        for (&h, (&v, &w)) in boundary_loop
+            .iter()
+            .zip(vertices.iter().circular_tuple_windows())
+        {
+            pair_to_halfedge.insert((v, w), h);
+        }
+    }
+
+    let mut faces = SVec::new();
+    for i in 0..n {
+        faces.push(add_face(
+            mesh,
+            &[
+                vertices_a[i],
+                vertices_a[(i + 1) % n],
+                paired(offset, i + 1),
+                paired(offset, i),
+            ],
+            &mut pair_to_halfedge,
+        ));
+    }
+    Ok(faces)
+}
+
+/// Flips the orientation of the given faces, reversing the order of their
+/// vertices, so their normals point the other way.
+///
+/// Flipping a face reverses the direction of its halfedges, so the halfedges
+/// across each of its edges must be reversed too for twins to stay
+/// consistent. That's only possible when they are on the boundary or belong
+/// to another flipped face, so the selection must contain whole connected
+/// regions of the mesh, up to the boundary. An error is returned otherwise,
+/// and the mesh is left untouched.
+pub fn flip_faces(mesh: &mut HalfEdgeMesh, faces: &[FaceId]) -> Result<()> {
+    let face_set: HashSet<FaceId> = faces.iter().cloned().collect();
+
+    // The halfedges to reverse, with their original source and destination
+    let mut face_loops = vec![];
+    let mut boundary = vec![];
+    for &face in &face_set {
+        let halfedges = mesh.at_face(face).halfedges()?;
+        for &h in &halfedges {
+            let twin = mesh.at_halfedge(h).twin().try_end()?;
+            match mesh[twin].face {
+                None => boundary.push(twin),
+                Some(f) if face_set.contains(&f) => {}
+                Some(_) => bail!(
+                    "Can't flip a face next to a face that is not flipped. Select all the \
+                     connected faces instead."
+                ),
+            }
+        }
+        face_loops.push(halfedges);
+    }
+    let mut src_dst = HashMap::new();
+    for &h in face_loops.iter().flatten().chain(boundary.iter()) {
+        src_dst.insert(h, mesh.at_halfedge(h).src_dst_pair()?);
+    }
+
+    let mut vertices = HashSet::new();
+    for halfedges in &face_loops {
+        // UVs belong to the corner at the source of each halfedge, which is
+        // now the destination
+        let uvs = halfedges
+            .iter()
+            .map(|h| mesh.halfedge_uv(*h))
+            .collect::<SVec<_>>();
+        for (i, &h) in halfedges.iter().enumerate() {
+            let prev = halfedges[(i + halfedges.len() - 1) % halfedges.len()];
+            mesh[h].next = Some(prev);
+            if let Some(uv) = uvs[(i + 1) % uvs.len()] {
+                mesh.set_halfedge_uv(h, uv);
+            }
+        }
+    }
+    for (&h, &(src, dst)) in &src_dst {
+        mesh[h].vertex = Some(dst);
+        mesh[dst].halfedge = Some(h);
+        vertices.insert(src);
+        vertices.insert(dst);
+    }
+
+    relink_boundary(mesh, &vertices)
+}
+
+/// Flips the orientation of a single face, reversing the order of its
+/// vertices. Unlike [`flip_faces`], the halfedges across its edges are left
+/// untouched, so they will run in the same direction as the halfedges of the
+/// face. This is meant as a building block for operations that fix the
+/// orientation of several faces, like [`recalculate_normals`].
+pub fn flip_face(mesh: &mut HalfEdgeMesh, face_id: FaceId) -> Result<()> {
+    let halfedges = mesh.at_face(face_id).halfedges()?;
+    let src_dst = halfedges
+        .iter()
+        .map(|h| mesh.at_halfedge(*h).src_dst_pair())
+        .collect::<Result<SVec<_>, _>>()?;
+    // UVs belong to the corner at the source of each halfedge, which is now
+    // the destination
+    let uvs = halfedges
+        .iter()
+        .map(|h| mesh.halfedge_uv(*h))
+        .collect::<SVec<_>>();
+
+    let n = halfedges.len();
+    for (i, &h) in halfedges.iter().enumerate() {
+        let prev = halfedges[(i + n - 1) % n];
+        let (src, dst) = src_dst[i];
+        mesh[h].next = Some(prev);
+        mesh[h].vertex = Some(dst);
+        if let Some(uv) = uvs[(i + 1) % n] {
+            mesh.set_halfedge_uv(h, uv);
+        }
+        // The halfedge no longer starts at its old source, but the previous
+        // one does.
+        if mesh[src].halfedge == Some(h) {
+            mesh[src].halfedge = Some(prev);
+        }
+    }
+    Ok(())
+}
+
+/// Flips the orientation of every face in the mesh, so all normals point the
+/// other way. The result is consistent, with every edge's halfedges running
+/// in opposite directions.
+pub fn flip_all_faces(mesh: &mut HalfEdgeMesh) -> Result<()> {
+    let faces = mesh.iter_faces().map(|(f, _)| f).collect_vec();
+    flip_faces(mesh, &faces)
+}
+
+/// Makes the orientation of the faces consistent, so the halfedges on both
+/// sides of every edge run in opposite directions. Each connected component is
+/// flood-filled from one of its faces, flipping neighbors that disagree with
+/// it. Closed components are then flipped as a whole if their volume comes out
+/// negative, so their normals point outward.
+///
+/// Returns an error, leaving the mesh untouched, if some component can't be
+/// oriented consistently, like a Möbius strip.
+pub fn recalculate_normals(mesh: &mut HalfEdgeMesh) -> Result<()> {
+    transaction(mesh, |mesh| {
+        let mut visited = HashSet::new();
+        let faces = mesh.iter_faces().map(|(f, _)| f).collect_vec();
+        for seed in faces {
+            if !visited.insert(seed) {
+                continue;
+            }
+            let mut component = vec![seed];
+            let mut is_closed = true;
+            let mut stack = vec![seed];
+            while let Some(face) = stack.pop() {
+                for h in mesh.at_face(face).halfedges()? {
+                    let twin = mesh.at_halfedge(h).twin().try_end()?;
+                    let agrees = mesh[twin].vertex != mesh[h].vertex;
+                    let twin_face = mesh[twin].face;
+                    match twin_face {
+                        None => is_closed = false,
+                        Some(neighbor) if visited.insert(neighbor) => {
+                            if !agrees {
+                                flip_face(mesh, neighbor)?;
+                            }
+                            component.push(neighbor);
+                            stack.push(neighbor);
+                        }
+                        Some(_) if !agrees => {
+                            bail!("recalculate_normals: The mesh is not orientable")
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+
+            if is_closed && faces_volume(mesh, &component) < 0.0 {
+                flip_faces(mesh, &component)?;
+            }
+        }
+
+        // Boundary halfedges must run opposite to their twins too, which
+        // changes how they link with each other.
+        let mut boundary_vertices = HashSet::new();
+        let boundary = mesh
+            .iter_halfedges()
+            .filter(|(_, halfedge)| halfedge.face.is_none())
+            .map(|(h, _)| h)
+            .collect_vec();
+        for h in boundary {
+            let twin = mesh.at_halfedge(h).twin().try_end()?;
+            let (src, dst) = mesh.at_halfedge(twin).src_dst_pair()?;
+            if mesh[h].vertex == Some(src) {
+                mesh[h].vertex = Some(dst);
+                if mesh[src].halfedge == Some(h) {
+                    mesh[src].halfedge = Some(twin);
+                }
+            }
+            boundary_vertices.insert(src);
+            boundary_vertices.insert(dst);
+        }
+        relink_boundary(mesh, &boundary_vertices)
+    })
+}
+
+/// The signed volume enclosed by `faces`, as in [`HalfEdgeMesh::volume`].
+fn faces_volume(mesh: &HalfEdgeMesh, faces: &[FaceId]) -> f32 {
+    faces
+        .iter()
+        .map(|&face| {
+            let positions = mesh
+                .face_vertices(face)
+                .iter()
+                .map(|v| mesh.vertex_position(*v))
+                .collect::<SVec<_>>();
+            positions
+                .iter()
+                .skip(1)
+                .tuple_windows()
+                .map(|(b, c)| positions[0].dot(b.cross(*c)) / 6.0)
+                .sum::<f32>()
+        })
+        .sum()
+}
+
 /// Creates a 2-sided face on the inside of this edge. This has no effect on the
 /// resulting mesh, but it's useful as one of the building blocks of the bevel operation
 pub fn duplicate_edge(mesh: &mut HalfEdgeMesh, h: HalfEdgeId) -> Result<HalfEdgeId> {
@@ -642,9 +1385,9 @@ pub fn duplicate_edge(mesh: &mut HalfEdgeMesh, h: HalfEdgeId) -> Result<HalfEdge
     Ok(h2_v_w)
 }
 
-/// Merges the src and dst vertices of `h` so that only the first one remains
-/// TODO: This does not handle the case where a collapse edge operation would
-/// remove a face
+/// Merges the src and dst vertices of `h` so that only the first one remains.
+/// When a face next to `h` is a triangle, the collapse leaves it with only two
+/// sides, so the face is removed and its two remaining edges are merged.
 pub fn collapse_edge(mesh: &mut HalfEdgeMesh, h: HalfEdgeId) -> Result<VertexId> {
     let (v, w) = mesh.at_halfedge(h).src_dst_pair()?;
     let t = mesh.at_halfedge(h).twin().try_end()?;
@@ -685,47 +1428,205 @@ pub fn collapse_edge(mesh: &mut HalfEdgeMesh, h: HalfEdgeId) -> Result<VertexId>
     mesh.remove_halfedge(h);
     mesh.remove_vertex(w);
 
+    // --- Remove degenerate faces ---
+    if mesh[h_next].next == Some(h_prev) {
+        dissolve_two_sided_loop(mesh, h_next)?;
+    }
+    if mesh[t_next].next == Some(t_prev) {
+        dissolve_two_sided_loop(mesh, t_next)?;
+    }
+
     Ok(v)
 }
 
-/// Adjusts the connectivity of the mesh in preparation for a bevel operation.
-/// Any `halfedges` passed in will get "duplicated", and a face will be created
-/// in-between, consistently adjusting the connectivity everywhere.
-///
-/// # Returns
-/// A set of halfedges that participated in the bevel. These are the halfedges
-/// that touched any of the original faces of the mesh. Thus, it is guaranteed
-/// that any of their twins is touching a newly created face.
-fn bevel_edges_connectivity(
-    mesh: &mut HalfEdgeMesh,
-    halfedges: &[HalfEdgeId],
-) -> Result<BTreeSet<HalfEdgeId>> {
-    let mut edges_to_bevel = BTreeSet::new();
-    let mut duplicated_edges = BTreeSet::new();
-    let mut vertices_to_chamfer = BTreeSet::new();
+/// Removes the loop formed by `h` and its next halfedge, which must go back
+/// and forth between the same two vertices. The twins of both halfedges
+/// become twins of each other. If the loop is a face, the face is removed.
+fn dissolve_two_sided_loop(mesh: &mut HalfEdgeMesh, h: HalfEdgeId) -> Result<()> {
+    let h_next = mesh.at_halfedge(h).next().try_end()?;
+    let (v, x) = mesh.at_halfedge(h).src_dst_pair()?;
+    let h_twin = mesh.at_halfedge(h).twin().try_end()?;
+    let h_next_twin = mesh.at_halfedge(h_next).twin().try_end()?;
+    if h_twin == h_next {
+        bail!("collapse_edge: Collapsing would leave a dangling edge")
+    }
+    let face = mesh[h].face;
 
-    // ---- 1. Duplicate all edges -----
-    for &h in halfedges {
-        // NOTE: Ignore edges for which we already handled its twin
-        let not_yet_handled =
-            edges_to_bevel.insert(h) && edges_to_bevel.insert(mesh[h].twin.unwrap());
-        if not_yet_handled {
-            let h_dup = duplicate_edge(mesh, h)?;
-            duplicated_edges.insert(h_dup);
-            duplicated_edges.insert(mesh.at_halfedge(h_dup).next().try_end()?);
-            let (src, dst) = mesh.at_halfedge(h).src_dst_pair()?;
-            vertices_to_chamfer.insert(src);
-            vertices_to_chamfer.insert(dst);
-        }
+    // h_next_twin goes from v to x, and h_twin from x to v
+    mesh[h_twin].twin = Some(h_next_twin);
+    mesh[h_next_twin].twin = Some(h_twin);
+    if matches!(mesh[v].halfedge, Some(hv) if hv == h || hv == h_next) {
+        mesh[v].halfedge = Some(h_next_twin);
+    }
+    if matches!(mesh[x].halfedge, Some(hx) if hx == h || hx == h_next) {
+        mesh[x].halfedge = Some(h_twin);
     }
 
-    // ---- 2. Chamfer all vertices -----
-    for v in vertices_to_chamfer {
-        let outgoing_halfedges = mesh.at_vertex(v).outgoing_halfedges()?;
+    if let Some(face) = face {
+        mesh.remove_face(face);
+    }
+    mesh.remove_halfedge(h);
+    mesh.remove_halfedge(h_next);
 
-        // After the chamfer operation, some vertex pairs need to get collapsed
-        // into a single one. This binary vector has a `true` for every vertex
-        // position where that needs to happen.
+    Ok(())
+}
+
+/// Returns whether `h` can be collapsed without breaking the mesh. This is
+/// the usual link condition: The endpoints of the edge may only share the
+/// neighbors opposite to it in its adjacent triangles. Collapses that would
+/// join two boundary vertices through an interior edge, or leave a vertex
+/// with less than three edges, are rejected as well.
+fn can_collapse_edge(mesh: &HalfEdgeMesh, h: HalfEdgeId) -> Result<bool> {
+    let (v, w) = mesh.at_halfedge(h).src_dst_pair()?;
+    let t = mesh.at_halfedge(h).twin().try_end()?;
+
+    let neighbors = |x: VertexId| -> Result<HashSet<VertexId>> {
+        mesh.at_vertex(x)
+            .outgoing_halfedges()?
+            .into_iter()
+            .map(|h| Ok(mesh.at_halfedge(h).src_dst_pair()?.1))
+            .collect()
+    };
+    let is_boundary = |x: VertexId| -> Result<bool> {
+        // One of the outgoing halfedges of a boundary vertex has no face
+        Ok(mesh
+            .at_vertex(x)
+            .outgoing_halfedges()?
+            .into_iter()
+            .any(|h| mesh[h].face.is_none()))
+    };
+
+    let edge_is_boundary = mesh[h].face.is_none() || mesh[t].face.is_none();
+    if !edge_is_boundary && is_boundary(v)? && is_boundary(w)? {
+        return Ok(false);
+    }
+
+    let mut opposite = SVec::<VertexId>::new();
+    for face in [mesh[h].face, mesh[t].face].into_iter().flatten() {
+        if mesh.num_face_edges(face) == 3 {
+            opposite.extend(
+                mesh.face_vertices(face)
+                    .into_iter()
+                    .filter(|&x| x != v && x != w),
+            );
+        }
+    }
+    for &x in opposite.iter() {
+        if mesh.at_vertex(x).outgoing_halfedges()?.len() <= 3 {
+            return Ok(false);
+        }
+    }
+
+    let (v_neighbors, w_neighbors) = (neighbors(v)?, neighbors(w)?);
+    Ok(v_neighbors.intersection(&w_neighbors).count() == opposite.len())
+}
+
+/// Collapses the edges of the mesh shorter than `threshold`, returning the
+/// decimated mesh. The two endpoints of each collapsed edge are merged at
+/// its midpoint. Since collapsing moves vertices around, the mesh is checked
+/// again until no short edges are left.
+///
+/// Edges that can't be collapsed without breaking the topology of the mesh,
+/// like those that would make two faces share all their vertices, are kept
+/// even when they are short.
+pub fn decimate_short_edges(mesh: &HalfEdgeMesh, threshold: f32) -> Result<HalfEdgeMesh> {
+    decimate_short_edges_with_progress(mesh, threshold, &progress::no_progress)
+}
+
+/// Same as [`decimate_short_edges`], but reports its progress and can be
+/// cancelled. See [`Progress`].
+pub fn decimate_short_edges_with_progress(
+    mesh: &HalfEdgeMesh,
+    threshold: f32,
+    progress: Progress,
+) -> Result<HalfEdgeMesh> {
+    let edge_length = |mesh: &HalfEdgeMesh, h: HalfEdgeId| -> Result<f32> {
+        let (v, w) = mesh.at_halfedge(h).src_dst_pair()?;
+        Ok(mesh.vertex_position(v).distance(mesh.vertex_position(w)))
+    };
+
+    let mut result = mesh.clone();
+    for pass in 0.. {
+        // The number of passes is not known in advance, so each one takes
+        // half of the remaining progress.
+        let pass_progress = progress::scaled(
+            progress,
+            1.0 - 0.5f32.powi(pass),
+            1.0 - 0.5f32.powi(pass + 1),
+        );
+
+        let mut candidates = vec![];
+        for h in result.iter_edges() {
+            let length = edge_length(&result, h)?;
+            if length < threshold {
+                candidates.push((h, length));
+            }
+        }
+        candidates.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        let mut collapsed = 0;
+        for (i, (h, _)) in candidates.iter().enumerate() {
+            progress::report(&pass_progress, i as f32 / candidates.len() as f32)?;
+            // Previous collapses may have removed or stretched the edge
+            if result.halfedge(*h).is_none()
+                || edge_length(&result, *h)? >= threshold
+                || !can_collapse_edge(&result, *h)?
+            {
+                continue;
+            }
+            let (v, w) = result.at_halfedge(*h).src_dst_pair()?;
+            let midpoint = (result.vertex_position(v) + result.vertex_position(w)) * 0.5;
+            let kept = collapse_edge(&mut result, *h)?;
+            result.set_vertex_position(kept, midpoint);
+            collapsed += 1;
+        }
+
+        if collapsed == 0 {
+            break;
+        }
+    }
+    progress::report(progress, 1.0)?;
+    Ok(result)
+}
+
+/// Adjusts the connectivity of the mesh in preparation for a bevel operation.
+/// Any `halfedges` passed in will get "duplicated", and a face will be created
+/// in-between, consistently adjusting the connectivity everywhere.
+///
+/// # Returns
+/// A set of halfedges that participated in the bevel. These are the halfedges
+/// that touched any of the original faces of the mesh. Thus, it is guaranteed
+/// that any of their twins is touching a newly created face.
+fn bevel_edges_connectivity(
+    mesh: &mut HalfEdgeMesh,
+    halfedges: &[HalfEdgeId],
+) -> Result<BTreeSet<HalfEdgeId>> {
+    let mut edges_to_bevel = BTreeSet::new();
+    let mut duplicated_edges = BTreeSet::new();
+    let mut vertices_to_chamfer = BTreeSet::new();
+
+    // ---- 1. Duplicate all edges -----
+    for &h in halfedges {
+        // NOTE: Ignore edges for which we already handled its twin
+        let not_yet_handled =
+            edges_to_bevel.insert(h) && edges_to_bevel.insert(mesh[h].twin.unwrap());
+        if not_yet_handled {
+            let h_dup = duplicate_edge(mesh, h)?;
+            duplicated_edges.insert(h_dup);
+            duplicated_edges.insert(mesh.at_halfedge(h_dup).next().try_end()?);
+            let (src, dst) = mesh.at_halfedge(h).src_dst_pair()?;
+            vertices_to_chamfer.insert(src);
+            vertices_to_chamfer.insert(dst);
+        }
+    }
+
+    // ---- 2. Chamfer all vertices -----
+    for v in vertices_to_chamfer {
+        let outgoing_halfedges = mesh.at_vertex(v).outgoing_halfedges()?;
+
+        // After the chamfer operation, some vertex pairs need to get collapsed
+        // into a single one. This binary vector has a `true` for every vertex
+        // position where that needs to happen.
         let collapse_indices = outgoing_halfedges
             .iter()
             .circular_tuple_windows()
@@ -744,7 +1645,7 @@ fn bevel_edges_connectivity(
 
         // Here, we execute the chamfer operation. The returned indices are
         // guaranteed to be in the same order as `v`'s outgoing halfedges.
-        let (_, new_verts) = chamfer_vertex(mesh, v, 0.0)?;
+        let (_, new_verts) = chamfer_vertex_in_place(mesh, v, 0.0)?;
 
         let collapse_ops = new_verts
             .iter()
@@ -789,8 +1690,23 @@ fn bevel_edges_connectivity(
     Ok(edges_to_bevel)
 }
 
-/// Bevels the given vertices by a given distance amount
+/// Bevels the given vertices by a given distance amount. On error, the mesh
+/// is left untouched.
 pub fn bevel_edges(mesh: &mut HalfEdgeMesh, halfedges: &[HalfEdgeId], amount: f32) -> Result<()> {
+    transaction(mesh, |mesh| {
+        bevel_edges_in_place(mesh, halfedges, amount)?;
+        Ok(())
+    })
+}
+
+/// Same as [`bevel_edges`], but leaves the mesh half-edited on error. Returns
+/// the halfedges that participated in the bevel, like
+/// [`bevel_edges_connectivity`].
+fn bevel_edges_in_place(
+    mesh: &mut HalfEdgeMesh,
+    halfedges: &[HalfEdgeId],
+    amount: f32,
+) -> Result<BTreeSet<HalfEdgeId>> {
     let beveled_edges = bevel_edges_connectivity(mesh, halfedges)?;
 
     // --- Adjust vertex positions ---
@@ -801,7 +1717,7 @@ pub fn bevel_edges(mesh: &mut HalfEdgeMesh, halfedges: &[HalfEdgeId], amount: f3
     // depending on their location of the halfedge (head, tail resp.). The final
     // move direction of a vertice is the sum of all its pulls.
     let mut move_ops = HashMap::<VertexId, HashSet<Vec3Ord>>::new();
-    for h in beveled_edges {
+    for &h in &beveled_edges {
         mesh.add_debug_halfedge(h, DebugMark::green("bvl"));
 
         let (v, w) = mesh.at_halfedge(h).src_dst_pair()?;
@@ -826,12 +1742,172 @@ pub fn bevel_edges(mesh: &mut HalfEdgeMesh, halfedges: &[HalfEdgeId], amount: f3
         }
     }
 
+    Ok(beveled_edges)
+}
+
+/// Same as [`bevel_edges`], but the bevel is made of `segments` rows of faces
+/// instead of a single one, to round the beveled edges. The `profile`
+/// controls the shape of the rows, from flat at 0.0 to a circular arc
+/// tangent to the faces next to the bevel at 1.0. On error, the mesh is left
+/// untouched.
+pub fn bevel_edges_segments(
+    mesh: &mut HalfEdgeMesh,
+    halfedges: &[HalfEdgeId],
+    amount: f32,
+    segments: usize,
+    profile: f32,
+) -> Result<()> {
+    if segments < 1 {
+        bail!("bevel_edges_segments: The bevel needs at least one segment")
+    }
+    transaction(mesh, |mesh| {
+        let beveled_edges = bevel_edges_in_place(mesh, halfedges, amount)?;
+        if segments > 1 {
+            add_bevel_segments(mesh, &beveled_edges, segments, profile.clamp(0.0, 1.0))?;
+        }
+        Ok(())
+    })
+}
+
+/// Splits each of the faces created by [`bevel_edges_connectivity`] into
+/// `segments` rows, running along the beveled edge.
+fn add_bevel_segments(
+    mesh: &mut HalfEdgeMesh,
+    beveled_edges: &BTreeSet<HalfEdgeId>,
+    segments: usize,
+    profile: f32,
+) -> Result<()> {
+    // Each bevel face is a quad with two long sides, the twins of the beveled
+    // halfedges, and two short sides at the ends, going across the bevel.
+    let mut bevel_faces = vec![];
+    for &h in beveled_edges {
+        let face = mesh.at_halfedge(h).twin().face().try_end()?;
+        if !bevel_faces.contains(&face) {
+            bevel_faces.push(face);
+        }
+    }
+    let mut strips = vec![];
+    for face in bevel_faces {
+        let halfedges = mesh.face_edges(face);
+        let is_long = |h: &HalfEdgeId| -> Result<bool> {
+            Ok(beveled_edges.contains(&mesh.at_halfedge(*h).twin().try_end()?))
+        };
+        let long = halfedges.iter().map(is_long).collect::<Result<SVec<_>>>()?;
+        // The short sides, going from one long side to the other
+        let short_sides = match long.as_slice() {
+            [true, false, true, false] => [halfedges[1], halfedges[3]],
+            [false, true, false, true] => [halfedges[0], halfedges[2]],
+            _ => bail!("bevel_edges_segments: Only bevels made of quads can be segmented"),
+        };
+        strips.push(short_sides);
+    }
+
+    // The positions of the new vertices on each short side. Short sides can
+    // be shared by two bevel faces, so each one is only divided once.
+    let mut divisions = HashMap::<HalfEdgeId, SVec<Vec3>>::new();
+    for short in strips.iter().flatten() {
+        if divisions.contains_key(short) {
+            continue;
+        }
+        let twin = mesh.at_halfedge(*short).twin().try_end()?;
+        let positions = bevel_profile(mesh, *short, segments, profile)?;
+        divisions.insert(twin, positions.iter().rev().cloned().collect());
+        divisions.insert(*short, positions);
+    }
+
+    let mut new_vertices = HashMap::<HalfEdgeId, SVec<VertexId>>::new();
+    for short in strips.iter().flatten() {
+        if new_vertices.contains_key(short) {
+            continue;
+        }
+        let twin = mesh.at_halfedge(*short).twin().try_end()?;
+        let vertices = subdivide_edge(mesh, *short, segments as u32)?;
+        for (v, pos) in vertices.iter().zip(divisions[short].iter()) {
+            mesh.set_vertex_position(*v, *pos);
+        }
+        new_vertices.insert(twin, vertices.iter().rev().cloned().collect());
+        new_vertices.insert(*short, vertices);
+    }
+
+    // The short sides of a face go in opposite directions, so the i-th vertex
+    // of one is connected to the i-th vertex from the end of the other.
+    for [a, b] in strips {
+        let (a, b) = (&new_vertices[&a], &new_vertices[&b]);
+        for (v, w) in a.iter().zip(b.iter().rev()) {
+            cut_face(mesh, *v, *w)?;
+        }
+    }
     Ok(())
 }
 
+/// Computes the positions of the `segments - 1` vertices that divide the
+/// short side `h` of a bevel face, in order from its source to its
+/// destination. See [`bevel_edges_segments`].
+fn bevel_profile(
+    mesh: &HalfEdgeMesh,
+    h: HalfEdgeId,
+    segments: usize,
+    profile: f32,
+) -> Result<SVec<Vec3>> {
+    let (src, dst) = mesh.at_halfedge(h).src_dst_pair()?;
+    let (p, q) = (mesh.vertex_position(src), mesh.vertex_position(dst));
+
+    // The direction going from an endpoint towards the other one, along the
+    // face next to the bevel on that side, and perpendicular to the bevel.
+    let tangent = |long: HalfEdgeId, towards: Vec3| -> Result<Option<Vec3>> {
+        let (a, b) = mesh.at_halfedge(long).src_dst_pair()?;
+        let edge = mesh.vertex_position(b) - mesh.vertex_position(a);
+        let face = match mesh.at_halfedge(long).twin().face_or_boundary()? {
+            Some(face) => face,
+            None => return Ok(None),
+        };
+        let dir = mesh.face_normal(face).cross(edge).normalize_or_zero();
+        Ok(Some(if dir.dot(towards) < 0.0 { -dir } else { dir }))
+    };
+    let tangent_p = tangent(mesh.at_halfedge(h).previous().try_end()?, q - p)?;
+    let tangent_q = tangent(mesh.at_halfedge(h).next().try_end()?, p - q)?;
+
+    // The point where both tangent lines meet, which is where the original
+    // edge was. A circular arc is a rational quadratic Bézier curve, with that
+    // point as the control point.
+    let control = tangent_p.zip(tangent_q).and_then(|(u, v)| {
+        let w0 = p - q;
+        let (b, d, e) = (u.dot(v), u.dot(w0), v.dot(w0));
+        let denom = 1.0 - b * b;
+        if denom < 1e-6 {
+            return None;
+        }
+        let (s, t) = ((b * e - d) / denom, (e - b * d) / denom);
+        (s > 0.0 && t > 0.0).then(|| ((p + u * s) + (q + v * t)) * 0.5)
+    });
+
+    let positions = (1..segments)
+        .map(|i| {
+            let t = i as f32 / segments as f32;
+            let flat = p.lerp(q, t);
+            match control {
+                Some(c) => {
+                    let angle = (p - c).angle_between(q - c);
+                    let weight = (angle * 0.5).sin();
+                    let (b0, b1, b2) = ((1.0 - t).powi(2), 2.0 * t * (1.0 - t), t * t);
+                    let round = (p * b0 + c * (b1 * weight) + q * b2) / (b0 + b1 * weight + b2);
+                    flat.lerp(round, profile)
+                }
+                None => flat,
+            }
+        })
+        .collect();
+    Ok(positions)
+}
+
 /// Extrudes the given set of faces. Faces that are connected by at least one
-/// edge will be connected after the extrude.
+/// edge will be connected after the extrude. On error, the mesh is left
+/// untouched.
 pub fn extrude_faces(mesh: &mut HalfEdgeMesh, faces: &[FaceId], amount: f32) -> Result<()> {
+    transaction(mesh, |mesh| extrude_faces_in_place(mesh, faces, amount))
+}
+
+fn extrude_faces_in_place(mesh: &mut HalfEdgeMesh, faces: &[FaceId], amount: f32) -> Result<()> {
     let face_set: HashSet<FaceId> = faces.iter().cloned().collect();
 
     // Find the set of all halfedges not adjacent to another extruded face.
@@ -889,3 +1965,1678 @@ pub fn extrude_faces(mesh: &mut HalfEdgeMesh, faces: &[FaceId], amount: f32) ->
 
     Ok(())
 }
+
+/// Extrudes each of the given faces on its own, along its own normal. Unlike
+/// [`extrude_faces`], adjacent faces are not welded together: Each face gets
+/// its own side walls, even on the edges it shares with other selected faces.
+/// On error, the mesh is left untouched.
+///
+/// # Returns
+/// The extruded faces, in the same order as `faces`. They keep their ids.
+pub fn extrude_faces_individual(
+    mesh: &mut HalfEdgeMesh,
+    faces: &[FaceId],
+    amount: f32,
+) -> Result<SVec<FaceId>> {
+    if faces.iter().unique().count() != faces.len() {
+        bail!("extrude_faces_individual: The same face was selected more than once")
+    }
+    if let Some(f) = faces.iter().find(|f| mesh.face(**f).is_none()) {
+        bail!("extrude_faces_individual: Face {:?} is not in the mesh", f)
+    }
+    transaction(mesh, |mesh| {
+        // NOTE: Normals are computed upfront. Extruding a face doesn't move
+        // the vertices of its neighbors, but it's better not to rely on it.
+        let normals = faces.iter().map(|f| mesh.face_normal(*f)).collect_vec();
+        let mut front_faces = SVec::new();
+        for (&face, normal) in faces.iter().zip(normals) {
+            let (_, front) = extrude_face_connectivity(mesh, face, normal * amount, true);
+            front_faces.push(front);
+        }
+        Ok(front_faces)
+    })
+}
+
+/// Insets a single face: A smaller copy of the face is created, with each of
+/// its edges moved inwards by `amount`, and a ring of quads is created to
+/// connect it with the original outline.
+///
+/// The offset happens in the plane given by the average normal of the face,
+/// so non-planar faces are supported. Concave faces, and amounts large enough
+/// to make the inner face fold over itself, are an error. In that case, the
+/// mesh is left untouched.
+///
+/// # Returns
+/// The new inner face. The original face is removed.
+pub fn inset_face(mesh: &mut HalfEdgeMesh, face_id: FaceId, amount: f32) -> Result<FaceId> {
+    if amount <= 0.0 {
+        bail!("inset_face: The amount must be positive")
+    }
+    let vertices = mesh.at_face(face_id).vertices()?;
+    let halfedges = mesh.at_face(face_id).halfedges()?;
+    let positions = vertices
+        .iter()
+        .map(|v| mesh.vertex_position(*v))
+        .collect::<SVec<_>>();
+
+    // Newell's method, which gives the average normal for non-planar faces.
+    let normal = positions
+        .iter()
+        .circular_tuple_windows()
+        .fold(Vec3::ZERO, |n, (a, b)| n + a.cross(*b))
+        .normalize_or_zero();
+    if normal == Vec3::ZERO {
+        bail!("inset_face: The face is degenerate")
+    }
+
+    // Each edge is moved inwards by `amount`, so vertices need to move further
+    // on sharp corners to keep the new edges parallel to the old ones.
+    let inward_dir = |a: Vec3, b: Vec3| normal.cross(b - a).normalize_or_zero();
+    let mut new_positions = SVec::new();
+    for (&prev, &pos, &next) in positions.iter().circular_tuple_windows() {
+        if (pos - prev).cross(next - pos).dot(normal) < -1e-6 {
+            bail!("inset_face: Concave faces are not supported")
+        }
+        let (dir_in, dir_out) = (inward_dir(prev, pos), inward_dir(pos, next));
+        let bisector = (dir_in + dir_out).normalize_or_zero();
+        new_positions.push(pos + bisector * amount / bisector.dot(dir_in).max(0.1));
+    }
+    // NOTE: The windows above start at the second vertex.
+    new_positions.rotate_right(1);
+
+    for ((&a, &new_a), (&b, &new_b)) in positions
+        .iter()
+        .zip(new_positions.iter())
+        .circular_tuple_windows()
+    {
+        if (new_b - new_a).dot(b - a) <= 0.0 {
+            bail!("inset_face: The amount is too large for this face")
+        }
+    }
+
+    let new_vertices = new_positions
+        .iter()
+        .map(|pos| mesh.alloc_vertex(*pos, None))
+        .collect::<SVec<_>>();
+
+    // NOTE: It's important to initialize this structure, or some halfedges
+    // would get duplicated.
+    let mut pair_to_halfedge: PairToHalfEdge = vertices
+        .iter()
+        .cloned()
+        .circular_tuple_windows()
+        .zip(halfedges.iter().cloned())
+        .collect();
+
+    let material = mesh.face_material(face_id);
+    mesh.remove_face(face_id);
+
+    // v1->v2 is the direction of the existing halfedges. We need to follow that
+    // same direction to preserve mesh orientation.
+    for ((&v1, &v1_new), (&v2, &v2_new)) in vertices
+        .iter()
+        .zip(new_vertices.iter())
+        .circular_tuple_windows()
+    {
+        let ring_face = add_face(mesh, &[v1, v2, v2_new, v1_new], &mut pair_to_halfedge);
+        if material != 0 {
+            mesh.set_face_material(ring_face, material);
+        }
+    }
+
+    let inner_face = add_face(mesh, &new_vertices, &mut pair_to_halfedge);
+    if material != 0 {
+        mesh.set_face_material(inner_face, material);
+    }
+    Ok(inner_face)
+}
+
+/// Pokes a face: A new vertex is created at the center of the face, displaced
+/// along its normal by `offset`, and the face is replaced by a fan of
+/// triangles, one for each of its edges, meeting at the new vertex.
+///
+/// # Returns
+/// The new center vertex. The original face is removed.
+pub fn poke_face(mesh: &mut HalfEdgeMesh, face_id: FaceId, offset: f32) -> Result<VertexId> {
+    let vertices = mesh.at_face(face_id).vertices()?;
+    let halfedges = mesh.at_face(face_id).halfedges()?;
+    let center = mesh.face_centroid(face_id) + mesh.face_normal(face_id) * offset;
+
+    // NOTE: It's important to initialize this structure, or some halfedges
+    // would get duplicated.
+    let mut pair_to_halfedge: PairToHalfEdge = vertices
+        .iter()
+        .cloned()
+        .circular_tuple_windows()
+        .zip(halfedges.iter().cloned())
+        .collect();
+
+    let material = mesh.face_material(face_id);
+    mesh.remove_face(face_id);
+
+    let center = mesh.alloc_vertex(center, None);
+    for (&v1, &v2) in vertices.iter().circular_tuple_windows() {
+        let face = add_face(mesh, &[v1, v2, center], &mut pair_to_halfedge);
+        if material != 0 {
+            mesh.set_face_material(face, material);
+        }
+    }
+    Ok(center)
+}
+
+/// Splits a face into triangles, by cutting it from its first vertex to each
+/// of the vertices not adjacent to it. This is a fan triangulation, which
+/// gives the expected result for convex faces. Concave faces may produce
+/// triangles that fall outside the original polygon.
+///
+/// # Returns
+/// The triangles, in order around the fan. The last one keeps the id of the
+/// original face.
+pub fn triangulate_face(mesh: &mut HalfEdgeMesh, face_id: FaceId) -> Result<SVec<FaceId>> {
+    let vertices = mesh.at_face(face_id).vertices()?;
+    if vertices.len() < 4 {
+        bail!("triangulate_face: The face is already a triangle")
+    }
+
+    // Each cut splits off the triangle behind it, and the original face is
+    // left with the rest of the polygon.
+    let mut triangles = SVec::new();
+    for &w in &vertices[2..vertices.len() - 1] {
+        let h = cut_face(mesh, vertices[0], w)?;
+        triangles.push(mesh.at_halfedge(h).twin().face().try_end()?);
+    }
+    triangles.push(face_id);
+    Ok(triangles)
+}
+
+/// Triangulates all the faces of the mesh with more than three sides, using
+/// [`triangulate_face`]. Returns the number of faces that were split.
+pub fn triangulate_mesh(mesh: &mut HalfEdgeMesh) -> Result<usize> {
+    // NOTE: The faces are collected first, since triangulating allocates new
+    // faces. Those are already triangles and don't need to be visited.
+    let faces = mesh
+        .iter_faces()
+        .map(|(f, _)| f)
+        .filter(|f| mesh.num_face_edges(*f) > 3)
+        .collect_vec();
+    for &face in &faces {
+        triangulate_face(mesh, face)?;
+    }
+    Ok(faces.len())
+}
+
+/// Insets a region of faces as a whole: The outline of the region is shrunk
+/// inwards by `amount`, and a ring of new faces is created to fill the gap
+/// between the old and the new outline. Edges shared by two faces of the
+/// region are kept, so the faces of the region stay connected.
+///
+/// The boundary of the region must be made of simple loops, that is, it can't
+/// touch itself at a vertex.
+///
+/// # Returns
+/// The faces of the region, after the inset. The faces get new ids, and are
+/// returned in the same order as `faces`.
+pub fn inset_faces_region(
+    mesh: &mut HalfEdgeMesh,
+    faces: &[FaceId],
+    amount: f32,
+) -> Result<SVec<FaceId>> {
+    let faces = faces.iter().cloned().unique().collect_vec();
+    let face_set: HashSet<FaceId> = faces.iter().cloned().collect();
+
+    // The halfedges on the boundary of the region, as (halfedge, src, dst),
+    // and indexed by their source and destination vertices.
+    let mut boundary = vec![];
+    let mut outgoing_boundary = HashMap::<VertexId, HalfEdgeId>::new();
+    let mut incoming_boundary = HashMap::<VertexId, HalfEdgeId>::new();
+    for &f in faces.iter() {
+        for h in mesh.at_face(f).halfedges()? {
+            let twin_face = mesh.at_halfedge(h).twin().face_or_boundary()?;
+            if twin_face.map(|tf| face_set.contains(&tf)).unwrap_or(false) {
+                continue;
+            }
+            let (src, dst) = mesh.at_halfedge(h).src_dst_pair()?;
+            if outgoing_boundary.insert(src, h).is_some()
+                || incoming_boundary.insert(dst, h).is_some()
+            {
+                bail!("inset_faces_region: The region's boundary touches itself at a vertex")
+            }
+            boundary.push((h, src, dst));
+        }
+    }
+    if boundary.is_empty() {
+        bail!("inset_faces_region: The region has no boundary")
+    }
+
+    // Compute the new position for each boundary vertex. Each edge of the
+    // outline is moved inwards by `amount`, so vertices need to move further
+    // on sharp corners to keep the new edges parallel to the old ones.
+    let mut new_vertices = HashMap::<VertexId, VertexId>::new();
+    for &(h_out, v, _) in boundary.iter() {
+        let h_in = incoming_boundary[&v];
+        let inward_dir = |h: HalfEdgeId| -> Result<Vec3> {
+            let (src, dst) = mesh.at_halfedge(h).src_dst_pair()?;
+            let face = mesh.at_halfedge(h).face().try_end()?;
+            let edge = mesh.vertex_position(dst) - mesh.vertex_position(src);
+            Ok(mesh.face_normal(face).cross(edge).normalize_or_zero())
+        };
+        let (dir_in, dir_out) = (inward_dir(h_in)?, inward_dir(h_out)?);
+        let bisector = (dir_in + dir_out).normalize_or_zero();
+        let offset = bisector * amount / bisector.dot(dir_in).max(0.1);
+
+        let pos = mesh.vertex_position(v);
+        new_vertices.insert(v, mesh.alloc_vertex(pos + offset, None));
+    }
+    let translate = |v: VertexId| new_vertices.get(&v).cloned().unwrap_or(v);
+
+    // The inner halfedges of the region are kept, and the boundary halfedges
+    // will become the outer side of the ring faces, which keep the material
+    // of the face they came from.
+    let mut pair_to_halfedge = PairToHalfEdge::new();
+    let mut region_polygons = vec![];
+    let mut ring_materials = HashMap::<HalfEdgeId, u32>::new();
+    // NOTE: Vertices are read before doing any changes, since translating
+    // the source of a halfedge changes the destination of the previous one.
+    let face_corners = faces
+        .iter()
+        .map(|&f| -> Result<(FaceId, SVec<(HalfEdgeId, VertexId)>)> {
+            let corners = mesh
+                .at_face(f)
+                .halfedges()?
+                .iter()
+                .map(|&h| Ok((h, mesh.at_halfedge(h).vertex().try_end()?)))
+                .collect::<Result<_>>()?;
+            Ok((f, corners))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    for (f, corners) in face_corners {
+        let mut polygon = SVec::new();
+        for (&(h, src), &(_, dst)) in corners.iter().circular_tuple_windows() {
+            if outgoing_boundary.get(&src) == Some(&h) {
+                pair_to_halfedge.insert((src, dst), h);
+                ring_materials.insert(h, mesh.face_material(f));
+            } else {
+                mesh[h].vertex = Some(translate(src));
+                pair_to_halfedge.insert((translate(src), translate(dst)), h);
+            }
+            polygon.push(translate(src));
+        }
+        region_polygons.push((polygon, mesh.face_material(f)));
+        mesh.remove_face(f);
+    }
+
+    // v1->v2 is the direction of the existing halfedges. We need to follow that
+    // same direction to preserve mesh orientation.
+    for (h, v1, v2) in boundary {
+        let material = ring_materials[&h];
+        let ring_face = add_face(
+            mesh,
+            &[v1, v2, translate(v2), translate(v1)],
+            &mut pair_to_halfedge,
+        );
+        if material != 0 {
+            mesh.set_face_material(ring_face, material);
+        }
+    }
+
+    let mut new_faces = SVec::new();
+    for (polygon, material) in region_polygons {
+        let face = add_face(mesh, &polygon, &mut pair_to_halfedge);
+        if material != 0 {
+            mesh.set_face_material(face, material);
+        }
+        new_faces.push(face);
+    }
+
+    Ok(new_faces)
+}
+
+/// Builds a mesh with [`HalfEdgeMesh::build_from_polygons`] and assigns the
+/// material of each polygon to its face. Faces are allocated in the same order
+/// as the polygons, so `result.iter_faces()` can be zipped with `polygons`.
+fn build_from_polygons_with_materials(
+    positions: &[Vec3],
+    polygons: &[SVec<usize>],
+    materials: &[u32],
+) -> Result<HalfEdgeMesh> {
+    let mut result = HalfEdgeMesh::build_from_polygons(positions, polygons)?;
+    let faces = result.iter_faces().map(|(f, _)| f).collect_vec();
+    for (face, &material) in faces.into_iter().zip(materials) {
+        if material != 0 {
+            result.set_face_material(face, material);
+        }
+    }
+    Ok(result)
+}
+
+/// Welds together the vertices of the mesh that are closer than `distance`,
+/// in place, and returns the number of vertices that were merged away. Faces
+/// that collapse as a result are removed. Pairs of faces that end up sharing
+/// the same vertices, like the touching sides of two adjacent boxes, are
+/// interior to the result and get removed as well.
+///
+/// The mesh is rebuilt from scratch, so all ids change. Face materials, edge
+/// creases, UVs and vertex colors are kept. Each welded vertex takes the
+/// position and color of the first of the vertices that were merged into it.
+/// The mesh is left untouched on error.
+pub fn merge_by_distance(mesh: &mut HalfEdgeMesh, distance: f32) -> Result<usize> {
+    if distance <= 0.0 {
+        bail!("Merge distance must be positive, got {}", distance);
+    }
+
+    // Vertices are bucketed in a grid with cells of size `distance`, so only
+    // the neighboring cells need to be checked for each vertex.
+    let cell_of = |p: Vec3| (p / distance).floor().to_array().map(|x| x as i64);
+    let mut grid = HashMap::<[i64; 3], SVec<usize>>::new();
+    let mut positions = Vec::<Vec3>::new();
+    let mut colors = Vec::<Option<Vec3>>::new();
+    let mut vertex_to_index = HashMap::<VertexId, usize>::new();
+
+    for (v, vertex) in mesh.iter_vertices() {
+        let p = vertex.position;
+        let [cx, cy, cz] = cell_of(p);
+        let existing = (-1..=1)
+            .flat_map(|dx| (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz))))
+            .filter_map(|(dx, dy, dz)| grid.get(&[cx + dx, cy + dy, cz + dz]))
+            .flatten()
+            .find(|&&idx| positions[idx].distance(p) < distance)
+            .cloned();
+
+        let idx = existing.unwrap_or_else(|| {
+            positions.push(p);
+            colors.push(mesh.vertex_color(v));
+            let idx = positions.len() - 1;
+            grid.entry(cell_of(p)).or_default().push(idx);
+            idx
+        });
+        vertex_to_index.insert(v, idx);
+    }
+    let merged = vertex_to_index.len() - positions.len();
+
+    // Each corner of a polygon remembers the halfedge it comes from, so its
+    // UV can be copied over to the result.
+    let mut polygons = vec![];
+    for (face_id, _) in mesh.iter_faces() {
+        let mut polygon = SVec::<(usize, HalfEdgeId)>::new();
+        for h in mesh.face_edges(face_id) {
+            let idx = vertex_to_index[&mesh.at_halfedge(h).src_vertex().try_end()?];
+            if polygon.last().map(|(last, _)| *last) != Some(idx) {
+                polygon.push((idx, h));
+            }
+        }
+        if polygon.len() > 1 && polygon.first().map(|c| c.0) == polygon.last().map(|c| c.0) {
+            polygon.pop();
+        }
+        // Skip collapsed faces
+        if polygon.len() >= 3 && polygon.iter().map(|c| c.0).duplicates().next().is_none() {
+            polygons.push((polygon, mesh.face_material(face_id)));
+        }
+    }
+
+    let sorted_key =
+        |polygon: &SVec<(usize, HalfEdgeId)>| polygon.iter().map(|c| c.0).sorted().collect_vec();
+    let mut face_count = HashMap::<Vec<usize>, usize>::new();
+    for (polygon, _) in polygons.iter() {
+        *face_count.entry(sorted_key(polygon)).or_default() += 1;
+    }
+    let (corners, materials): (Vec<_>, Vec<u32>) = polygons
+        .into_iter()
+        .filter(|(polygon, _)| face_count[&sorted_key(polygon)] == 1)
+        .unzip();
+    let polygons = corners
+        .iter()
+        .map(|polygon| polygon.iter().map(|c| c.0).collect::<SVec<usize>>())
+        .collect_vec();
+
+    let mut result = build_from_polygons_with_materials(&positions, &polygons, &materials)?;
+    // Vertices are allocated in the order they first appear in the polygons.
+    let idx_to_vertex: HashMap<usize, VertexId> = polygons
+        .iter()
+        .flatten()
+        .cloned()
+        .unique()
+        .zip(result.iter_vertices().map(|(v, _)| v))
+        .collect();
+    for (&idx, &v) in idx_to_vertex.iter() {
+        if let Some(color) = colors[idx] {
+            result.set_vertex_color(v, color);
+        }
+    }
+
+    for polygon in corners.iter() {
+        for (i, &(idx, h)) in polygon.iter().enumerate() {
+            let (next_idx, _) = polygon[(i + 1) % polygon.len()];
+            let new_h = result
+                .at_vertex(idx_to_vertex[&idx])
+                .halfedge_to(idx_to_vertex[&next_idx])
+                .try_end()?;
+            if let Some(uv) = mesh.halfedge_uv(h) {
+                result.set_halfedge_uv(new_h, uv);
+            }
+            let crease = mesh.edge_crease(h);
+            if crease > 0.0 {
+                result.set_edge_crease(new_h, crease);
+            }
+        }
+    }
+
+    *mesh = result;
+    Ok(merged)
+}
+
+/// Returns a new mesh made of `count` copies of `mesh`, where the i-th copy
+/// is translated by `offset * i`. When `merge_distance` is set, vertices of
+/// the copies closer than that distance are welded together, so that copies
+/// touching each other form continuous geometry. See [`merge_by_distance`].
+///
+/// Fails if the result would exceed [`limits::max_elements`].
+pub fn array(
+    mesh: &HalfEdgeMesh,
+    count: u32,
+    offset: Vec3,
+    merge_distance: Option<f32>,
+) -> Result<HalfEdgeMesh> {
+    let counts = limits::ElementCounts::of_mesh(mesh);
+    let count_u64 = count as u64;
+    limits::ElementCounts {
+        vertices: counts.vertices.saturating_mul(count_u64),
+        edges: counts.edges.saturating_mul(count_u64),
+        faces: counts.faces.saturating_mul(count_u64),
+    }
+    .check("array")?;
+
+    let mut result = HalfEdgeMesh::default();
+    for i in 0..count {
+        let mut copy = mesh.clone();
+        let vertices = copy.iter_vertices().map(|(v, _)| v).collect_vec();
+        for v in vertices {
+            copy.update_vertex_position(v, |pos| pos + offset * i as f32);
+        }
+        result.merge_with(&copy);
+    }
+
+    if let Some(distance) = merge_distance {
+        merge_by_distance(&mut result, distance)?;
+    }
+    Ok(result)
+}
+
+/// Gives thickness to a surface, returning a new mesh. An inner copy of the
+/// surface is created by moving each vertex against its normal by
+/// `thickness`, and its faces are flipped to face the other way. The boundary
+/// loops of both copies are then joined by a ring of quads, so the result is
+/// closed when the surface has no other defects.
+///
+/// Negative values of `thickness` grow the surface outwards instead. Face
+/// materials are kept on both copies.
+pub fn solidify(mesh: &HalfEdgeMesh, thickness: f32) -> Result<HalfEdgeMesh> {
+    if thickness == 0.0 {
+        bail!("solidify: The thickness can't be zero")
+    }
+    let boundary_loops = mesh.boundary_loops()?;
+    let counts = limits::ElementCounts::of_mesh(mesh);
+    let boundary_edges = boundary_loops.iter().map(|l| l.len() as u64).sum::<u64>();
+    limits::ElementCounts {
+        vertices: counts.vertices.saturating_mul(2),
+        edges: counts
+            .edges
+            .saturating_mul(2)
+            .saturating_add(boundary_edges),
+        faces: counts
+            .faces
+            .saturating_mul(2)
+            .saturating_add(boundary_edges),
+    }
+    .check("solidify")?;
+
+    // The outer vertices come first, and the inner copy of vertex `i` is at
+    // `i + num_vertices`.
+    let normals = mesh.compute_vertex_normals();
+    let vertex_idx: HashMap<VertexId, usize> = mesh
+        .iter_vertices()
+        .enumerate()
+        .map(|(i, (v, _))| (v, i))
+        .collect();
+    let num_vertices = vertex_idx.len();
+    let positions = mesh
+        .iter_vertices()
+        .map(|(_, vertex)| vertex.position)
+        .chain(mesh.iter_vertices().map(|(v, vertex)| {
+            let normal = normals.get(&v).cloned().unwrap_or(Vec3::ZERO);
+            vertex.position - normal * thickness
+        }))
+        .collect_vec();
+
+    let mut polygons = vec![];
+    let mut materials = vec![];
+    for (face, _) in mesh.iter_faces() {
+        let outer = mesh
+            .face_vertices(face)
+            .iter()
+            .map(|v| vertex_idx[v])
+            .collect::<SVec<_>>();
+        let inner = outer.iter().rev().map(|i| i + num_vertices).collect();
+        polygons.push(outer);
+        polygons.push(inner);
+        materials.extend([mesh.face_material(face); 2]);
+    }
+    // Boundary halfedges go in the opposite direction of the face next to
+    // them, which is the direction the walls need.
+    for h in boundary_loops.into_iter().flatten() {
+        let (src, dst) = mesh.at_halfedge(h).src_dst_pair()?;
+        let (src, dst) = (vertex_idx[&src], vertex_idx[&dst]);
+        polygons.push(SVec::from_slice(&[
+            src,
+            dst,
+            dst + num_vertices,
+            src + num_vertices,
+        ]));
+        materials.push(0);
+    }
+
+    build_from_polygons_with_materials(&positions, &polygons, &materials)
+}
+
+/// Returns a new mesh with the original geometry and a copy of it reflected
+/// across the plane going through `plane_origin`, perpendicular to
+/// `plane_normal`. The faces of the copy are flipped, so both halves face
+/// outwards.
+///
+/// Vertices closer to the plane than `weld_threshold` are moved onto it and
+/// shared by both halves, so a mesh touching the plane results in a single
+/// connected mesh. Faces lying on the plane are interior to the result and
+/// get removed. Face materials are kept.
+pub fn mirror(
+    mesh: &HalfEdgeMesh,
+    plane_origin: Vec3,
+    plane_normal: Vec3,
+    weld_threshold: f32,
+) -> Result<HalfEdgeMesh> {
+    let normal = plane_normal.normalize_or_zero();
+    if normal == Vec3::ZERO {
+        bail!("mirror: The plane normal can't be zero")
+    }
+    if weld_threshold < 0.0 {
+        bail!("mirror: The weld threshold can't be negative")
+    }
+    let counts = limits::ElementCounts::of_mesh(mesh);
+    limits::ElementCounts {
+        vertices: counts.vertices.saturating_mul(2),
+        edges: counts.edges.saturating_mul(2),
+        faces: counts.faces.saturating_mul(2),
+    }
+    .check("mirror")?;
+
+    // The original vertices come first, followed by the reflected ones that
+    // were not welded.
+    let mut positions = vec![];
+    let mut vertex_idx = HashMap::<VertexId, usize>::new();
+    let mut mirrored_idx = HashMap::<VertexId, usize>::new();
+    let mut reflected = vec![];
+    for (v, vertex) in mesh.iter_vertices() {
+        let distance = (vertex.position - plane_origin).dot(normal);
+        vertex_idx.insert(v, positions.len());
+        if distance.abs() <= weld_threshold {
+            mirrored_idx.insert(v, positions.len());
+            positions.push(vertex.position - normal * distance);
+        } else {
+            positions.push(vertex.position);
+            reflected.push((v, vertex.position - normal * distance * 2.0));
+        }
+    }
+    for (v, pos) in reflected {
+        mirrored_idx.insert(v, positions.len());
+        positions.push(pos);
+    }
+
+    let mut polygons = vec![];
+    for (face, _) in mesh.iter_faces() {
+        let vertices = mesh.face_vertices(face);
+        let original = vertices.iter().map(|v| vertex_idx[v]).collect::<SVec<_>>();
+        let mirrored = vertices.iter().rev().map(|v| mirrored_idx[v]).collect();
+        let material = mesh.face_material(face);
+        polygons.push((original, material));
+        polygons.push((mirrored, material));
+    }
+
+    // Faces on the plane are equal to their mirrored copy
+    let mut face_count = HashMap::<Vec<usize>, usize>::new();
+    for (polygon, _) in polygons.iter() {
+        *face_count
+            .entry(polygon.iter().cloned().sorted().collect())
+            .or_default() += 1;
+    }
+    let (polygons, materials): (Vec<SVec<usize>>, Vec<u32>) = polygons
+        .into_iter()
+        .filter(|(polygon, _)| {
+            let key: Vec<usize> = polygon.iter().cloned().sorted().collect();
+            face_count[&key] == 1
+        })
+        .unzip();
+
+    build_from_polygons_with_materials(&positions, &polygons, &materials)
+}
+
+/// Returns a separate mesh for each of the connected components of `mesh`,
+/// as found by [`HalfEdgeMesh::connected_components`]. Elements in the new
+/// meshes get fresh ids. Face materials, UVs and vertex colors are kept.
+/// Vertices and edges that are not part of any face are left out.
+pub fn split_into_components(mesh: &HalfEdgeMesh) -> Result<Vec<HalfEdgeMesh>> {
+    let mut results = vec![];
+    for component in mesh.connected_components()? {
+        // Faces are visited in the same order as in the mesh
+        let faces = mesh
+            .iter_faces()
+            .map(|(f, _)| f)
+            .filter(|f| component.contains(f))
+            .collect_vec();
+
+        let mut positions = vec![];
+        let mut vertex_idx = HashMap::<VertexId, usize>::new();
+        let mut polygons = vec![];
+        for &face in &faces {
+            let polygon = mesh
+                .face_vertices(face)
+                .iter()
+                .map(|&v| {
+                    *vertex_idx.entry(v).or_insert_with(|| {
+                        positions.push(mesh.vertex_position(v));
+                        positions.len() - 1
+                    })
+                })
+                .collect::<SVec<_>>();
+            polygons.push(polygon);
+        }
+
+        let mut result = HalfEdgeMesh::build_from_polygons(&positions, &polygons)?;
+        // Vertices and faces are allocated in the same order as the
+        // positions and polygons
+        let new_vertices = result.iter_vertices().map(|(v, _)| v).collect_vec();
+        for (&v, &idx) in &vertex_idx {
+            if let Some(color) = mesh.vertex_color(v) {
+                result.set_vertex_color(new_vertices[idx], color);
+            }
+        }
+        let new_faces = result.iter_faces().map(|(f, _)| f).collect_vec();
+        for (&face, new_face) in faces.iter().zip(new_faces) {
+            let material = mesh.face_material(face);
+            if material != 0 {
+                result.set_face_material(new_face, material);
+            }
+            // UVs belong to the corner at the source of each halfedge
+            let uvs = mesh
+                .at_face(face)
+                .halfedges()?
+                .iter()
+                .filter_map(|&h| {
+                    let uv = mesh.halfedge_uv(h)?;
+                    let v = mesh[h].vertex?;
+                    Some((new_vertices[vertex_idx[&v]], uv))
+                })
+                .collect::<HashMap<_, _>>();
+            for h in result.at_face(new_face).halfedges()? {
+                if let Some(&uv) = result[h].vertex.and_then(|v| uvs.get(&v)) {
+                    result.set_halfedge_uv(h, uv);
+                }
+            }
+        }
+        results.push(result);
+    }
+    Ok(results)
+}
+
+/// Rounds the position of each vertex to the nearest multiple of `cell`,
+/// independently for each axis. Axes where `cell` is 0 are left untouched.
+///
+/// Vertices snapped to the same position are not merged. Use
+/// [`merge_by_distance`] afterwards for that.
+pub fn snap_to_grid(mesh: &mut HalfEdgeMesh, cell: Vec3) {
+    let snap = |x: f32, cell: f32| {
+        if cell == 0.0 {
+            x
+        } else {
+            (x / cell).round() * cell
+        }
+    };
+    let vertices = mesh.iter_vertices().map(|(v, _)| v).collect_vec();
+    for v in vertices {
+        mesh.update_vertex_position(v, |pos| {
+            Vec3::new(
+                snap(pos.x, cell.x),
+                snap(pos.y, cell.y),
+                snap(pos.z, cell.z),
+            )
+        });
+    }
+}
+
+/// Sweeps the `profile` polyline around the axis through `origin` with
+/// direction `axis`, by `angle` radians split into `steps` steps, building a
+/// surface of revolution. The profile also moves by `height` along the axis
+/// over the sweep, so a non-zero height gives a helix, like a screw thread.
+///
+/// A full turn with no height (a lathe) closes the surface, joining the last
+/// step with the first one. Profile points lying on the axis become a single
+/// vertex, so their faces are triangles, closing the surface at the poles.
+///
+/// Faces point outwards when the profile goes in the direction of `axis` and
+/// `angle` is positive.
+pub fn screw(
+    profile: &[Vec3],
+    origin: Vec3,
+    axis: Vec3,
+    angle: f32,
+    steps: u32,
+    height: f32,
+) -> Result<HalfEdgeMesh> {
+    if profile.len() < 2 {
+        bail!("screw: The profile needs at least two points")
+    }
+    if steps == 0 {
+        bail!("screw: The number of steps must be at least one")
+    }
+    if axis.length_squared() < 1e-12 {
+        bail!("screw: The axis can't be zero")
+    }
+    let axis = axis.normalize();
+
+    let is_lathe = height.abs() < 1e-6;
+    let closes = is_lathe && (angle.abs() - std::f32::consts::TAU).abs() < 1e-4;
+    let num_rings = if closes { steps } else { steps + 1 };
+    let on_axis = |p: Vec3| {
+        let rel = p - origin;
+        is_lathe && (rel - rel.dot(axis) * axis).length() < 1e-6
+    };
+
+    // Maps each (profile point, step) to an index in `positions`. Points on
+    // the axis are only added once.
+    let mut positions = vec![];
+    let mut indices = vec![];
+    for &p in profile {
+        if on_axis(p) {
+            indices.push(vec![positions.len(); num_rings as usize]);
+            positions.push(p);
+            continue;
+        }
+        let mut ring = vec![];
+        for step in 0..num_rings {
+            let t = step as f32 / steps as f32;
+            let rotation = Quat::from_axis_angle(axis, angle * t);
+            ring.push(positions.len());
+            positions.push(origin + rotation * (p - origin) + axis * height * t);
+        }
+        indices.push(ring);
+    }
+
+    let mut polygons = vec![];
+    for (ring_a, ring_b) in indices.iter().tuple_windows() {
+        for step in 0..steps as usize {
+            let next = (step + 1) % num_rings as usize;
+            let polygon: SVec<usize> = [ring_a[step], ring_a[next], ring_b[next], ring_b[step]]
+                .iter()
+                .cloned()
+                .dedup()
+                .collect();
+            // Segments lying entirely on the axis produce no faces
+            if polygon.len() >= 3 {
+                polygons.push(polygon);
+            }
+        }
+    }
+    if polygons.is_empty() {
+        bail!("screw: The profile lies on the axis, so it produces no faces")
+    }
+
+    HalfEdgeMesh::build_from_polygons(&positions, &polygons)
+}
+
+/// Revolves a `profile` mesh around the axis through `axis_origin` with
+/// direction `axis_dir`, by `angle` radians split into `steps` steps. The
+/// profile must be an open polyline, like the ones built with
+/// [`primitives::Polyline`]. This is [`screw`] with no height, taking the
+/// profile as a mesh: A full turn closes the surface.
+///
+/// Faces point outwards when the polyline goes in the direction of
+/// `axis_dir`, see [`HalfEdgeMesh::polyline_points`] for the order of its
+/// points.
+pub fn spin(
+    profile: &HalfEdgeMesh,
+    axis_origin: Vec3,
+    axis_dir: Vec3,
+    angle: f32,
+    steps: usize,
+) -> Result<HalfEdgeMesh> {
+    let points = profile
+        .polyline_points()
+        .map_err(|err| anyhow!("spin: Invalid profile. {}", err))?;
+    let steps = steps.try_into()?;
+    screw(&points, axis_origin, axis_dir, angle, steps, 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_array_cube() {
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+
+        // Without welding, the copies are disconnected
+        let arrayed = array(&cube, 5, Vec3::X, None).unwrap();
+        assert_eq!(arrayed.iter_vertices().count(), 5 * 8);
+        assert_eq!(arrayed.iter_faces().count(), 5 * 6);
+
+        // Adjacent copies share their touching sides, which get removed
+        let welded = array(&cube, 5, Vec3::X, Some(1e-4)).unwrap();
+        assert_eq!(welded.iter_vertices().count(), 6 * 4);
+        assert_eq!(welded.iter_faces().count(), 5 * 6 - 4 * 2);
+        assert!(welded.stats().is_closed);
+    }
+
+    #[test]
+    pub fn test_merge_by_distance_attributes() {
+        // Two quads side by side, with the shared side duplicated
+        let positions = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(2.0, 0.0, 1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+        ];
+        let polygons = vec![[0usize, 1, 2, 3], [4, 5, 6, 7]];
+        let mut mesh = HalfEdgeMesh::build_from_polygons(&positions, &polygons).unwrap();
+        let halfedges = mesh.iter_halfedges().map(|(h, _)| h).collect_vec();
+        for h in halfedges {
+            let src = mesh.at_halfedge(h).src_vertex().end();
+            let pos = mesh.vertex_position(src);
+            mesh.set_halfedge_uv(h, Vec2::new(pos.x, pos.z));
+            mesh.set_vertex_color(src, pos);
+        }
+
+        let merged = merge_by_distance(&mut mesh, 1e-4).unwrap();
+        assert_eq!(merged, 2);
+        assert_eq!(mesh.iter_vertices().count(), 6);
+        assert_eq!(mesh.iter_faces().count(), 2);
+        for (h, halfedge) in mesh.iter_halfedges() {
+            if halfedge.face.is_none() {
+                continue;
+            }
+            let src = mesh.at_halfedge(h).src_vertex().end();
+            let pos = mesh.vertex_position(src);
+            assert_eq!(mesh.halfedge_uv(h), Some(Vec2::new(pos.x, pos.z)));
+            assert_eq!(mesh.vertex_color(src), Some(pos));
+        }
+    }
+
+    #[test]
+    pub fn test_inset_faces_region() {
+        // Two quads side by side, forming a 2x1 rectangle
+        let positions = (0..=1)
+            .flat_map(|z| (0..=2).map(move |x| Vec3::new(x as f32, 0.0, z as f32)))
+            .collect_vec();
+        let polygons = vec![[0usize, 3, 4, 1], [1, 4, 5, 2]];
+        let mut mesh = HalfEdgeMesh::build_from_polygons(&positions, &polygons).unwrap();
+        let faces = mesh.iter_faces().map(|(f, _)| f).collect_vec();
+
+        let inset = inset_faces_region(&mut mesh, &faces, 0.1).unwrap();
+        assert_eq!(inset.len(), 2);
+        // The two inset faces plus a ring of 6 faces, one per outline edge
+        assert_eq!(mesh.iter_faces().count(), 8);
+        assert_eq!(mesh.iter_vertices().count(), 12);
+
+        // The inset faces form a single, smaller rectangle
+        let inner_vertices = inset
+            .iter()
+            .flat_map(|f| mesh.face_vertices(*f))
+            .unique()
+            .collect_vec();
+        assert_eq!(inner_vertices.len(), 6);
+        for v in inner_vertices {
+            let pos = mesh.vertex_position(v);
+            assert!(
+                (pos.x - 0.1).abs() < 1e-5
+                    || (pos.x - 1.0).abs() < 1e-5
+                    || (pos.x - 1.9).abs() < 1e-5
+            );
+            assert!((pos.z - 0.1).abs() < 1e-5 || (pos.z - 0.9).abs() < 1e-5);
+        }
+
+        // The edge between the two faces is still shared
+        let shared = mesh
+            .at_face(inset[0])
+            .halfedges()
+            .unwrap()
+            .iter()
+            .filter(|h| mesh.at_halfedge(**h).twin().face().try_end().ok() == Some(inset[1]))
+            .count();
+        assert_eq!(shared, 1);
+    }
+
+    #[test]
+    pub fn test_extrude_reuse_face() {
+        let mut mesh = halfedge::primitives::Quad::build(Vec3::ZERO, Vec3::Y, Vec3::X, Vec2::ONE);
+        let face = mesh.iter_faces().next().unwrap().0;
+
+        // Extruding the same face twice, like the steps of a staircase
+        for _ in 0..2 {
+            let (_, front) = extrude_face_connectivity(&mut mesh, face, Vec3::Y, true);
+            assert_eq!(front, face);
+        }
+
+        assert_eq!(mesh.iter_faces().count(), 1 + 2 * 4);
+        for v in mesh.face_vertices(face) {
+            assert!((mesh.vertex_position(v).y - 2.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    pub fn test_uv_propagation() {
+        let mut mesh = halfedge::primitives::Quad::build(Vec3::ZERO, Vec3::Y, Vec3::X, Vec2::ONE);
+        let face = mesh.iter_faces().next().unwrap().0;
+        // A planar projection, so the expected UV of a corner can be
+        // computed from the position of its vertex.
+        let planar_uv = |mesh: &HalfEdgeMesh, h: HalfEdgeId| {
+            let pos = mesh.vertex_position(mesh.at_halfedge(h).vertex().end());
+            Vec2::new(pos.x, pos.z)
+        };
+        for h in mesh.at_face(face).halfedges().unwrap() {
+            let uv = planar_uv(&mesh, h);
+            mesh.set_halfedge_uv(h, uv);
+        }
+
+        let h = mesh.at_face(face).halfedges().unwrap()[0];
+        divide_edge(&mut mesh, h, 0.25).unwrap();
+        let (sides, front) = extrude_face_connectivity(&mut mesh, face, Vec3::Y, true);
+
+        let halfedges = mesh.at_face(front).halfedges().unwrap();
+        assert_eq!(halfedges.len(), 5);
+        for h in halfedges {
+            let uv = mesh.halfedge_uv(h).unwrap();
+            assert!(uv.distance(planar_uv(&mesh, h)) < 1e-5);
+        }
+        for side in sides {
+            for h in mesh.at_face(side).halfedges().unwrap() {
+                assert_eq!(mesh.halfedge_uv(h), None);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_spin() {
+        let points = [Vec3::ZERO, Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y];
+        let profile = halfedge::primitives::Polyline::build(&points).unwrap();
+        let steps = 8;
+        let mesh = spin(&profile, Vec3::ZERO, Vec3::Y, std::f32::consts::TAU, steps).unwrap();
+        assert_eq!(mesh.iter_vertices().count(), 2 + 2 * steps);
+        assert_eq!(mesh.iter_faces().count(), 3 * steps);
+        assert!(mesh.stats().is_closed);
+
+        // Only polylines can be used as the profile
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        assert!(spin(&cube, Vec3::ZERO, Vec3::Y, std::f32::consts::TAU, steps).is_err());
+    }
+
+    #[test]
+    pub fn test_screw_lathe() {
+        // A profile going up the side of a cylinder, closed at both poles
+        let profile = [Vec3::ZERO, Vec3::X, Vec3::new(1.0, 1.0, 0.0), Vec3::Y];
+        let steps = 8;
+        let mesh = screw(
+            &profile,
+            Vec3::ZERO,
+            Vec3::Y,
+            std::f32::consts::TAU,
+            steps,
+            0.0,
+        )
+        .unwrap();
+        assert_eq!(mesh.iter_vertices().count(), 2 + 2 * steps as usize);
+        assert_eq!(mesh.iter_faces().count(), 3 * steps as usize);
+        assert!(mesh.stats().is_closed);
+        // The side faces point outwards
+        let side = mesh
+            .iter_faces()
+            .map(|(f, _)| f)
+            .find(|f| mesh.num_face_edges(*f) == 4)
+            .unwrap();
+        let centroid = mesh.face_centroid(side);
+        assert!(
+            mesh.face_normal(side)
+                .dot(centroid * Vec3::new(1.0, 0.0, 1.0))
+                > 0.0
+        );
+
+        // Half a turn leaves the surface open
+        let half = screw(
+            &profile,
+            Vec3::ZERO,
+            Vec3::Y,
+            std::f32::consts::PI,
+            steps,
+            0.0,
+        )
+        .unwrap();
+        assert!(!half.stats().is_closed);
+    }
+
+    #[test]
+    pub fn test_subdivide_edge() {
+        let mut mesh = halfedge::primitives::Quad::build(Vec3::ZERO, Vec3::Y, Vec3::X, Vec2::ONE);
+        let h = mesh.iter_edges().next().unwrap();
+        let (src, dst) = mesh.at_halfedge(h).src_dst_pair().unwrap();
+        let (src_pos, dst_pos) = (mesh.vertex_position(src), mesh.vertex_position(dst));
+
+        assert!(subdivide_edge(&mut mesh, h, 0).is_err());
+        assert!(subdivide_edge(&mut mesh, h, 1).unwrap().is_empty());
+
+        let vertices = subdivide_edge(&mut mesh, h, 4).unwrap();
+        assert_eq!(vertices.len(), 3);
+        for (i, &v) in vertices.iter().enumerate() {
+            let expected = src_pos.lerp(dst_pos, (i + 1) as f32 / 4.0);
+            assert!(mesh.vertex_position(v).distance(expected) < 1e-5);
+        }
+        assert_eq!(
+            mesh.at_halfedge(h).src_dst_pair().unwrap(),
+            (vertices[2], dst)
+        );
+        let face = mesh.iter_faces().next().unwrap().0;
+        assert_eq!(mesh.face_vertices(face).len(), 7);
+    }
+
+    #[test]
+    pub fn test_divide_edge_colors() {
+        let mut mesh = halfedge::primitives::Quad::build(Vec3::ZERO, Vec3::Y, Vec3::X, Vec2::ONE);
+        let h = mesh.iter_edges().next().unwrap();
+        let (src, dst) = mesh.at_halfedge(h).src_dst_pair().unwrap();
+
+        // Vertices without colors don't get one
+        let x = divide_edge(&mut mesh, h, 0.5).unwrap();
+        assert_eq!(mesh.vertex_color(x), None);
+
+        mesh.set_vertex_color(x, Vec3::X);
+        mesh.set_vertex_color(dst, Vec3::Z);
+        let y = divide_edge(&mut mesh, h, 0.25).unwrap();
+        let color = mesh.vertex_color(y).unwrap();
+        assert!(color.distance(Vec3::new(0.75, 0.0, 0.25)) < 1e-5);
+
+        // A single colored endpoint is copied
+        let src_h = mesh.at_vertex(src).halfedge_to(x).try_end().unwrap();
+        let z = divide_edge(&mut mesh, src_h, 0.5).unwrap();
+        assert_eq!(mesh.vertex_color(z), Some(Vec3::X));
+    }
+
+    #[test]
+    pub fn test_divide_edge_smooth() {
+        // An arc of the unit circle, in 30 degree steps
+        let points = (0..4)
+            .map(|i| {
+                let angle = (i as f32 * 30.0).to_radians();
+                Vec3::new(angle.cos(), angle.sin(), 0.0)
+            })
+            .collect_vec();
+        let mut mesh = halfedge::primitives::Polyline::build(&points).unwrap();
+        let h = mesh
+            .iter_halfedges()
+            .map(|(h, _)| h)
+            .find(|h| {
+                let (src, dst) = mesh.at_halfedge(*h).src_dst_pair().unwrap();
+                mesh.vertex_position(src) == points[1] && mesh.vertex_position(dst) == points[2]
+            })
+            .unwrap();
+
+        let x = divide_edge_smooth(&mut mesh, h, 0.5).unwrap();
+        // The new vertex lies close to the circle, unlike the chord's midpoint
+        assert!((mesh.vertex_position(x).length() - 1.0).abs() < 0.005);
+        assert!(((points[1] + points[2]) * 0.5).length() < 0.97);
+        assert_eq!(mesh.at_halfedge(h).src_dst_pair().unwrap().0, x);
+    }
+
+    #[test]
+    pub fn test_delete_faces() {
+        let mut mesh = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let face = mesh.iter_faces().next().unwrap().0;
+        delete_faces(&mut mesh, &[face], false).unwrap();
+
+        assert_eq!(mesh.iter_faces().count(), 5);
+        assert_eq!(mesh.iter_vertices().count(), 8);
+        let loops = mesh.boundary_loops().unwrap();
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].len(), 4);
+
+        // Deleting the rest of the faces removes everything, unless the edges
+        // are kept
+        let mut wireframe = mesh.clone();
+        let faces = mesh.iter_faces().map(|(f, _)| f).collect_vec();
+        delete_faces(&mut mesh, &faces, false).unwrap();
+        assert_eq!(mesh.iter_halfedges().count(), 0);
+        assert_eq!(mesh.iter_vertices().count(), 0);
+
+        delete_faces(&mut wireframe, &faces, true).unwrap();
+        assert_eq!(wireframe.iter_edges().count(), 12);
+        assert_eq!(wireframe.iter_vertices().count(), 8);
+        assert!(wireframe.boundary_loops().is_ok());
+    }
+
+    #[test]
+    pub fn test_flip_faces() {
+        let mut quad = halfedge::primitives::Quad::build(Vec3::ZERO, Vec3::Y, Vec3::X, Vec2::ONE);
+        let face = quad.iter_faces().next().unwrap().0;
+        let normal = quad.face_normal(face);
+        flip_faces(&mut quad, &[face]).unwrap();
+
+        assert!(quad.face_normal(face).distance(-normal) < 1e-5);
+        assert!(quad.non_manifold_vertices().is_empty());
+        assert_eq!(quad.boundary_loops().unwrap()[0].len(), 4);
+
+        // On a closed mesh, only whole regions can be flipped
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let faces = cube.iter_faces().map(|(f, _)| f).collect_vec();
+        assert!(flip_faces(&mut cube, &faces[0..1]).is_err());
+        let volume = cube.volume();
+        flip_faces(&mut cube, &faces).unwrap();
+        assert!((cube.volume() + volume).abs() < 1e-5);
+        assert!(cube.non_manifold_vertices().is_empty());
+    }
+
+    #[test]
+    pub fn test_transaction_rollback() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let v = cube.iter_vertices().next().unwrap().0;
+        let position = cube.vertex_position(v);
+
+        let result = transaction(&mut cube, |mesh| -> Result<()> {
+            chamfer_vertex_in_place(mesh, v, 0.25)?;
+            bail!("Failed halfway")
+        });
+        assert!(result.is_err());
+        assert_eq!(cube.iter_vertices().count(), 8);
+        assert_eq!(cube.iter_faces().count(), 6);
+        assert_eq!(cube.vertex_position(v), position);
+
+        // Stale ids fail without corrupting the mesh
+        let (_, new_vertices) = chamfer_vertex(&mut cube, v, 0.25).unwrap();
+        assert!(chamfer_vertex(&mut cube, v, 0.25).is_err());
+        assert_eq!(cube.iter_vertices().count(), 7 + new_vertices.len());
+        assert!(cube.non_manifold_vertices().is_empty());
+    }
+
+    #[test]
+    pub fn test_inset_face() {
+        let square = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0),
+            Vec3::new(2.0, 0.0, 2.0),
+            Vec3::new(2.0, 0.0, 0.0),
+        ];
+        let mut mesh = HalfEdgeMesh::build_from_polygons(&square, &[[0usize, 1, 2, 3]]).unwrap();
+        let face = mesh.iter_faces().next().unwrap().0;
+        assert!(inset_face(&mut mesh, face, 1.0).is_err());
+        assert!(mesh.face(face).is_some());
+
+        let inner = inset_face(&mut mesh, face, 0.5).unwrap();
+        assert_eq!(mesh.iter_faces().count(), 5);
+        assert_eq!(mesh.iter_vertices().count(), 8);
+        for v in mesh.face_vertices(inner) {
+            let pos = mesh.vertex_position(v);
+            assert!(((pos.x - 1.0).abs() - 0.5).abs() < 1e-5);
+            assert!(((pos.z - 1.0).abs() - 0.5).abs() < 1e-5);
+        }
+        assert!(mesh.non_manifold_vertices().is_empty());
+        assert_eq!(mesh.boundary_loops().unwrap().len(), 1);
+
+        // An L shape
+        let l_shape = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0),
+            Vec3::new(1.0, 0.0, 2.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(2.0, 0.0, 1.0),
+            Vec3::new(2.0, 0.0, 0.0),
+        ];
+        let mut mesh =
+            HalfEdgeMesh::build_from_polygons(&l_shape, &[[0usize, 1, 2, 3, 4, 5]]).unwrap();
+        let face = mesh.iter_faces().next().unwrap().0;
+        assert!(inset_face(&mut mesh, face, 0.1).is_err());
+    }
+
+    #[test]
+    pub fn test_extrude_faces_individual() {
+        // Two quads side by side, forming a 2x1 rectangle
+        let positions = (0..=1)
+            .flat_map(|z| (0..=2).map(move |x| Vec3::new(x as f32, 0.0, z as f32)))
+            .collect_vec();
+        let polygons = vec![[0usize, 3, 4, 1], [1, 4, 5, 2]];
+        let mut mesh = HalfEdgeMesh::build_from_polygons(&positions, &polygons).unwrap();
+        let faces = mesh.iter_faces().map(|(f, _)| f).collect_vec();
+        let normal = mesh.face_normal(faces[0]);
+
+        let extruded = extrude_faces_individual(&mut mesh, &faces, 1.0).unwrap();
+        assert_eq!(extruded.as_slice(), faces.as_slice());
+        // Each face gets four walls, including the two on the shared edge
+        assert_eq!(mesh.iter_faces().count(), 2 + 8);
+        assert_eq!(mesh.iter_vertices().count(), 6 + 8);
+        for f in extruded {
+            for v in mesh.face_vertices(f) {
+                assert!((mesh.vertex_position(v).dot(normal) - 1.0).abs() < 1e-5);
+            }
+        }
+        assert!(mesh.non_manifold_vertices().is_empty());
+
+        assert!(extrude_faces_individual(&mut mesh, &[faces[0], faces[0]], 1.0).is_err());
+    }
+
+    #[test]
+    pub fn test_loop_cut() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let h = cube.iter_halfedges().next().unwrap().0;
+        let new_edges = loop_cut(&mut cube, h, 2).unwrap();
+        assert_eq!(new_edges.len(), 8);
+        assert_eq!(cube.iter_faces().count(), 6 + 8);
+        assert_eq!(cube.iter_vertices().count(), 8 + 8);
+        assert!(cube.non_manifold_vertices().is_empty());
+
+        // A quad and a triangle side by side. The ring stops at the boundary
+        // and at the triangle.
+        let positions = (0..=1)
+            .flat_map(|z| (0..=2).map(move |x| Vec3::new(x as f32, 0.0, z as f32)))
+            .collect_vec();
+        let polygons: Vec<Vec<usize>> = vec![vec![0, 3, 4, 1], vec![1, 4, 2]];
+        let mut mesh = HalfEdgeMesh::build_from_polygons(&positions, &polygons).unwrap();
+        let h = mesh
+            .iter_halfedges()
+            .map(|(h, _)| h)
+            .find(|h| {
+                let (v, w) = mesh.at_halfedge(*h).src_dst_pair().unwrap();
+                let (v, w) = (mesh.vertex_position(v), mesh.vertex_position(w));
+                v.x == 0.0 && w.x == 0.0
+            })
+            .unwrap();
+        let new_edges = loop_cut(&mut mesh, h, 1).unwrap();
+        assert_eq!(new_edges.len(), 1);
+        assert_eq!(mesh.iter_faces().count(), 3);
+        assert_eq!(mesh.iter_vertices().count(), 8);
+        let (v, w) = mesh.at_halfedge(new_edges[0]).src_dst_pair().unwrap();
+        assert!((mesh.vertex_position(v).z - 0.5).abs() < 1e-5);
+        assert!((mesh.vertex_position(w).z - 0.5).abs() < 1e-5);
+    }
+
+    #[test]
+    pub fn test_poke_face() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let face = cube.iter_faces().next().unwrap().0;
+        let expected = cube.face_centroid(face) + cube.face_normal(face) * 0.5;
+
+        let center = poke_face(&mut cube, face, 0.5).unwrap();
+        assert!(cube.vertex_position(center).distance(expected) < 1e-5);
+        assert_eq!(cube.iter_faces().count(), 5 + 4);
+        assert_eq!(
+            cube.at_vertex(center).outgoing_halfedges().unwrap().len(),
+            4
+        );
+        assert!(cube.stats().is_closed);
+    }
+
+    #[test]
+    pub fn test_triangulate_face() {
+        let hexagon = (0..6)
+            .map(|i| {
+                let angle = i as f32 * std::f32::consts::TAU / 6.0;
+                Vec3::new(angle.cos(), 0.0, angle.sin())
+            })
+            .collect_vec();
+        let mut mesh =
+            HalfEdgeMesh::build_from_polygons(&hexagon, &[[0usize, 1, 2, 3, 4, 5]]).unwrap();
+        let face = mesh.iter_faces().next().unwrap().0;
+
+        let triangles = triangulate_face(&mut mesh, face).unwrap();
+        assert_eq!(triangles.len(), 4);
+        assert_eq!(triangles.last(), Some(&face));
+        assert_eq!(mesh.iter_faces().count(), 4);
+        for f in triangles {
+            assert_eq!(mesh.num_face_edges(f), 3);
+        }
+        assert!(triangulate_face(&mut mesh, face).is_err());
+    }
+
+    #[test]
+    pub fn test_triangulate_mesh() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let volume = cube.volume();
+        assert_eq!(triangulate_mesh(&mut cube).unwrap(), 6);
+        assert_eq!(cube.iter_faces().count(), 12);
+        assert!(cube.iter_faces().all(|(f, _)| cube.num_face_edges(f) == 3));
+        assert!(cube.stats().is_closed);
+        assert!((cube.volume() - volume).abs() < 1e-5);
+
+        // Nothing left to do
+        assert_eq!(triangulate_mesh(&mut cube).unwrap(), 0);
+    }
+
+    #[test]
+    pub fn test_solidify() {
+        let quad = halfedge::primitives::Quad::build(Vec3::ZERO, Vec3::Y, Vec3::X, Vec2::ONE);
+        let solid = solidify(&quad, 0.25).unwrap();
+        assert_eq!(solid.iter_faces().count(), 2 + 4);
+        assert_eq!(solid.iter_vertices().count(), 8);
+        assert!(solid.stats().is_closed);
+        assert!((solid.volume().abs() - 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    pub fn test_mirror() {
+        // A quad touching the YZ plane
+        let quad = halfedge::primitives::Quad::build(Vec3::X * 0.5, Vec3::Y, Vec3::X, Vec2::ONE);
+        let mirrored = mirror(&quad, Vec3::ZERO, Vec3::X, 1e-3).unwrap();
+        assert_eq!(mirrored.iter_faces().count(), 2);
+        assert_eq!(mirrored.iter_vertices().count(), 6);
+        assert_eq!(mirrored.boundary_loops().unwrap().len(), 1);
+        let normals = mirrored
+            .iter_faces()
+            .map(|(f, _)| mirrored.face_normal(f))
+            .collect_vec();
+        assert!(normals[0].distance(normals[1]) < 1e-5);
+
+        // The face of the cube on the plane is removed
+        let cube = halfedge::primitives::Box::build(Vec3::X * 0.5, Vec3::ONE);
+        let mirrored = mirror(&cube, Vec3::ZERO, Vec3::X, 1e-3).unwrap();
+        assert_eq!(mirrored.iter_faces().count(), 10);
+        assert_eq!(mirrored.iter_vertices().count(), 12);
+        assert!(mirrored.stats().is_closed);
+        assert!((mirrored.volume() - 2.0 * cube.volume()).abs() < 1e-5);
+    }
+
+    #[test]
+    pub fn test_fill_hole() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let volume = cube.volume();
+        let face = cube.iter_faces().next().unwrap().0;
+        let h = cube.at_face(face).halfedge().twin().try_end().unwrap();
+        assert!(fill_hole(&mut cube, h).is_err());
+
+        delete_faces(&mut cube, &[face], true).unwrap();
+        let h = cube.boundary_loops().unwrap()[0][0];
+        let filled = fill_hole(&mut cube, h).unwrap();
+        assert_eq!(cube.num_face_edges(filled), 4);
+        assert!(cube.stats().is_closed);
+        assert!((cube.volume() - volume).abs() < 1e-5);
+    }
+
+    #[test]
+    pub fn test_bridge_loops() {
+        // An open tube, from a cube without its top and bottom faces
+        let mut tube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let caps = tube
+            .iter_faces()
+            .map(|(f, _)| f)
+            .filter(|f| tube.face_normal(*f).y.abs() > 0.5)
+            .collect_vec();
+        delete_faces(&mut tube, &caps, true).unwrap();
+        let loops = tube.boundary_loops().unwrap();
+        assert!(bridge_loops(&mut tube, &loops[0], &loops[1][1..]).is_err());
+
+        let faces = bridge_loops(&mut tube, &loops[0], &loops[1]).unwrap();
+        assert_eq!(faces.len(), 4);
+        assert!(tube.stats().is_closed);
+        // The vertices are paired straight across, without twisting
+        for f in faces {
+            for h in tube.at_face(f).halfedges().unwrap() {
+                let (v, w) = tube.at_halfedge(h).src_dst_pair().unwrap();
+                let length = tube.vertex_position(v).distance(tube.vertex_position(w));
+                assert!((length - 1.0).abs() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_bevel_edges_segments() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let on_edge = |p: Vec3| p.x > 0.0 && p.y > 0.0;
+        let h = cube
+            .iter_halfedges()
+            .map(|(h, _)| h)
+            .find(|h| {
+                let (v, w) = cube.at_halfedge(*h).src_dst_pair().unwrap();
+                on_edge(cube.vertex_position(v)) && on_edge(cube.vertex_position(w))
+            })
+            .unwrap();
+
+        bevel_edges_segments(&mut cube, &[h], 0.1, 4, 1.0).unwrap();
+        assert_eq!(cube.iter_faces().count(), 6 + 4);
+        assert_eq!(cube.iter_vertices().count(), 10 + 2 * 3);
+        assert!(cube.stats().is_closed);
+        // The vertices of the bevel lie on a quarter circle centered on the
+        // inner corner
+        let center = Vec2::new(0.4, 0.4);
+        for (_, vertex) in cube.iter_vertices() {
+            let p = vertex.position;
+            if p.x > 0.4 - 1e-5 && p.y > 0.4 - 1e-5 {
+                assert!((Vec2::new(p.x, p.y).distance(center) - 0.1).abs() < 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_chamfer_vertices() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        // Two opposite corners, which don't share any edge
+        let corner = |cube: &HalfEdgeMesh, pos: Vec3| {
+            cube.iter_vertices()
+                .find(|(_, vertex)| vertex.position.distance(pos) < 1e-4)
+                .map(|(v, _)| v)
+                .unwrap()
+        };
+        let vertices = [
+            corner(&cube, Vec3::splat(-0.5)),
+            corner(&cube, Vec3::splat(0.5)),
+        ];
+
+        let faces = chamfer_vertices(&mut cube, &vertices, 0.25).unwrap();
+        assert_eq!(faces.len(), 2);
+        assert_eq!(cube.iter_faces().count(), 6 + 2);
+        assert_eq!(cube.iter_vertices().count(), 8 - 2 + 2 * 3);
+        assert!(cube.stats().is_closed);
+
+        // The chamfered vertices are gone
+        let before = cube.iter_vertices().count();
+        assert!(chamfer_vertices(&mut cube, &vertices, 0.25).is_err());
+        assert_eq!(cube.iter_vertices().count(), before);
+    }
+
+    #[test]
+    pub fn test_bevel_vertex() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let (v, vertex) = cube.iter_vertices().next().unwrap();
+        let corner = vertex.position;
+        assert!(bevel_vertex(&mut cube, v, 1.0).is_err());
+
+        let face = bevel_vertex(&mut cube, v, 0.25).unwrap();
+        assert_eq!(cube.num_face_edges(face), 3);
+        for w in cube.face_vertices(face) {
+            assert!((cube.vertex_position(w).distance(corner) - 0.25).abs() < 1e-5);
+        }
+        assert!(cube.stats().is_closed);
+    }
+
+    #[test]
+    pub fn test_decimate_short_edges() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let h = cube.iter_edges().next().unwrap();
+        divide_edge(&mut cube, h, 0.01).unwrap();
+        assert_eq!(cube.iter_vertices().count(), 9);
+
+        let decimated = decimate_short_edges(&cube, 0.05).unwrap();
+        assert_eq!(decimated.iter_vertices().count(), 8);
+        assert_eq!(decimated.iter_faces().count(), 6);
+        assert_eq!(decimated.validate(), Ok(()));
+        assert!(decimated.stats().is_closed);
+
+        // Cancelling fails without touching the input mesh
+        let cancel = |_: f32| std::ops::ControlFlow::Break(());
+        let result = decimate_short_edges_with_progress(&cube, 0.05, &cancel);
+        assert!(result.unwrap_err().downcast_ref::<Cancelled>().is_some());
+        assert_eq!(cube.iter_vertices().count(), 9);
+    }
+
+    #[test]
+    pub fn test_decimate_short_edges_cancel() {
+        let grid =
+            halfedge::primitives::Box::build_subdivided(Vec3::ZERO, Vec3::ONE, UVec3::splat(10));
+        let num_vertices = grid.iter_vertices().count();
+        let reported = std::cell::RefCell::new(vec![]);
+        let cancel_early = |fraction: f32| {
+            reported.borrow_mut().push(fraction);
+            if fraction > 0.2 {
+                std::ops::ControlFlow::Break(())
+            } else {
+                std::ops::ControlFlow::Continue(())
+            }
+        };
+
+        // Every edge is shorter than the threshold, so the first pass alone
+        // reports progress many times before reaching the cancel point.
+        let result = decimate_short_edges_with_progress(&grid, 0.15, &cancel_early);
+        assert!(result.unwrap_err().downcast_ref::<Cancelled>().is_some());
+        let reported = reported.into_inner();
+        assert!(reported.len() > 2);
+        assert!(reported.windows(2).all(|w| w[0] <= w[1]));
+        assert!(*reported.last().unwrap() < 0.5);
+        assert_eq!(grid.iter_vertices().count(), num_vertices);
+    }
+
+    #[test]
+    pub fn test_collapse_edge_triangle() {
+        let mut tetrahedron = HalfEdgeMesh::build_from_polygons(
+            &[
+                Vec3::new(1.0, 1.0, 1.0),
+                Vec3::new(1.0, -1.0, -1.0),
+                Vec3::new(-1.0, 1.0, -1.0),
+                Vec3::new(-1.0, -1.0, 1.0),
+            ],
+            &[&[0usize, 1, 2], &[0, 3, 1], &[0, 2, 3], &[1, 3, 2]],
+        )
+        .unwrap();
+        let (h, _) = tetrahedron.iter_halfedges().next().unwrap();
+        collapse_edge(&mut tetrahedron, h).unwrap();
+
+        // Both triangles next to the edge are gone, leaving two triangles
+        // glued back to back.
+        assert_eq!(tetrahedron.iter_vertices().count(), 3);
+        assert_eq!(tetrahedron.iter_faces().count(), 2);
+        assert_eq!(tetrahedron.iter_halfedges().count(), 6);
+        assert_eq!(tetrahedron.validate(), Ok(()));
+        assert!(tetrahedron.stats().is_closed);
+        for (face, _) in tetrahedron.iter_faces() {
+            assert_eq!(tetrahedron.num_face_edges(face), 3);
+        }
+        for (v, _) in tetrahedron.iter_vertices() {
+            assert_eq!(
+                tetrahedron.at_vertex(v).outgoing_halfedges().unwrap().len(),
+                2
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_flip_face() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let face = cube.iter_faces().next().unwrap().0;
+        let normal = cube.face_normal(face);
+        let vertices = cube.face_vertices(face);
+        flip_face(&mut cube, face).unwrap();
+        assert!(cube.face_normal(face).distance(-normal) < 1e-5);
+        let mut flipped = cube.face_vertices(face);
+        flipped.reverse();
+        assert!((0..4).any(|_| {
+            flipped.rotate_left(1);
+            flipped == vertices
+        }));
+
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let volume = cube.volume();
+        flip_all_faces(&mut cube).unwrap();
+        assert!((cube.volume() + volume).abs() < 1e-5);
+        assert!(cube.non_manifold_vertices().is_empty());
+        for (h, _) in cube.iter_halfedges() {
+            let (src, dst) = cube.at_halfedge(h).src_dst_pair().unwrap();
+            let twin = cube.at_halfedge(h).twin().end();
+            assert_eq!(cube.at_halfedge(twin).src_dst_pair().unwrap(), (dst, src));
+        }
+    }
+
+    #[test]
+    pub fn test_recalculate_normals() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let faces = cube.iter_faces().map(|(f, _)| f).collect_vec();
+        flip_face(&mut cube, faces[1]).unwrap();
+        flip_face(&mut cube, faces[4]).unwrap();
+        recalculate_normals(&mut cube).unwrap();
+        assert!((cube.volume() - 1.0).abs() < 1e-5);
+        assert!(cube.non_manifold_vertices().is_empty());
+
+        // Inside-out meshes are turned back
+        flip_all_faces(&mut cube).unwrap();
+        recalculate_normals(&mut cube).unwrap();
+        assert!((cube.volume() - 1.0).abs() < 1e-5);
+
+        // Open meshes are only made consistent
+        let mut quads = HalfEdgeMesh::build_from_polygons(
+            &[
+                Vec3::new(0.0, 0.0, 0.0),
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(2.0, 0.0, 0.0),
+                Vec3::new(0.0, 0.0, 1.0),
+                Vec3::new(1.0, 0.0, 1.0),
+                Vec3::new(2.0, 0.0, 1.0),
+            ],
+            &[&[0usize, 3, 4, 1], &[1, 4, 5, 2]],
+        )
+        .unwrap();
+        let (f0, f1) = quads.iter_faces().map(|(f, _)| f).collect_tuple().unwrap();
+        flip_face(&mut quads, f1).unwrap();
+        recalculate_normals(&mut quads).unwrap();
+        assert_eq!(quads.validate(), Ok(()));
+        assert!(quads.face_normal(f0).distance(quads.face_normal(f1)) < 1e-5);
+        assert_eq!(quads.boundary_loops().unwrap().len(), 1);
+        assert_eq!(quads.boundary_loops().unwrap()[0].len(), 6);
+    }
+
+    #[test]
+    pub fn test_split_into_components() {
+        let mut mesh = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let first_face = mesh.iter_faces().next().unwrap().0;
+        mesh.set_face_material(first_face, 2);
+        mesh.merge_with(&halfedge::primitives::Box::build(Vec3::X * 3.0, Vec3::ONE));
+        mesh.merge_with(&halfedge::primitives::Quad::build(
+            Vec3::Y * 3.0,
+            Vec3::Y,
+            Vec3::X,
+            Vec2::ONE,
+        ));
+        // Isolated vertices don't belong to any component
+        mesh.alloc_vertex(Vec3::ZERO, None);
+
+        let components = mesh.connected_components().unwrap();
+        assert_eq!(
+            components.iter().map(|c| c.len()).collect_vec(),
+            vec![6, 6, 1]
+        );
+
+        let parts = split_into_components(&mesh).unwrap();
+        assert_eq!(parts.len(), 3);
+        for part in &parts[0..2] {
+            assert_eq!(part.iter_vertices().count(), 8);
+            assert!(part.stats().is_closed);
+            assert_eq!(part.validate(), Ok(()));
+        }
+        let materials = parts[0]
+            .iter_faces()
+            .map(|(f, _)| parts[0].face_material(f))
+            .collect_vec();
+        assert_eq!(materials, vec![2, 0, 0, 0, 0, 0]);
+        assert!((parts[1].centroid().unwrap() - Vec3::X * 3.0).length() < 1e-5);
+        assert_eq!(parts[2].iter_vertices().count(), 4);
+    }
+}
@@ -0,0 +1,8 @@
+/// Export to the glTF 2.0 format, both as `.gltf` + `.bin` and binary `.glb`
+pub mod gltf;
+
+/// Import / Export from the PLY (Polygon File Format), with vertex colors
+pub mod ply;
+
+/// Export to the STL format used for 3D printing, either binary or ASCII
+pub mod stl;
@@ -0,0 +1,388 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use gltf_json as json;
+use json::validation::Checked::Valid;
+
+use crate::prelude::*;
+
+/// Converts a list of vectors to the little-endian byte layout glTF expects.
+fn vec3_bytes(values: &[Vec3]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|v| v.to_array())
+        .flat_map(|x| x.to_le_bytes())
+        .collect()
+}
+
+/// Converts a list of vectors to the little-endian byte layout glTF expects.
+fn vec2_bytes(values: &[Vec2]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|v| v.to_array())
+        .flat_map(|x| x.to_le_bytes())
+        .collect()
+}
+
+/// Converts a list of indices to the little-endian byte layout glTF expects.
+fn u32_bytes(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+/// Appends `data` to the binary buffer, and registers a buffer view and an
+/// accessor pointing to it. Returns the index of the new accessor.
+///
+/// NOTE: All the components we write are 4 bytes wide, so every view starts
+/// at a properly aligned offset without any padding.
+#[allow(clippy::too_many_arguments)]
+fn add_accessor(
+    root: &mut json::Root,
+    buffer_data: &mut Vec<u8>,
+    data: &[u8],
+    count: usize,
+    type_: json::accessor::Type,
+    component_type: json::accessor::ComponentType,
+    target: json::buffer::Target,
+    bounds: Option<(Vec3, Vec3)>,
+) -> json::Index<json::Accessor> {
+    let byte_offset = buffer_data.len();
+    buffer_data.extend_from_slice(data);
+
+    root.buffer_views.push(json::buffer::View {
+        buffer: json::Index::new(0),
+        byte_length: data.len() as u32,
+        byte_offset: Some(byte_offset as u32),
+        byte_stride: None,
+        name: None,
+        target: Some(Valid(target)),
+        extensions: Default::default(),
+        extras: Default::default(),
+    });
+
+    root.accessors.push(json::Accessor {
+        buffer_view: Some(json::Index::new(root.buffer_views.len() as u32 - 1)),
+        byte_offset: 0,
+        count: count as u32,
+        component_type: Valid(json::accessor::GenericComponentType(component_type)),
+        extensions: Default::default(),
+        extras: Default::default(),
+        type_: Valid(type_),
+        min: bounds.map(|(min, _)| json::Value::from(min.to_array().to_vec())),
+        max: bounds.map(|(_, max)| json::Value::from(max.to_array().to_vec())),
+        name: None,
+        normalized: false,
+        sparse: None,
+    });
+    json::Index::new(root.accessors.len() as u32 - 1)
+}
+
+/// Writes a binary glTF container with a JSON chunk and a BIN chunk. See:
+/// https://www.khronos.org/registry/glTF/specs/2.0/glTF-2.0.html#binary-gltf-layout
+fn write_glb(path: &Path, json_str: &str, bin: &[u8]) -> Result<()> {
+    // Both chunks must be 4-byte aligned. The spec mandates padding the JSON
+    // chunk with spaces and the binary chunk with zeros.
+    let mut json_chunk = json_str.as_bytes().to_vec();
+    while json_chunk.len() % 4 != 0 {
+        json_chunk.push(b' ');
+    }
+    let mut bin_chunk = bin.to_vec();
+    while bin_chunk.len() % 4 != 0 {
+        bin_chunk.push(0);
+    }
+
+    let total_length = 12 + 8 + json_chunk.len() + 8 + bin_chunk.len();
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(b"glTF")?;
+    writer.write_all(&2u32.to_le_bytes())?;
+    writer.write_all(&(total_length as u32).to_le_bytes())?;
+
+    writer.write_all(&(json_chunk.len() as u32).to_le_bytes())?;
+    writer.write_all(b"JSON")?;
+    writer.write_all(&json_chunk)?;
+
+    writer.write_all(&(bin_chunk.len() as u32).to_le_bytes())?;
+    writer.write_all(b"BIN\0")?;
+    writer.write_all(&bin_chunk)?;
+
+    Ok(())
+}
+
+/// Exports the mesh as a glTF 2.0 scene with a single mesh node. Faces are
+/// grouped in one primitive per material id, and each primitive gets its own
+/// material slot so they can be assigned separately in the target engine.
+/// Normals are smooth, computed by [`HalfEdgeMesh::compute_vertex_normals`].
+/// When the mesh has UVs, they are written as `TEXCOORD_0`, and vertices are
+/// split along UV seams. Corners without a UV get (0, 0).
+///
+/// Fails for meshes without faces, since glTF meshes need at least one
+/// primitive.
+///
+/// When `path` has a `.glb` extension, a self-contained binary glTF is
+/// written. Otherwise, the JSON document is written at `path` and the binary
+/// buffer next to it, with the same name and a `.bin` extension.
+pub fn export(mesh: &HalfEdgeMesh, path: &Path) -> Result<()> {
+    if mesh.iter_faces().next().is_none() {
+        bail!("Cannot export a mesh without faces to glTF");
+    }
+    let normals = mesh.compute_vertex_normals();
+    let has_uvs = mesh.has_uvs();
+
+    // A BTreeMap keeps the primitives sorted by material id
+    let mut faces_by_material = BTreeMap::<u32, Vec<FaceId>>::new();
+    for (face_id, _) in mesh.iter_faces() {
+        faces_by_material
+            .entry(mesh.face_material(face_id))
+            .or_default()
+            .push(face_id);
+    }
+
+    let mut root = json::Root::default();
+    let mut buffer_data = Vec::<u8>::new();
+    let mut primitives = vec![];
+
+    for (material_id, faces) in faces_by_material {
+        // Vertices are shared between the faces of a primitive, but not
+        // across primitives. Corners of the same vertex with different UVs
+        // need separate vertices.
+        let mut index_map = HashMap::<(VertexId, [u32; 2]), u32>::new();
+        let mut positions = vec![];
+        let mut vertex_normals = vec![];
+        let mut uvs = vec![];
+        let mut indices = vec![];
+
+        for face in faces {
+            let mut face_indices = SVec::<u32>::new();
+            for h in mesh.face_edges(face) {
+                let v = mesh.at_halfedge(h).vertex().try_end()?;
+                let uv = mesh.halfedge_uv(h).unwrap_or(Vec2::ZERO);
+                let key = (v, uv.to_array().map(f32::to_bits));
+                let idx = *index_map.entry(key).or_insert_with(|| {
+                    positions.push(mesh.vertex_position(v));
+                    vertex_normals.push(normals.get(&v).cloned().unwrap_or(Vec3::Y));
+                    uvs.push(uv);
+                    (positions.len() - 1) as u32
+                });
+                face_indices.push(idx);
+            }
+
+            // Fan triangulation
+            let a = face_indices[0];
+            for (&b, &c) in face_indices[1..].iter().tuple_windows() {
+                indices.extend([a, b, c]);
+            }
+        }
+
+        // Some viewers reject files without bounds for the position accessor
+        let bounds = positions.iter().fold(
+            (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+            |(min, max), p| (min.min(*p), max.max(*p)),
+        );
+
+        let position_accessor = add_accessor(
+            &mut root,
+            &mut buffer_data,
+            &vec3_bytes(&positions),
+            positions.len(),
+            json::accessor::Type::Vec3,
+            json::accessor::ComponentType::F32,
+            json::buffer::Target::ArrayBuffer,
+            Some(bounds),
+        );
+        let normal_accessor = add_accessor(
+            &mut root,
+            &mut buffer_data,
+            &vec3_bytes(&vertex_normals),
+            vertex_normals.len(),
+            json::accessor::Type::Vec3,
+            json::accessor::ComponentType::F32,
+            json::buffer::Target::ArrayBuffer,
+            None,
+        );
+        let index_accessor = add_accessor(
+            &mut root,
+            &mut buffer_data,
+            &u32_bytes(&indices),
+            indices.len(),
+            json::accessor::Type::Scalar,
+            json::accessor::ComponentType::U32,
+            json::buffer::Target::ElementArrayBuffer,
+            None,
+        );
+
+        root.materials.push(json::Material {
+            name: Some(format!("material_{}", material_id)),
+            ..Default::default()
+        });
+
+        let mut attributes = std::collections::HashMap::new();
+        attributes.insert(Valid(json::mesh::Semantic::Positions), position_accessor);
+        attributes.insert(Valid(json::mesh::Semantic::Normals), normal_accessor);
+        if has_uvs {
+            let uv_accessor = add_accessor(
+                &mut root,
+                &mut buffer_data,
+                &vec2_bytes(&uvs),
+                uvs.len(),
+                json::accessor::Type::Vec2,
+                json::accessor::ComponentType::F32,
+                json::buffer::Target::ArrayBuffer,
+                None,
+            );
+            attributes.insert(Valid(json::mesh::Semantic::TexCoords(0)), uv_accessor);
+        }
+
+        primitives.push(json::mesh::Primitive {
+            attributes,
+            extensions: Default::default(),
+            extras: Default::default(),
+            indices: Some(index_accessor),
+            material: Some(json::Index::new(root.materials.len() as u32 - 1)),
+            mode: Valid(json::mesh::Mode::Triangles),
+            targets: None,
+        });
+    }
+
+    root.meshes.push(json::Mesh {
+        extensions: Default::default(),
+        extras: Default::default(),
+        name: Some("blackjack_mesh".into()),
+        primitives,
+        weights: None,
+    });
+    root.nodes.push(json::Node {
+        camera: None,
+        children: None,
+        extensions: Default::default(),
+        extras: Default::default(),
+        matrix: None,
+        mesh: Some(json::Index::new(0)),
+        name: None,
+        rotation: None,
+        scale: None,
+        translation: None,
+        skin: None,
+        weights: None,
+    });
+    root.scenes.push(json::Scene {
+        extensions: Default::default(),
+        extras: Default::default(),
+        name: None,
+        nodes: vec![json::Index::new(0)],
+    });
+    root.scene = Some(json::Index::new(0));
+
+    let is_glb = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("glb"))
+        .unwrap_or(false);
+
+    if is_glb {
+        root.buffers.push(json::Buffer {
+            byte_length: buffer_data.len() as u32,
+            name: None,
+            uri: None,
+            extensions: Default::default(),
+            extras: Default::default(),
+        });
+        write_glb(path, &json::serialize::to_string(&root)?, &buffer_data)?;
+    } else {
+        let bin_path = path.with_extension("bin");
+        let bin_name = bin_path
+            .file_name()
+            .ok_or_else(|| anyhow!("Invalid export path: {:?}", path))?
+            .to_string_lossy()
+            .into_owned();
+        root.buffers.push(json::Buffer {
+            byte_length: buffer_data.len() as u32,
+            name: None,
+            uri: Some(bin_name),
+            extensions: Default::default(),
+            extras: Default::default(),
+        });
+        let writer = BufWriter::new(File::create(path)?);
+        json::serialize::to_writer_pretty(writer, &root)?;
+        std::fs::write(bin_path, &buffer_data)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use json::mesh::Semantic;
+
+    /// Returns the number of elements of the accessor used by the given
+    /// attribute of the only primitive of the document.
+    fn attribute_count(root: &json::Root, semantic: Semantic) -> Option<u32> {
+        let primitive = &root.meshes[0].primitives[0];
+        let accessor = primitive.attributes.get(&Valid(semantic))?;
+        Some(root.accessors[accessor.value()].count)
+    }
+
+    #[test]
+    pub fn test_gltf_export() {
+        let mut mesh = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let path = std::env::temp_dir().join("blackjack_test_export.gltf");
+
+        export(&mesh, &path).unwrap();
+        let root = json::Root::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(root.meshes[0].primitives.len(), 1);
+        assert_eq!(attribute_count(&root, Semantic::Positions), Some(8));
+        assert_eq!(attribute_count(&root, Semantic::Normals), Some(8));
+        assert_eq!(attribute_count(&root, Semantic::TexCoords(0)), None);
+        let indices = root.meshes[0].primitives[0].indices.unwrap();
+        // Six quads, two triangles each
+        assert_eq!(root.accessors[indices.value()].count, 36);
+        let bin = std::fs::read(path.with_extension("bin")).unwrap();
+        assert_eq!(bin.len() as u32, root.buffers[0].byte_length);
+
+        // With UVs, vertices get split along the seams. Here, every face has
+        // its own UVs, so no corners are shared.
+        let faces = mesh.iter_faces().map(|(f, _)| f).collect_vec();
+        for (i, face) in faces.into_iter().enumerate() {
+            for (j, h) in mesh.face_edges(face).into_iter().enumerate() {
+                mesh.set_halfedge_uv(h, Vec2::new(i as f32, j as f32));
+            }
+        }
+        export(&mesh, &path).unwrap();
+        let root = json::Root::from_slice(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(attribute_count(&root, Semantic::Positions), Some(24));
+        assert_eq!(attribute_count(&root, Semantic::TexCoords(0)), Some(24));
+    }
+
+    #[test]
+    pub fn test_glb_export() {
+        let mesh = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let path = std::env::temp_dir().join("blackjack_test_export.glb");
+
+        export(&mesh, &path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        let read_u32 =
+            |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+        assert_eq!(&bytes[0..4], b"glTF");
+        assert_eq!(read_u32(4), 2);
+        assert_eq!(read_u32(8) as usize, bytes.len());
+
+        let json_length = read_u32(12) as usize;
+        assert_eq!(&bytes[16..20], b"JSON");
+        let root = json::Root::from_slice(&bytes[20..20 + json_length]).unwrap();
+        assert_eq!(attribute_count(&root, Semantic::Positions), Some(8));
+        assert!(root.buffers[0].uri.is_none());
+
+        let bin_start = 20 + json_length;
+        assert_eq!(&bytes[bin_start + 4..bin_start + 8], b"BIN\0");
+        assert!(read_u32(bin_start) >= root.buffers[0].byte_length);
+    }
+
+    #[test]
+    pub fn test_gltf_export_no_faces() {
+        let path = std::env::temp_dir().join("blackjack_test_export_empty.glb");
+        assert!(export(&HalfEdgeMesh::default(), &path).is_err());
+    }
+}
@@ -0,0 +1,516 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use crate::mesh::halfedge::edit_ops::{add_face, PairToHalfEdge};
+use crate::prelude::*;
+
+/// Colors are stored as linear floats in the mesh, but as 8-bit channels in
+/// the file.
+fn color_to_u8(c: f32) -> u8 {
+    (c.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Exports the mesh as a PLY file, either ASCII or, when `binary` is set,
+/// binary little endian. The vertex colors are only written when the mesh has
+/// a color attribute. Vertices without a color are written as white in that
+/// case.
+///
+/// Binary files store the size of each face in a byte, so they can't have
+/// faces with more than 255 vertices. Such meshes fail before anything is
+/// written.
+pub fn export(mesh: &HalfEdgeMesh, path: &Path, binary: bool) -> Result<()> {
+    if binary && mesh.iter_faces().any(|(f, _)| mesh.num_face_edges(f) > 255) {
+        bail!("Binary PLY faces can have at most 255 vertices");
+    }
+
+    let mut writer = BufWriter::new(File::create(path)?);
+    let has_colors = mesh.has_vertex_colors();
+
+    let mut imap = HashMap::<VertexId, usize>::new();
+    for (idx, (v_id, _)) in mesh.iter_vertices().enumerate() {
+        imap.insert(v_id, idx);
+    }
+
+    writeln!(writer, "ply")?;
+    if binary {
+        writeln!(writer, "format binary_little_endian 1.0")?;
+    } else {
+        writeln!(writer, "format ascii 1.0")?;
+    }
+    writeln!(
+        writer,
+        "comment Generated by Blackjack: https://github.com/setzer22/blackjack"
+    )?;
+    writeln!(writer, "element vertex {}", imap.len())?;
+    writeln!(writer, "property float x")?;
+    writeln!(writer, "property float y")?;
+    writeln!(writer, "property float z")?;
+    if has_colors {
+        writeln!(writer, "property uchar red")?;
+        writeln!(writer, "property uchar green")?;
+        writeln!(writer, "property uchar blue")?;
+    }
+    writeln!(writer, "element face {}", mesh.iter_faces().count())?;
+    writeln!(writer, "property list uchar int vertex_indices")?;
+    writeln!(writer, "end_header")?;
+
+    for (v_id, v) in mesh.iter_vertices() {
+        let p = v.position;
+        let color = if has_colors {
+            let c = mesh.vertex_color(v_id).unwrap_or(Vec3::ONE);
+            Some([color_to_u8(c.x), color_to_u8(c.y), color_to_u8(c.z)])
+        } else {
+            None
+        };
+
+        if binary {
+            for c in p.to_array() {
+                writer.write_all(&c.to_le_bytes())?;
+            }
+            if let Some(color) = color {
+                writer.write_all(&color)?;
+            }
+        } else if let Some([r, g, b]) = color {
+            writeln!(writer, "{} {} {} {} {} {}", p.x, p.y, p.z, r, g, b)?;
+        } else {
+            writeln!(writer, "{} {} {}", p.x, p.y, p.z)?;
+        }
+    }
+
+    for (face_id, _) in mesh.iter_faces() {
+        let vertices = mesh.face_vertices(face_id);
+        if binary {
+            // Face sizes were checked before writing anything
+            writer.write_all(&[vertices.len() as u8])?;
+            for v in vertices {
+                writer.write_all(&(imap[&v] as i32).to_le_bytes())?;
+            }
+        } else {
+            write!(writer, "{}", vertices.len())?;
+            for v in vertices {
+                write!(writer, " {}", imap[&v])?;
+            }
+            writeln!(writer)?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+/// The numeric types PLY properties can have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlyScalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl PlyScalar {
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name {
+            "char" | "int8" => PlyScalar::I8,
+            "uchar" | "uint8" => PlyScalar::U8,
+            "short" | "int16" => PlyScalar::I16,
+            "ushort" | "uint16" => PlyScalar::U16,
+            "int" | "int32" => PlyScalar::I32,
+            "uint" | "uint32" => PlyScalar::U32,
+            "float" | "float32" => PlyScalar::F32,
+            "double" | "float64" => PlyScalar::F64,
+            _ => bail!("Unknown PLY property type '{}'", name),
+        })
+    }
+
+    fn size(self) -> usize {
+        match self {
+            PlyScalar::I8 | PlyScalar::U8 => 1,
+            PlyScalar::I16 | PlyScalar::U16 => 2,
+            PlyScalar::I32 | PlyScalar::U32 | PlyScalar::F32 => 4,
+            PlyScalar::F64 => 8,
+        }
+    }
+
+    fn is_float(self) -> bool {
+        matches!(self, PlyScalar::F32 | PlyScalar::F64)
+    }
+
+    /// Decodes a little endian value. `bytes` must have the right size.
+    fn from_le_bytes(self, bytes: &[u8]) -> f64 {
+        match self {
+            PlyScalar::I8 => bytes[0] as i8 as f64,
+            PlyScalar::U8 => bytes[0] as f64,
+            PlyScalar::I16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            PlyScalar::U16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            PlyScalar::I32 => i32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            PlyScalar::U32 => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            PlyScalar::F32 => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+            PlyScalar::F64 => f64::from_le_bytes(bytes.try_into().unwrap()),
+        }
+    }
+}
+
+/// A property of a PLY element. List properties have the type of their
+/// length prefix in `list_count`.
+struct PlyProperty {
+    name: String,
+    scalar: PlyScalar,
+    list_count: Option<PlyScalar>,
+}
+
+/// An element declared in the PLY header, like `vertex` or `face`.
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+impl PlyElement {
+    fn property_index(&self, name: &str) -> Option<usize> {
+        self.properties.iter().position(|p| p.name == name)
+    }
+}
+
+/// Adds a property declared in the header to the last declared element.
+fn add_property(
+    elements: &mut [PlyElement],
+    name: &str,
+    scalar: &str,
+    list_count: Option<PlyScalar>,
+) -> Result<()> {
+    let element = elements
+        .last_mut()
+        .ok_or_else(|| anyhow!("PLY property declared before any element"))?;
+    element.properties.push(PlyProperty {
+        name: name.to_string(),
+        scalar: PlyScalar::parse(scalar)?,
+        list_count,
+    });
+    Ok(())
+}
+
+/// Reads the body of a PLY file, one element instance at a time. Each row
+/// has the values of every property, in declaration order. Scalar
+/// properties have a single value, and list properties any number of them.
+enum PlyBody<'a> {
+    Ascii(std::str::Lines<'a>),
+    BinaryLittleEndian(&'a [u8]),
+}
+
+impl<'a> PlyBody<'a> {
+    fn read_row(&mut self, element: &PlyElement) -> Result<Vec<SVec<f64>>> {
+        match self {
+            PlyBody::Ascii(lines) => {
+                let line = lines
+                    .next()
+                    .ok_or_else(|| anyhow!("Unexpected end of PLY file"))?;
+                let mut tokens = line.split_whitespace();
+                let mut next = || -> Result<f64> {
+                    Ok(tokens
+                        .next()
+                        .ok_or_else(|| anyhow!("Missing value in PLY {} line", element.name))?
+                        .parse()?)
+                };
+                element
+                    .properties
+                    .iter()
+                    .map(|property| {
+                        let len = match property.list_count {
+                            Some(_) => next()? as usize,
+                            None => 1,
+                        };
+                        (0..len).map(|_| next()).collect()
+                    })
+                    .collect()
+            }
+            PlyBody::BinaryLittleEndian(bytes) => {
+                let mut next = |scalar: PlyScalar| -> Result<f64> {
+                    let data: &'a [u8] = *bytes;
+                    if data.len() < scalar.size() {
+                        bail!("Unexpected end of PLY file");
+                    }
+                    let (value, rest) = data.split_at(scalar.size());
+                    *bytes = rest;
+                    Ok(scalar.from_le_bytes(value))
+                };
+                element
+                    .properties
+                    .iter()
+                    .map(|property| {
+                        let len = match property.list_count {
+                            Some(count) => next(count)? as usize,
+                            None => 1,
+                        };
+                        (0..len).map(|_| next(property.scalar)).collect()
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Imports a PLY file, either ASCII or binary little endian. Positions,
+/// faces and, when present, the `red`/`green`/`blue` vertex properties are
+/// read. Any other elements or properties in the file are ignored.
+pub fn import(path: &Path) -> Result<HalfEdgeMesh> {
+    let bytes = std::fs::read(path)?;
+
+    // The header is always text, even for binary files
+    let header_end = b"end_header";
+    let header_len = bytes
+        .windows(header_end.len())
+        .position(|w| w == header_end)
+        .ok_or_else(|| anyhow!("Not a PLY file: {:?}", path))?;
+    let header = std::str::from_utf8(&bytes[..header_len])?;
+    // The body starts on the line after `end_header`
+    let body_start = bytes[header_len..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map(|i| header_len + i + 1)
+        .unwrap_or(bytes.len());
+
+    let mut lines = header.lines();
+    if lines.next().map(str::trim) != Some("ply") {
+        bail!("Not a PLY file: {:?}", path);
+    }
+
+    let mut binary = false;
+    let mut elements = Vec::<PlyElement>::new();
+    for line in lines {
+        let tokens = line.split_whitespace().collect_vec();
+        match tokens.as_slice() {
+            ["format", "ascii", _] => binary = false,
+            ["format", "binary_little_endian", _] => binary = true,
+            ["format", fmt, _] => {
+                bail!(
+                    "Unsupported PLY format '{}'. Only ascii and binary_little_endian are supported",
+                    fmt
+                )
+            }
+            ["element", name, count] => elements.push(PlyElement {
+                name: name.to_string(),
+                count: count.parse()?,
+                properties: vec![],
+            }),
+            ["property", "list", count, scalar, name] => {
+                let list_count = Some(PlyScalar::parse(count)?);
+                add_property(&mut elements, name, scalar, list_count)?;
+            }
+            ["property", scalar, name] => add_property(&mut elements, name, scalar, None)?,
+            // Comments, obj_info and empty lines
+            _ => {}
+        }
+    }
+
+    let mut body = if binary {
+        PlyBody::BinaryLittleEndian(&bytes[body_start..])
+    } else {
+        PlyBody::Ascii(std::str::from_utf8(&bytes[body_start..])?.lines())
+    };
+
+    let mut mesh = HalfEdgeMesh::default();
+    let mut vertices = Vec::<VertexId>::new();
+    let mut pair_to_halfedge = PairToHalfEdge::new();
+
+    for element in elements.iter() {
+        match element.name.as_str() {
+            "vertex" => {
+                let idx = |name| {
+                    element
+                        .property_index(name)
+                        .ok_or_else(|| anyhow!("PLY vertex element has no '{}' property", name))
+                };
+                let (x, y, z) = (idx("x")?, idx("y")?, idx("z")?);
+                let color = match (
+                    element.property_index("red"),
+                    element.property_index("green"),
+                    element.property_index("blue"),
+                ) {
+                    (Some(r), Some(g), Some(b)) => Some((r, g, b)),
+                    _ => None,
+                };
+
+                for _ in 0..element.count {
+                    let values = body.read_row(element)?;
+                    let get = |i: usize| {
+                        values[i]
+                            .first()
+                            .map(|x| *x as f32)
+                            .ok_or_else(|| anyhow!("Missing value in PLY vertex"))
+                    };
+
+                    let v = mesh.alloc_vertex(Vec3::new(get(x)?, get(y)?, get(z)?), None);
+                    if let Some((r, g, b)) = color {
+                        // Integer color channels are in the [0, 255] range
+                        let channel = |i: usize| -> Result<f32> {
+                            if element.properties[i].scalar.is_float() {
+                                get(i)
+                            } else {
+                                Ok(get(i)? / 255.0)
+                            }
+                        };
+                        mesh.set_vertex_color(v, Vec3::new(channel(r)?, channel(g)?, channel(b)?));
+                    }
+                    vertices.push(v);
+                }
+            }
+            "face" => {
+                let indices_idx = element
+                    .property_index("vertex_indices")
+                    .or_else(|| element.property_index("vertex_index"))
+                    .ok_or_else(|| anyhow!("PLY face element has no vertex_indices property"))?;
+
+                for _ in 0..element.count {
+                    let values = body.read_row(element)?;
+                    let indices = &values[indices_idx];
+
+                    if indices.len() < 3 {
+                        bail!("Cannot build meshes where polygons have less than three vertices.")
+                    }
+                    let face_vertices = indices
+                        .iter()
+                        .map(|&i| {
+                            vertices
+                                .get(i as usize)
+                                .cloned()
+                                .ok_or_else(|| anyhow!("Out-of-bounds index in PLY face {}", i))
+                        })
+                        .collect::<Result<SVec<_>>>()?;
+                    if face_vertices.iter().duplicates().next().is_some() {
+                        bail!("Cannot build meshes where a polygon has duplicate vertices")
+                    }
+                    // add_face would silently reuse the existing halfedge
+                    for (&a, &b) in face_vertices.iter().circular_tuple_windows() {
+                        if pair_to_halfedge.contains_key(&(a, b)) {
+                            bail!(
+                                "Found multiple oriented edges with the same indices. The PLY \
+                                 mesh is either non-manifold or has inconsistent orientation"
+                            )
+                        }
+                    }
+
+                    add_face(&mut mesh, &face_vertices, &mut pair_to_halfedge);
+                }
+            }
+            _ => {
+                // Skip the rows of unknown elements
+                for _ in 0..element.count {
+                    body.read_row(element)?;
+                }
+            }
+        }
+    }
+
+    // Open meshes have edges without a twin after add_face.
+    mesh.add_boundary_halfedges();
+    mesh.validate()
+        .map_err(|errors| anyhow!("The PLY mesh is not a valid manifold: {:?}", errors))?;
+
+    Ok(mesh)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_ply_roundtrip_colors() {
+        let mut mesh = crate::mesh::halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let vertices = mesh.iter_vertices().map(|(v, _)| v).collect_vec();
+        for v in vertices {
+            // Map the [-0.5, 0.5] cube to the RGB color cube
+            let color = mesh.vertex_position(v) + Vec3::splat(0.5);
+            mesh.set_vertex_color(v, color);
+        }
+
+        let path = std::env::temp_dir().join("blackjack_test_roundtrip.ply");
+        export(&mesh, &path, false).unwrap();
+        let imported = import(&path).unwrap();
+
+        assert_eq!(imported.iter_vertices().count(), 8);
+        assert_eq!(imported.iter_faces().count(), 6);
+        for (v, vertex) in imported.iter_vertices() {
+            let expected = vertex.position + Vec3::splat(0.5);
+            let color = imported.vertex_color(v).unwrap();
+            assert!((color - expected).abs().max_element() < 1.0 / 255.0);
+        }
+    }
+
+    #[test]
+    pub fn test_ply_import_inconsistent_orientation() {
+        // The second triangle walks the shared edge in the same direction
+        let ply = "ply\nformat ascii 1.0\nelement vertex 4\nproperty float x\n\
+                   property float y\nproperty float z\nelement face 2\n\
+                   property list uchar int vertex_indices\nend_header\n\
+                   0 0 0\n1 0 0\n1 1 0\n0 1 0\n3 0 1 2\n3 0 1 3\n";
+        let path = std::env::temp_dir().join("blackjack_test_inconsistent.ply");
+        std::fs::write(&path, ply).unwrap();
+        assert!(import(&path).is_err());
+    }
+
+    #[test]
+    pub fn test_ply_export_binary() {
+        let mut mesh = crate::mesh::halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let v = mesh.iter_vertices().next().unwrap().0;
+        mesh.set_vertex_color(v, Vec3::X);
+
+        let path = std::env::temp_dir().join("blackjack_test_binary.ply");
+        export(&mesh, &path, true).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+
+        let header_end = b"end_header\n";
+        let body_start = bytes
+            .windows(header_end.len())
+            .position(|w| w == header_end)
+            .unwrap()
+            + header_end.len();
+        let header = std::str::from_utf8(&bytes[..body_start]).unwrap();
+        assert!(header.contains("format binary_little_endian 1.0"));
+        assert!(header.contains("property uchar red"));
+
+        // 8 vertices with xyz floats and rgb bytes, then 6 quads with a
+        // count byte and four int indices.
+        assert_eq!(bytes.len() - body_start, 8 * 15 + 6 * 17);
+
+        let imported = import(&path).unwrap();
+        assert_eq!(imported.iter_vertices().count(), 8);
+        assert_eq!(imported.iter_faces().count(), 6);
+        assert!(imported.stats().is_closed);
+        let colors = imported
+            .iter_vertices()
+            .map(|(v, _)| imported.vertex_color(v).unwrap())
+            .collect_vec();
+        assert_eq!(colors.iter().filter(|c| **c == Vec3::X).count(), 1);
+        assert_eq!(colors.iter().filter(|c| **c == Vec3::ONE).count(), 7);
+        for ((_, original), (_, vertex)) in mesh.iter_vertices().zip(imported.iter_vertices()) {
+            assert_eq!(original.position, vertex.position);
+        }
+    }
+
+    #[test]
+    pub fn test_ply_export_binary_large_face() {
+        // A single polygon with 300 sides
+        let positions = (0..300)
+            .map(|i| {
+                let angle = i as f32 / 300.0 * std::f32::consts::TAU;
+                Vec3::new(angle.cos(), 0.0, angle.sin())
+            })
+            .collect_vec();
+        let polygon = (0..300usize).collect_vec();
+        let mesh = HalfEdgeMesh::build_from_polygons(&positions, &[polygon]).unwrap();
+
+        let path = std::env::temp_dir().join("blackjack_test_large_face.ply");
+        let _ = std::fs::remove_file(&path);
+        assert!(export(&mesh, &path, true).is_err());
+        // Nothing was written
+        assert!(!path.exists());
+        assert!(export(&mesh, &path, false).is_ok());
+    }
+}
@@ -0,0 +1,133 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use crate::prelude::*;
+
+/// A single STL facet: the normal, followed by the three corners.
+type Facet = (Vec3, [Vec3; 3]);
+
+/// Triangles where the sine of the angle between the two edges at the first
+/// corner is below this are considered degenerate. Being relative to the edge
+/// lengths, this works the same for meshes of any scale.
+const DEGENERATE_SINE: f32 = 1e-6;
+
+/// Triangulates the faces of the mesh as a fan around their first vertex.
+/// Degenerate triangles, with collinear or coincident corners, are skipped,
+/// since they have no well-defined normal.
+fn facets(mesh: &HalfEdgeMesh) -> Vec<Facet> {
+    let mut facets = vec![];
+    for (face_id, _) in mesh.iter_faces() {
+        let vertices = mesh.face_vertices(face_id);
+        if vertices.len() < 3 {
+            continue;
+        }
+        let face_normal = mesh.face_normal(face_id);
+        let v0 = mesh.vertex_position(vertices[0]);
+        for (&v1, &v2) in vertices[1..].iter().tuple_windows() {
+            let (v1, v2) = (mesh.vertex_position(v1), mesh.vertex_position(v2));
+            let (e1, e2) = (v1 - v0, v2 - v0);
+            let cross = e1.cross(e2);
+            if cross.length() <= DEGENERATE_SINE * e1.length() * e2.length() {
+                continue;
+            }
+            // The face normal is computed from its first three vertices,
+            // which may be collinear even if the face is not degenerate.
+            let normal = if face_normal.is_finite() {
+                face_normal
+            } else {
+                cross.normalize()
+            };
+            facets.push((normal, [v0, v1, v2]));
+        }
+    }
+    facets
+}
+
+/// Exports the mesh as an STL file. Faces are fan-triangulated, and each
+/// triangle gets the normal of the face it comes from. When `binary` is set,
+/// the compact binary format is written, otherwise the ASCII one.
+pub fn export(mesh: &HalfEdgeMesh, path: &Path, binary: bool) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    let facets = facets(mesh);
+
+    if binary {
+        let mut header = [0u8; 80];
+        let comment = b"Generated by Blackjack: https://github.com/setzer22/blackjack";
+        header[..comment.len()].copy_from_slice(comment);
+        writer.write_all(&header)?;
+        writer.write_all(&(facets.len() as u32).to_le_bytes())?;
+        for (normal, corners) in &facets {
+            for v in std::iter::once(normal).chain(corners.iter()) {
+                for c in v.to_array() {
+                    writer.write_all(&c.to_le_bytes())?;
+                }
+            }
+            // Attribute byte count, unused
+            writer.write_all(&0u16.to_le_bytes())?;
+        }
+    } else {
+        writeln!(writer, "solid blackjack")?;
+        for (n, corners) in &facets {
+            writeln!(writer, "  facet normal {} {} {}", n.x, n.y, n.z)?;
+            writeln!(writer, "    outer loop")?;
+            for v in corners {
+                writeln!(writer, "      vertex {} {} {}", v.x, v.y, v.z)?;
+            }
+            writeln!(writer, "    endloop")?;
+            writeln!(writer, "  endfacet")?;
+        }
+        writeln!(writer, "endsolid blackjack")?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_stl_export() {
+        let mesh = crate::mesh::halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+
+        let path = std::env::temp_dir().join("blackjack_test_export.stl");
+        export(&mesh, &path, true).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        // Six quads, two triangles each
+        assert_eq!(u32::from_le_bytes(bytes[80..84].try_into().unwrap()), 12);
+        assert_eq!(bytes.len(), 84 + 12 * 50);
+
+        export(&mesh, &path, false).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.matches("facet normal").count(), 12);
+        assert!(!text.contains("NaN"));
+    }
+
+    #[test]
+    pub fn test_stl_degenerate_facets() {
+        // The first three vertices are collinear, so the first triangle of
+        // the fan is degenerate.
+        let positions = [
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 0.0),
+            Vec3::new(2.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ];
+        let mesh = HalfEdgeMesh::build_from_polygons(&positions, &[[0usize, 4, 3, 2, 1]]).unwrap();
+        let triangles = facets(&mesh);
+        assert_eq!(triangles.len(), 2);
+        for (normal, _) in triangles {
+            assert!(normal.is_finite());
+            assert!((normal.length() - 1.0).abs() < 1e-5);
+        }
+
+        // Small meshes are not mistaken for degenerate ones
+        let tiny = crate::mesh::halfedge::primitives::Box::build(Vec3::ZERO, Vec3::splat(1e-4));
+        assert_eq!(facets(&tiny).len(), 12);
+    }
+}
@@ -0,0 +1,61 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::prelude::*;
+
+/// The default value for [`max_elements`].
+pub const DEFAULT_MAX_ELEMENTS: usize = 10_000_000;
+
+static MAX_ELEMENTS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_ELEMENTS);
+
+/// The maximum number of vertices, edges or faces that operations which can
+/// grow a mesh very quickly, like subdivision or arrays, are allowed to
+/// produce. These operations fail before allocating anything when the result
+/// would exceed the limit, instead of running out of memory.
+pub fn max_elements() -> usize {
+    MAX_ELEMENTS.load(Ordering::Relaxed)
+}
+
+/// Sets the limit returned by [`max_elements`], for the whole application.
+pub fn set_max_elements(max: usize) {
+    MAX_ELEMENTS.store(max, Ordering::Relaxed);
+}
+
+/// The projected size of the mesh produced by an operation.
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct ElementCounts {
+    pub vertices: u64,
+    pub edges: u64,
+    pub faces: u64,
+}
+
+impl ElementCounts {
+    pub fn of_mesh(mesh: &HalfEdgeMesh) -> Self {
+        Self {
+            vertices: mesh.iter_vertices().count() as u64,
+            edges: mesh.iter_edges().count() as u64,
+            faces: mesh.iter_faces().count() as u64,
+        }
+    }
+
+    /// Returns an error naming `op` if any of the counts exceeds
+    /// [`max_elements`].
+    pub fn check(&self, op: &str) -> Result<()> {
+        let max = max_elements() as u64;
+        for (count, what) in [
+            (self.vertices, "vertices"),
+            (self.edges, "edges"),
+            (self.faces, "faces"),
+        ] {
+            if count > max {
+                bail!(
+                    "{}: The result would have {} {}, over the limit of {}",
+                    op,
+                    count,
+                    what,
+                    max
+                )
+            }
+        }
+        Ok(())
+    }
+}
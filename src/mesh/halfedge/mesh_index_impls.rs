@@ -18,6 +18,7 @@ macro_rules! impl_index_traits {
 
         impl std::ops::IndexMut<$id_type> for HalfEdgeMesh {
             fn index_mut(&mut self, index: $id_type) -> &mut Self::Output {
+                self.bvh_cache.invalidate();
                 self.$arena.get_mut(index.0).unwrap_or_else(|| {
                     panic!(
                         "{} index error for {:?}. Has the value been deleted?",
@@ -43,6 +44,7 @@ macro_rules! impl_index_ops {
 
         /// Try to mutably borrow data
         pub fn $field_name_mut(&mut self, id: $id_type) -> Option<&mut $output_type> {
+            self.bvh_cache.invalidate();
             self.$arena.get_mut(id.0)
         }
     };
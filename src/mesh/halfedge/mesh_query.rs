@@ -0,0 +1,260 @@
+use crate::prelude::*;
+
+/// A read-only interface to a mesh, for code that only needs to inspect it,
+/// like analysis plugins. Unlike the inherent methods of [`HalfEdgeMesh`],
+/// this trait doesn't expose the internal halfedge records, so code written
+/// against it keeps working if the way meshes are stored changes.
+///
+/// Elements are referred to by their ids. Passing an id that doesn't belong
+/// to the mesh may panic, like indexing the mesh does. Methods that follow
+/// the connectivity of the mesh return an error when it is malformed.
+pub trait MeshQuery {
+    /// Iterates the ids of all vertices.
+    fn vertices(&self) -> Box<dyn Iterator<Item = VertexId> + '_>;
+
+    /// Iterates the ids of all faces.
+    fn faces(&self) -> Box<dyn Iterator<Item = FaceId> + '_>;
+
+    /// Iterates the edges of the mesh. Each edge is identified by one of its
+    /// two halfedges.
+    fn edges(&self) -> Box<dyn Iterator<Item = HalfEdgeId> + '_>;
+
+    fn vertex_position(&self, vertex: VertexId) -> Vec3;
+
+    /// Returns the (src, dst) vertices of a halfedge.
+    fn edge_endpoints(&self, halfedge: HalfEdgeId) -> Result<(VertexId, VertexId)>;
+
+    /// Returns the vertices of a face, in counter-clockwise order.
+    fn face_vertices(&self, face: FaceId) -> SVec<VertexId>;
+
+    /// Returns the unit normal of a face, or None if the face is degenerate,
+    /// e.g. when it has less than three vertices.
+    fn face_normal(&self, face: FaceId) -> Option<Vec3>;
+
+    /// Returns the faces on each side of a halfedge: The face of the
+    /// halfedge itself and the face of its twin. Either is None on the
+    /// boundary of the mesh.
+    fn edge_faces(&self, halfedge: HalfEdgeId) -> Result<(Option<FaceId>, Option<FaceId>)>;
+
+    /// Returns the vertices connected to `vertex` by an edge.
+    fn vertex_neighbors(&self, vertex: VertexId) -> Result<SVec<VertexId>>;
+
+    /// Returns the faces that have `vertex` as one of their corners.
+    fn vertex_faces(&self, vertex: VertexId) -> Result<SVec<FaceId>>;
+
+    /// See [`MeshStats`].
+    fn stats(&self) -> MeshStats;
+
+    /// Returns the average position of the vertices of a face.
+    fn face_centroid(&self, face: FaceId) -> Vec3 {
+        let vertices = self.face_vertices(face);
+        vertices
+            .iter()
+            .fold(Vec3::ZERO, |sum, v| sum + self.vertex_position(*v))
+            / vertices.len().max(1) as f32
+    }
+
+    /// Returns the area of a face. Non-planar faces are measured as a fan of
+    /// triangles around their first vertex.
+    fn face_area(&self, face: FaceId) -> f32 {
+        let positions = self
+            .face_vertices(face)
+            .iter()
+            .map(|v| self.vertex_position(*v))
+            .collect::<SVec<_>>();
+        positions
+            .iter()
+            .skip(1)
+            .tuple_windows()
+            .map(|(b, c)| (*b - positions[0]).cross(*c - positions[0]).length() * 0.5)
+            .sum()
+    }
+
+    /// Returns the total area of all faces.
+    fn surface_area(&self) -> f32 {
+        self.faces().map(|f| self.face_area(f)).sum()
+    }
+
+    /// Returns the volume enclosed by the mesh, computed as the sum of the
+    /// signed volumes of the tetrahedra formed by the origin and each face,
+    /// split as a fan of triangles like in [`MeshQuery::face_area`]. The
+    /// result is only meaningful for closed meshes, and is negative when the
+    /// faces point inwards.
+    fn volume(&self) -> f32 {
+        self.faces()
+            .map(|face| {
+                let positions = self
+                    .face_vertices(face)
+                    .iter()
+                    .map(|v| self.vertex_position(*v))
+                    .collect::<SVec<_>>();
+                positions
+                    .iter()
+                    .skip(1)
+                    .tuple_windows()
+                    .map(|(b, c)| positions[0].dot(b.cross(*c)) / 6.0)
+                    .sum::<f32>()
+            })
+            .sum()
+    }
+
+    /// Returns V - E + F. This is 2 for any closed mesh that is topologically
+    /// a sphere, and decreases by 2 for each hole through the mesh.
+    fn euler_characteristic(&self) -> i32 {
+        self.vertices().count() as i32 - self.edges().count() as i32 + self.faces().count() as i32
+    }
+
+    /// Returns the genus of the mesh, i.e. the number of holes through it,
+    /// like the one in a torus. Only defined for closed manifold meshes, so
+    /// None is returned otherwise. When the mesh has several connected
+    /// pieces, the genus of all of them is added up.
+    fn genus(&self) -> Option<i32> {
+        let stats = self.stats();
+        if !stats.is_closed || !stats.is_manifold {
+            return None;
+        }
+
+        // Count the connected pieces by joining the faces on both sides of
+        // each edge.
+        let mut parent = HashMap::<FaceId, FaceId>::new();
+        fn root(parent: &HashMap<FaceId, FaceId>, mut face: FaceId) -> FaceId {
+            while let Some(&p) = parent.get(&face) {
+                face = p;
+            }
+            face
+        }
+        for h in self.edges() {
+            if let Ok((Some(a), Some(b))) = self.edge_faces(h) {
+                let (a, b) = (root(&parent, a), root(&parent, b));
+                if a != b {
+                    parent.insert(a, b);
+                }
+            }
+        }
+        let num_pieces = self.faces().filter(|f| !parent.contains_key(f)).count() as i32;
+
+        // Each closed piece has an Euler characteristic of 2 - 2 * genus
+        Some((2 * num_pieces - self.euler_characteristic()) / 2)
+    }
+}
+
+impl MeshQuery for HalfEdgeMesh {
+    fn vertices(&self) -> Box<dyn Iterator<Item = VertexId> + '_> {
+        Box::new(self.iter_vertices().map(|(v, _)| v))
+    }
+
+    fn faces(&self) -> Box<dyn Iterator<Item = FaceId> + '_> {
+        Box::new(self.iter_faces().map(|(f, _)| f))
+    }
+
+    fn edges(&self) -> Box<dyn Iterator<Item = HalfEdgeId> + '_> {
+        Box::new(self.iter_edges())
+    }
+
+    fn vertex_position(&self, vertex: VertexId) -> Vec3 {
+        HalfEdgeMesh::vertex_position(self, vertex)
+    }
+
+    fn edge_endpoints(&self, halfedge: HalfEdgeId) -> Result<(VertexId, VertexId)> {
+        Ok(self.at_halfedge(halfedge).src_dst_pair()?)
+    }
+
+    fn face_vertices(&self, face: FaceId) -> SVec<VertexId> {
+        HalfEdgeMesh::face_vertices(self, face)
+    }
+
+    fn face_normal(&self, face: FaceId) -> Option<Vec3> {
+        if HalfEdgeMesh::face_vertices(self, face).len() < 3 {
+            return None;
+        }
+        Some(HalfEdgeMesh::face_normal(self, face)).filter(|n| n.is_finite())
+    }
+
+    fn edge_faces(&self, halfedge: HalfEdgeId) -> Result<(Option<FaceId>, Option<FaceId>)> {
+        let twin = self.at_halfedge(halfedge).twin().try_end()?;
+        Ok((self[halfedge].face, self[twin].face))
+    }
+
+    fn vertex_neighbors(&self, vertex: VertexId) -> Result<SVec<VertexId>> {
+        self.at_vertex(vertex)
+            .outgoing_halfedges()?
+            .iter()
+            .map(|h| -> Result<VertexId> { Ok(self.at_halfedge(*h).dst_vertex().try_end()?) })
+            .collect()
+    }
+
+    fn vertex_faces(&self, vertex: VertexId) -> Result<SVec<FaceId>> {
+        Ok(self
+            .at_vertex(vertex)
+            .outgoing_halfedges()?
+            .iter()
+            .filter_map(|h| self[*h].face)
+            .collect())
+    }
+
+    fn stats(&self) -> MeshStats {
+        HalfEdgeMesh::stats(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plugin code is meant to be generic over the trait, so the tests are too.
+    fn summary(mesh: &impl MeshQuery) -> (i32, f32, usize) {
+        let corner = mesh.vertices().next().unwrap();
+        (
+            mesh.euler_characteristic(),
+            mesh.surface_area(),
+            mesh.vertex_neighbors(corner).unwrap().len(),
+        )
+    }
+
+    #[test]
+    pub fn test_mesh_query_box() {
+        let mesh = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::new(1.0, 2.0, 3.0));
+        let (euler, area, neighbors) = summary(&mesh);
+        assert_eq!(euler, 2);
+        assert!((area - 2.0 * (1.0 * 2.0 + 2.0 * 3.0 + 1.0 * 3.0)).abs() < 1e-4);
+        assert_eq!(neighbors, 3);
+        assert!((mesh.volume() - 1.0 * 2.0 * 3.0).abs() < 1e-4);
+
+        for face in MeshQuery::faces(&mesh) {
+            let normal = MeshQuery::face_normal(&mesh, face).unwrap();
+            assert!((normal.length() - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    pub fn test_genus() {
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        assert_eq!(cube.genus(), Some(0));
+
+        // A torus, as a grid of quads wrapping around in both directions
+        let n = 4;
+        let positions = (0..n * n)
+            .map(|i| {
+                let (u, v) = ((i / n) as f32, (i % n) as f32);
+                let angle = |x: f32| x / n as f32 * std::f32::consts::TAU;
+                let ring = Vec3::new(angle(u).cos(), 0.0, angle(u).sin());
+                ring * (2.0 + angle(v).cos()) + Vec3::Y * angle(v).sin()
+            })
+            .collect_vec();
+        let idx = |u: usize, v: usize| (u % n) * n + v % n;
+        let polygons = (0..n)
+            .flat_map(|u| {
+                (0..n).map(move |v| [idx(u, v), idx(u, v + 1), idx(u + 1, v + 1), idx(u + 1, v)])
+            })
+            .collect_vec();
+        let mut torus = HalfEdgeMesh::build_from_polygons(&positions, &polygons).unwrap();
+        assert_eq!(torus.euler_characteristic(), 0);
+        assert_eq!(torus.genus(), Some(1));
+
+        torus.merge_with(&cube);
+        assert_eq!(torus.genus(), Some(1));
+
+        let quad = halfedge::primitives::Quad::build(Vec3::ZERO, Vec3::Y, Vec3::X, Vec2::ONE);
+        assert_eq!(quad.genus(), None);
+    }
+}
@@ -43,6 +43,59 @@ impl Box {
         )
         .expect("Cube construction should not fail")
     }
+
+    /// Same as [`Box::build`], but each side of the box is a grid of quads.
+    /// The box is split in `divisions.x`, `divisions.y` and `divisions.z`
+    /// parts along each axis. Sides share the vertices along their edges, so
+    /// the result is a single closed mesh.
+    pub fn build_subdivided(center: Vec3, size: Vec3, divisions: UVec3) -> HalfEdgeMesh {
+        let divisions = divisions.max(UVec3::ONE);
+        let corner = center - size * 0.5;
+
+        // Vertices are identified by their cell in the lattice of divisions
+        let mut positions = vec![];
+        let mut vertex_idx = HashMap::<UVec3, usize>::new();
+        let mut vertex = |cell: UVec3| -> usize {
+            *vertex_idx.entry(cell).or_insert_with(|| {
+                positions.push(corner + size * cell.as_vec3() / divisions.as_vec3());
+                positions.len() - 1
+            })
+        };
+
+        let mut polygons = vec![];
+        for axis in 0..3 {
+            // The other two axes, in an order such that b × c points along
+            // the axis
+            let (b, c) = ((axis + 1) % 3, (axis + 2) % 3);
+            for side in [0, divisions[axis]] {
+                for i in 0..divisions[b] {
+                    for j in 0..divisions[c] {
+                        let cell = |di: u32, dj: u32| {
+                            let mut cell = UVec3::ZERO;
+                            cell[axis] = side;
+                            cell[b] = i + di;
+                            cell[c] = j + dj;
+                            cell
+                        };
+                        let mut polygon = [
+                            vertex(cell(0, 0)),
+                            vertex(cell(1, 0)),
+                            vertex(cell(1, 1)),
+                            vertex(cell(0, 1)),
+                        ];
+                        // Faces on the near side look the other way
+                        if side == 0 {
+                            polygon.reverse();
+                        }
+                        polygons.push(polygon);
+                    }
+                }
+            }
+        }
+
+        HalfEdgeMesh::build_from_polygons(&positions, &polygons)
+            .expect("Cube construction should not fail")
+    }
 }
 
 pub struct Quad;
@@ -63,3 +116,269 @@ impl Quad {
             .expect("Quad construction should not fail")
     }
 }
+
+/// A flat grid of quads on the XZ plane, facing up. `size` is the extent of
+/// the grid along the X and Z axes, which are split into `divisions_x` and
+/// `divisions_z` rows of quads respectively.
+pub struct Grid;
+impl Grid {
+    pub fn build(center: Vec3, size: Vec2, divisions_x: usize, divisions_z: usize) -> HalfEdgeMesh {
+        let divisions_x = divisions_x.max(1);
+        let divisions_z = divisions_z.max(1);
+        let corner = center - Vec3::new(size.x, 0.0, size.y) * 0.5;
+
+        let mut positions = vec![];
+        for i in 0..=divisions_x {
+            for j in 0..=divisions_z {
+                positions.push(
+                    corner
+                        + Vec3::new(
+                            size.x * i as f32 / divisions_x as f32,
+                            0.0,
+                            size.y * j as f32 / divisions_z as f32,
+                        ),
+                );
+            }
+        }
+        let idx = |i: usize, j: usize| i * (divisions_z + 1) + j;
+
+        let mut polygons = vec![];
+        for i in 0..divisions_x {
+            for j in 0..divisions_z {
+                polygons.push([idx(i, j), idx(i, j + 1), idx(i + 1, j + 1), idx(i + 1, j)]);
+            }
+        }
+
+        HalfEdgeMesh::build_from_polygons(&positions, &polygons)
+            .expect("Grid construction should not fail")
+    }
+}
+
+/// A sphere made of rings of quads, like the lines of latitude and longitude
+/// on a globe, closed by a fan of triangles around each pole.
+pub struct UVSphere;
+impl UVSphere {
+    pub fn build(center: Vec3, radius: f32, segments: usize, rings: usize) -> HalfEdgeMesh {
+        let segments = segments.max(3);
+        let rings = rings.max(2);
+
+        // The poles come first, followed by the vertices of each ring, from
+        // top to bottom.
+        let mut positions = vec![center + Vec3::Y * radius, center - Vec3::Y * radius];
+        for ring in 1..rings {
+            let theta = ring as f32 / rings as f32 * std::f32::consts::PI;
+            for segment in 0..segments {
+                let phi = segment as f32 / segments as f32 * std::f32::consts::TAU;
+                let dir = Vec3::new(
+                    theta.sin() * phi.cos(),
+                    theta.cos(),
+                    theta.sin() * phi.sin(),
+                );
+                positions.push(center + dir * radius);
+            }
+        }
+        let idx = |ring: usize, segment: usize| 2 + (ring - 1) * segments + segment % segments;
+
+        let mut polygons: Vec<SVec<usize>> = vec![];
+        for s in 0..segments {
+            polygons.push(smallvec::smallvec![0, idx(1, s + 1), idx(1, s)]);
+            for ring in 1..rings - 1 {
+                polygons.push(smallvec::smallvec![
+                    idx(ring, s),
+                    idx(ring, s + 1),
+                    idx(ring + 1, s + 1),
+                    idx(ring + 1, s),
+                ]);
+            }
+            polygons.push(smallvec::smallvec![
+                1,
+                idx(rings - 1, s),
+                idx(rings - 1, s + 1)
+            ]);
+        }
+
+        HalfEdgeMesh::build_from_polygons(&positions, &polygons)
+            .expect("UV sphere construction should not fail")
+    }
+}
+
+/// A closed torus made of quads, lying on the XZ plane. The `major_radius` is
+/// the distance from the center to the middle of the tube, and the
+/// `minor_radius` is the radius of the tube itself.
+pub struct Torus;
+impl Torus {
+    pub fn build(
+        center: Vec3,
+        major_radius: f32,
+        minor_radius: f32,
+        major_segments: usize,
+        minor_segments: usize,
+    ) -> HalfEdgeMesh {
+        let major_segments = major_segments.max(3);
+        let minor_segments = minor_segments.max(3);
+
+        let mut positions = vec![];
+        for i in 0..major_segments {
+            let u = i as f32 / major_segments as f32 * std::f32::consts::TAU;
+            let ring_dir = Vec3::new(u.cos(), 0.0, u.sin());
+            for j in 0..minor_segments {
+                let v = j as f32 / minor_segments as f32 * std::f32::consts::TAU;
+                positions.push(
+                    center
+                        + ring_dir * (major_radius + minor_radius * v.cos())
+                        + Vec3::Y * minor_radius * v.sin(),
+                );
+            }
+        }
+        // The grid of vertices wraps around in both directions
+        let idx = |i: usize, j: usize| (i % major_segments) * minor_segments + j % minor_segments;
+
+        let mut polygons = vec![];
+        for i in 0..major_segments {
+            for j in 0..minor_segments {
+                polygons.push([idx(i, j), idx(i, j + 1), idx(i + 1, j + 1), idx(i + 1, j)]);
+            }
+        }
+
+        HalfEdgeMesh::build_from_polygons(&positions, &polygons)
+            .expect("Torus construction should not fail")
+    }
+}
+
+/// An open polyline, made of edges with no faces. Useful as a profile for
+/// other operations, like [`edit_ops::screw`].
+pub struct Polyline;
+impl Polyline {
+    pub fn build(points: &[Vec3]) -> Result<HalfEdgeMesh> {
+        if points.len() < 2 {
+            bail!("A polyline needs at least two points")
+        }
+        let mut mesh = HalfEdgeMesh::default();
+        let vertices = points
+            .iter()
+            .map(|p| mesh.alloc_vertex(*p, None))
+            .collect_vec();
+
+        // One pair of halfedges per segment: `forward` goes from the first
+        // point to the last, and `backward` goes the opposite way.
+        let mut forward = vec![];
+        let mut backward = vec![];
+        for (&a, &b) in vertices.iter().tuple_windows() {
+            let h = mesh.alloc_halfedge(HalfEdge::default());
+            let t = mesh.alloc_halfedge(HalfEdge::default());
+            mesh[h].vertex = Some(a);
+            mesh[h].twin = Some(t);
+            mesh[t].vertex = Some(b);
+            mesh[t].twin = Some(h);
+            mesh[a].halfedge = Some(h);
+            forward.push(h);
+            backward.push(t);
+        }
+        let last = *vertices.last().unwrap();
+        mesh[last].halfedge = backward.last().cloned();
+
+        // All halfedges are on the boundary, which forms a single loop that
+        // goes along the polyline and back.
+        let boundary_loop = forward
+            .iter()
+            .chain(backward.iter().rev())
+            .cloned()
+            .collect_vec();
+        for (&h, &h_next) in boundary_loop.iter().circular_tuple_windows() {
+            mesh[h].next = Some(h_next);
+        }
+
+        Ok(mesh)
+    }
+}
+
+/// A straight line, split into segments. See [`Polyline`].
+pub struct Line;
+impl Line {
+    pub fn build(start: Vec3, end: Vec3, segments: usize) -> HalfEdgeMesh {
+        let segments = segments.max(1);
+        let points = (0..=segments)
+            .map(|i| start.lerp(end, i as f32 / segments as f32))
+            .collect_vec();
+        Polyline::build(&points).expect("Line construction should not fail")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_uv_sphere() {
+        let center = Vec3::new(1.0, 2.0, 3.0);
+        let sphere = UVSphere::build(center, 2.0, 12, 6);
+        assert_eq!(sphere.iter_vertices().count(), 2 + 5 * 12);
+        assert_eq!(sphere.iter_faces().count(), 12 * 6);
+        assert_eq!(sphere.validate(), Ok(()));
+        assert!(sphere.stats().is_closed);
+        assert!(sphere.is_manifold());
+        for (_, vertex) in sphere.iter_vertices() {
+            assert!((vertex.position.distance(center) - 2.0).abs() < 1e-5);
+        }
+        // The faces point outwards
+        assert!(sphere.volume() > 0.0);
+    }
+
+    #[test]
+    pub fn test_torus() {
+        let torus = Torus::build(Vec3::ZERO, 2.0, 0.5, 24, 8);
+        assert_eq!(torus.iter_vertices().count(), 24 * 8);
+        assert_eq!(torus.iter_faces().count(), 24 * 8);
+        assert_eq!(torus.validate(), Ok(()));
+        assert_eq!(torus.genus(), Some(1));
+        // The faces point outwards, and the volume approaches 2π²Rr²
+        let volume = 2.0 * std::f32::consts::PI.powi(2) * 2.0 * 0.5f32.powi(2);
+        assert!(torus.volume() > 0.8 * volume && torus.volume() < volume);
+    }
+
+    #[test]
+    pub fn test_grid() {
+        let mut grid = Grid::build(Vec3::ZERO, Vec2::new(4.0, 2.0), 4, 3);
+        assert_eq!(grid.iter_vertices().count(), 5 * 4);
+        assert_eq!(grid.iter_faces().count(), 4 * 3);
+        assert_eq!(grid.validate(), Ok(()));
+        assert_eq!(
+            grid.bounding_box(),
+            Some((Vec3::new(-2.0, 0.0, -1.0), Vec3::new(2.0, 0.0, 1.0)))
+        );
+        for (face, _) in grid.iter_faces() {
+            assert!(grid.face_normal(face).distance(Vec3::Y) < 1e-5);
+        }
+
+        let loops = grid.boundary_loops().unwrap();
+        assert_eq!(loops.len(), 1);
+        assert_eq!(loops[0].len(), 2 * (4 + 3));
+        edit_ops::fill_hole(&mut grid, loops[0][0]).unwrap();
+        assert!(grid.stats().is_closed);
+    }
+
+    #[test]
+    pub fn test_subdivided_box() {
+        let size = Vec3::new(1.0, 2.0, 3.0);
+        let mut cube = Box::build_subdivided(Vec3::ZERO, size, UVec3::new(2, 3, 4));
+        assert_eq!(cube.iter_vertices().count(), 3 * 4 * 5 - 1 * 2 * 3);
+        assert_eq!(cube.iter_faces().count(), 2 * (2 * 3 + 3 * 4 + 2 * 4));
+        assert_eq!(cube.validate(), Ok(()));
+        assert_eq!(cube.genus(), Some(0));
+        assert!((cube.volume() - 6.0).abs() < 1e-4);
+
+        // The sides are connected, so the edges of the box can be beveled
+        let edge = cube
+            .iter_halfedges()
+            .map(|(h, _)| h)
+            .filter(|h| {
+                let (src, dst) = cube.at_halfedge(*h).src_dst_pair().unwrap();
+                let (a, b) = (cube.vertex_position(src), cube.vertex_position(dst));
+                a.x == -0.5 && b.x == -0.5 && a.y == -1.0 && b.y == -1.0
+            })
+            .collect_vec();
+        assert_eq!(edge.len(), 2 * 4);
+        edit_ops::bevel_edges(&mut cube, &edge, 0.1).unwrap();
+        assert!(cube.stats().is_closed);
+    }
+}
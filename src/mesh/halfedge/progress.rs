@@ -0,0 +1,47 @@
+use std::ops::ControlFlow;
+
+use crate::prelude::*;
+
+/// A callback for long-running operations. It gets called periodically with
+/// the completed fraction of the work, between 0.0 and 1.0, and can return
+/// `ControlFlow::Break` to cancel the operation.
+pub type Progress<'a> = &'a dyn Fn(f32) -> ControlFlow<()>;
+
+/// A progress callback that ignores progress and never cancels.
+pub fn no_progress(_: f32) -> ControlFlow<()> {
+    ControlFlow::Continue(())
+}
+
+/// The error returned by operations cancelled through their [`Progress`]
+/// callback. Operations that can be cancelled never modify their inputs, so
+/// nothing needs to be undone.
+#[derive(Debug, Clone, Copy)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The operation was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Reports the given progress, returning a [`Cancelled`] error if the
+/// callback asks to stop.
+pub fn report(progress: Progress, fraction: f32) -> Result<()> {
+    match progress(fraction.clamp(0.0, 1.0)) {
+        ControlFlow::Continue(()) => Ok(()),
+        ControlFlow::Break(()) => Err(Cancelled.into()),
+    }
+}
+
+/// Maps the progress of one step of an operation into the `start..end` range
+/// of the whole operation's `progress`. The returned callback takes the
+/// progress of the step, from 0.0 to 1.0.
+pub fn scaled<'a>(
+    progress: Progress<'a>,
+    start: f32,
+    end: f32,
+) -> impl Fn(f32) -> ControlFlow<()> + 'a {
+    move |fraction| progress(start + (end - start) * fraction)
+}
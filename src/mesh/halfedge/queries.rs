@@ -0,0 +1,771 @@
+use crate::prelude::*;
+
+/// Summary information about a mesh, meant to be displayed in the UI.
+#[derive(Debug, Clone, Copy)]
+pub struct MeshStats {
+    pub num_vertices: usize,
+    /// The number of edges. Each pair of twin halfedges counts as one edge.
+    pub num_edges: usize,
+    pub num_faces: usize,
+    /// A mesh is closed when it has no boundary loops, i.e. every halfedge
+    /// belongs to a face.
+    pub is_closed: bool,
+    /// See [`HalfEdgeMesh::is_manifold`].
+    pub is_manifold: bool,
+    /// The axis-aligned bounding box of the mesh, as a (min, max) pair. Empty
+    /// meshes have a zero-sized bounding box at the origin.
+    pub bbox: (Vec3, Vec3),
+}
+
+/// A broken invariant of the mesh connectivity, found by
+/// [`HalfEdgeMesh::validate`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    HalfEdgeHasNoTwin(HalfEdgeId),
+    HalfEdgeHasNoNext(HalfEdgeId),
+    HalfEdgeHasNoVertex(HalfEdgeId),
+    /// The twin of the twin of this halfedge is not the halfedge itself.
+    TwinMismatch(HalfEdgeId),
+    /// Following `next` from this halfedge never gets back to it.
+    NextLoopOpen(HalfEdgeId),
+    /// The halfedge and its `next` belong to different faces.
+    NextFaceMismatch(HalfEdgeId),
+    /// The halfedge of this face belongs to another face.
+    FaceHalfEdgeMismatch(FaceId),
+    FaceHasNoHalfedge(FaceId),
+    /// The halfedge of this vertex starts at another vertex.
+    VertexHalfEdgeMismatch(VertexId),
+    DeletedTwin(HalfEdgeId),
+    DeletedNext(HalfEdgeId),
+    DeletedVertex(HalfEdgeId),
+    DeletedFace(HalfEdgeId),
+    DeletedFaceHalfEdge(FaceId),
+    DeletedVertexHalfEdge(VertexId),
+}
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("{:?}", self))
+    }
+}
+impl std::error::Error for ValidationError {}
+
+impl HalfEdgeMesh {
+    /// Returns the vertices where the mesh is not manifold: The faces around
+    /// a manifold vertex form a single fan, so cycling around the fan should
+    /// visit all of the vertex's outgoing halfedges. Vertices with missing
+    /// connectivity are also reported.
+    pub fn non_manifold_vertices(&self) -> Vec<VertexId> {
+        let mut num_outgoing = HashMap::<VertexId, usize>::new();
+        for (_, halfedge) in self.iter_halfedges() {
+            if let Some(v) = halfedge.vertex {
+                *num_outgoing.entry(v).or_default() += 1;
+            }
+        }
+
+        // Returns whether cycling around the fan of `v` visits exactly all of
+        // its outgoing halfedges.
+        let is_single_fan = |v: VertexId| -> bool {
+            let expected = num_outgoing.get(&v).cloned().unwrap_or(0);
+            let h0 = match self[v].halfedge {
+                Some(h0) => h0,
+                None => return expected == 0,
+            };
+            let mut h = h0;
+            let mut count = 0;
+            loop {
+                count += 1;
+                // This also protects us from looping forever on malformed meshes
+                if count > expected {
+                    return false;
+                }
+                h = match self.at_halfedge(h).cycle_around_fan().try_end() {
+                    Ok(h) => h,
+                    Err(_) => return false,
+                };
+                if h == h0 {
+                    break;
+                }
+            }
+            count == expected
+        };
+
+        self.iter_vertices()
+            .map(|(v, _)| v)
+            .filter(|&v| !is_single_fan(v))
+            .collect()
+    }
+
+    /// Returns the edges where the mesh is not manifold: An edge can only be
+    /// shared by two faces, which traverse it in opposite directions, so there
+    /// can only be one halfedge from any vertex to another. When there's more
+    /// than one, all of them are reported.
+    pub fn non_manifold_edges(&self) -> Vec<HalfEdgeId> {
+        let mut by_pair = HashMap::<(VertexId, VertexId), SVec<HalfEdgeId>>::new();
+        for (h, _) in self.iter_halfedges() {
+            if let Ok(pair) = self.at_halfedge(h).src_dst_pair() {
+                by_pair.entry(pair).or_default().push(h);
+            }
+        }
+        by_pair
+            .into_values()
+            .filter(|halfedges| halfedges.len() > 1)
+            .flatten()
+            .sorted()
+            .collect()
+    }
+
+    /// Returns whether the mesh has no non-manifold vertices or edges. See
+    /// [`HalfEdgeMesh::non_manifold_vertices`] and
+    /// [`HalfEdgeMesh::non_manifold_edges`].
+    pub fn is_manifold(&self) -> bool {
+        self.non_manifold_vertices().is_empty() && self.non_manifold_edges().is_empty()
+    }
+
+    /// Returns the average position of the mesh's vertices, or None if the
+    /// mesh has no vertices.
+    pub fn centroid(&self) -> Option<Vec3> {
+        let (sum, count) = self
+            .iter_vertices()
+            .fold((Vec3::ZERO, 0), |(sum, count), (_, v)| {
+                (sum + v.position, count + 1)
+            });
+        (count > 0).then(|| sum / count as f32)
+    }
+
+    /// Returns the axis-aligned bounding box of the mesh, as a (min, max)
+    /// pair, or None if the mesh has no vertices.
+    pub fn bounding_box(&self) -> Option<(Vec3, Vec3)> {
+        self.iter_vertices()
+            .map(|(_, v)| v.position)
+            .fold(None, |bbox, p| match bbox {
+                Some((min, max)) => Some((min.min(p), max.max(p))),
+                None => Some((p, p)),
+            })
+    }
+
+    /// Returns every other face of the quad grid containing `start`, in a
+    /// checkerboard pattern. Faces are flood-filled from `start` across shared
+    /// edges and 2-colored, returning the faces with the same color as
+    /// `start`. The result can be passed to [`edit_ops::extrude_faces`] to
+    /// make waffle-like patterns.
+    ///
+    /// Only quads take part in the flood fill: Any other face is never
+    /// selected and acts as a barrier. On grids with irregular vertices a
+    /// consistent coloring may not exist, in which case each face takes the
+    /// color of the first path that reaches it.
+    pub fn select_checker_faces(&self, start: FaceId) -> Vec<FaceId> {
+        let is_quad = |f: FaceId| self.num_face_edges(f) == 4;
+        if !is_quad(start) {
+            return vec![];
+        }
+
+        let mut is_even = HashMap::<FaceId, bool>::new();
+        let mut queue = std::collections::VecDeque::new();
+        is_even.insert(start, true);
+        queue.push_back(start);
+
+        while let Some(face) = queue.pop_front() {
+            let even = is_even[&face];
+            for h in self.face_edges(face) {
+                let neighbor = self[h].twin.and_then(|twin| self[twin].face);
+                if let Some(neighbor) = neighbor {
+                    if is_quad(neighbor) && !is_even.contains_key(&neighbor) {
+                        is_even.insert(neighbor, !even);
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+
+        // Keep the output in a deterministic order
+        self.iter_faces()
+            .map(|(f, _)| f)
+            .filter(|f| is_even.get(f).cloned().unwrap_or(false))
+            .collect()
+    }
+
+    /// Returns the edges where the two adjacent faces meet at an angle larger
+    /// than `angle_threshold_radians`, measured between their normals. One
+    /// halfedge is returned for each edge, always one that belongs to a face,
+    /// so the result can be passed directly to [`edit_ops::bevel_edges`].
+    ///
+    /// Edges on the boundary of the mesh only have one face, so they have no
+    /// angle. They are returned when `include_boundary` is set.
+    pub fn select_sharp_edges(
+        &self,
+        angle_threshold_radians: f32,
+        include_boundary: bool,
+    ) -> Vec<HalfEdgeId> {
+        let face_of = |h: HalfEdgeId| self[h].face;
+        self.iter_edges()
+            .filter_map(|h| {
+                let twin = self[h].twin;
+                match (face_of(h), twin.and_then(face_of)) {
+                    (Some(f1), Some(f2)) => {
+                        let cos = self.face_normal(f1).dot(self.face_normal(f2));
+                        (cos.clamp(-1.0, 1.0).acos() > angle_threshold_radians).then(|| h)
+                    }
+                    (Some(_), None) => include_boundary.then(|| h),
+                    (None, Some(_)) => include_boundary.then(|| twin).flatten(),
+                    (None, None) => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the average position of the vertices of a face.
+    pub fn face_centroid(&self, face: FaceId) -> Vec3 {
+        let vertices = self.face_vertices(face);
+        vertices
+            .iter()
+            .fold(Vec3::ZERO, |sum, v| sum + self.vertex_position(*v))
+            / vertices.len() as f32
+    }
+
+    /// Mirrors a face selection across the plane through the origin that is
+    /// perpendicular to `axis`. For each face in `faces`, returns the face
+    /// whose centroid is at the mirrored position of the face's centroid,
+    /// within `epsilon`. Faces without a mirrored counterpart are skipped.
+    ///
+    /// This only looks at face centroids, so it works best on meshes that
+    /// are symmetric across the plane.
+    pub fn mirror_selection(&self, faces: &[FaceId], axis: Axis, epsilon: f32) -> Vec<FaceId> {
+        // Centroids are bucketed in a grid with cells of size `epsilon`, so
+        // only the neighboring cells need to be checked for each lookup.
+        let epsilon = epsilon.max(f32::EPSILON);
+        let cell_of = |p: Vec3| (p / epsilon).floor().to_array().map(|x| x as i64);
+        let mut grid = HashMap::<[i64; 3], SVec<(FaceId, Vec3)>>::new();
+        for (face, _) in self.iter_faces() {
+            let centroid = self.face_centroid(face);
+            grid.entry(cell_of(centroid))
+                .or_default()
+                .push((face, centroid));
+        }
+
+        faces
+            .iter()
+            .filter_map(|&face| {
+                let centroid = self.face_centroid(face);
+                let mirrored = axis.mirror(centroid);
+                let [cx, cy, cz] = cell_of(mirrored);
+                (-1..=1)
+                    .flat_map(|dx| {
+                        (-1..=1).flat_map(move |dy| (-1..=1).map(move |dz| (dx, dy, dz)))
+                    })
+                    .filter_map(|(dx, dy, dz)| grid.get(&[cx + dx, cy + dy, cz + dz]))
+                    .flatten()
+                    .filter(|(_, c)| c.distance(mirrored) <= epsilon)
+                    .min_by(|(_, c1), (_, c2)| {
+                        c1.distance(mirrored).total_cmp(&c2.distance(mirrored))
+                    })
+                    .map(|(f, _)| *f)
+            })
+            .collect()
+    }
+
+    /// Returns whether two meshes are the same up to a relabeling of their
+    /// ids. See [`HalfEdgeMesh::structural_diff`].
+    pub fn structurally_equal(&self, other: &HalfEdgeMesh, epsilon: f32) -> bool {
+        self.structural_diff(other, epsilon).is_none()
+    }
+
+    /// Compares two meshes, ignoring their ids. Vertices are matched by
+    /// position, up to `epsilon`, and then the faces and halfedges of both
+    /// meshes are checked to connect the matched vertices in the same way.
+    ///
+    /// Returns None if the meshes are equal, or a human-readable description
+    /// of the first difference found otherwise. Useful in tests.
+    pub fn structural_diff(&self, other: &HalfEdgeMesh, epsilon: f32) -> Option<String> {
+        macro_rules! compare_counts {
+            ($name:expr, $iter:ident) => {
+                let (a, b) = (self.$iter().count(), other.$iter().count());
+                if a != b {
+                    return Some(format!("Different number of {}: {} vs {}", $name, a, b));
+                }
+            };
+        }
+        compare_counts!("vertices", iter_vertices);
+        compare_counts!("faces", iter_faces);
+        compare_counts!("halfedges", iter_halfedges);
+
+        // Vertices of both meshes are identified by their index in `self`
+        let self_idx: HashMap<VertexId, usize> = self
+            .iter_vertices()
+            .enumerate()
+            .map(|(i, (v, _))| (v, i))
+            .collect();
+        let mut other_idx = HashMap::<VertexId, usize>::new();
+        for (v, vertex) in self.iter_vertices() {
+            let matching = other
+                .iter_vertices()
+                .filter(|(w, _)| !other_idx.contains_key(w))
+                .map(|(w, other_vertex)| (w, other_vertex.position.distance(vertex.position)))
+                .filter(|(_, dist)| *dist <= epsilon)
+                .min_by(|(_, d1), (_, d2)| d1.total_cmp(d2));
+            match matching {
+                Some((w, _)) => {
+                    other_idx.insert(w, self_idx[&v]);
+                }
+                None => {
+                    return Some(format!(
+                        "Vertex {:?} at {:?} has no match in the other mesh",
+                        v, vertex.position
+                    ))
+                }
+            }
+        }
+
+        // Faces are compared as cycles of vertex indices, rotated to start at
+        // the smallest index so the starting halfedge doesn't matter.
+        let face_cycles = |mesh: &HalfEdgeMesh, idx: &HashMap<VertexId, usize>| {
+            mesh.iter_faces()
+                .map(|(f, _)| {
+                    let mut cycle = mesh.face_vertices(f).iter().map(|v| idx[v]).collect_vec();
+                    let min_pos = cycle.iter().position_min().unwrap_or(0);
+                    cycle.rotate_left(min_pos);
+                    cycle
+                })
+                .sorted()
+                .collect_vec()
+        };
+        let (faces_a, faces_b) = (face_cycles(self, &self_idx), face_cycles(other, &other_idx));
+        if let Some((a, b)) = faces_a.iter().zip(faces_b.iter()).find(|(a, b)| a != b) {
+            return Some(format!("Face mismatch: {:?} vs {:?}", a, b));
+        }
+
+        // Halfedges include the boundary, so this also compares the boundary
+        // loops and the twin relationships.
+        let halfedge_pairs = |mesh: &HalfEdgeMesh, idx: &HashMap<VertexId, usize>| {
+            mesh.iter_halfedges()
+                .map(|(h, _)| {
+                    mesh.at_halfedge(h)
+                        .src_dst_pair()
+                        .map(|(src, dst)| (idx[&src], idx[&dst]))
+                        .map_err(|err| format!("Malformed halfedge {:?}: {}", h, err))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map(|pairs| pairs.into_iter().sorted().collect_vec())
+        };
+        let (halfedges_a, halfedges_b) = match (
+            halfedge_pairs(self, &self_idx),
+            halfedge_pairs(other, &other_idx),
+        ) {
+            (Ok(a), Ok(b)) => (a, b),
+            (Err(err), _) | (_, Err(err)) => return Some(err),
+        };
+        if let Some((a, b)) = halfedges_a
+            .iter()
+            .zip(halfedges_b.iter())
+            .find(|(a, b)| a != b)
+        {
+            return Some(format!("Halfedge mismatch: {:?} vs {:?}", a, b));
+        }
+
+        None
+    }
+
+    /// Checks the connectivity of the mesh, returning every broken invariant
+    /// that was found. Useful to catch bugs in edit operations before some
+    /// traversal fails far away from the cause.
+    ///
+    /// Isolated vertices, with no halfedge, are valid.
+    pub fn validate(&self) -> Result<(), Vec<ValidationError>> {
+        use ValidationError::*;
+        let mut errors = vec![];
+
+        for (h, halfedge) in self.iter_halfedges() {
+            match halfedge.twin {
+                None => errors.push(HalfEdgeHasNoTwin(h)),
+                Some(twin) => match self.halfedge(twin) {
+                    None => errors.push(DeletedTwin(h)),
+                    Some(twin) if twin.twin != Some(h) => errors.push(TwinMismatch(h)),
+                    Some(_) => {}
+                },
+            }
+            match halfedge.next {
+                None => errors.push(HalfEdgeHasNoNext(h)),
+                Some(next) => match self.halfedge(next) {
+                    None => errors.push(DeletedNext(h)),
+                    Some(next) if next.face != halfedge.face => errors.push(NextFaceMismatch(h)),
+                    Some(_) => {}
+                },
+            }
+            match halfedge.vertex {
+                None => errors.push(HalfEdgeHasNoVertex(h)),
+                Some(v) if self.vertex(v).is_none() => errors.push(DeletedVertex(h)),
+                Some(_) => {}
+            }
+            if let Some(face) = halfedge.face {
+                if self.face(face).is_none() {
+                    errors.push(DeletedFace(h));
+                }
+            }
+        }
+
+        // Every halfedge must be part of a closed `next` loop. Loops can't
+        // share halfedges, so each one is only walked once.
+        let num_halfedges = self.iter_halfedges().count();
+        let mut visited = HashSet::<HalfEdgeId>::new();
+        for (h0, _) in self.iter_halfedges() {
+            if visited.contains(&h0) {
+                continue;
+            }
+            let mut h = h0;
+            let mut count = 0;
+            loop {
+                visited.insert(h);
+                match self.halfedge(h).and_then(|halfedge| halfedge.next) {
+                    Some(next) if next == h0 => break,
+                    Some(next) if !visited.contains(&next) && count < num_halfedges => {
+                        h = next;
+                        count += 1;
+                    }
+                    _ => {
+                        errors.push(NextLoopOpen(h0));
+                        break;
+                    }
+                }
+            }
+        }
+
+        for (face, face_data) in self.iter_faces() {
+            match face_data.halfedge {
+                None => errors.push(FaceHasNoHalfedge(face)),
+                Some(h) => match self.halfedge(h) {
+                    None => errors.push(DeletedFaceHalfEdge(face)),
+                    Some(halfedge) if halfedge.face != Some(face) => {
+                        errors.push(FaceHalfEdgeMismatch(face))
+                    }
+                    Some(_) => {}
+                },
+            }
+        }
+
+        for (v, vertex) in self.iter_vertices() {
+            if let Some(h) = vertex.halfedge {
+                match self.halfedge(h) {
+                    None => errors.push(DeletedVertexHalfEdge(v)),
+                    Some(halfedge) if halfedge.vertex != Some(v) => {
+                        errors.push(VertexHalfEdgeMismatch(v))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Groups the faces of the mesh into connected components, flood-filling
+    /// from each face across the edges it shares with other faces. Vertices
+    /// and edges that are not part of any face are not included.
+    pub fn connected_components(&self) -> Result<Vec<HashSet<FaceId>>> {
+        let mut visited = HashSet::new();
+        let mut components = vec![];
+        for (seed, _) in self.iter_faces() {
+            if !visited.insert(seed) {
+                continue;
+            }
+            let mut component = HashSet::new();
+            let mut stack = vec![seed];
+            while let Some(face) = stack.pop() {
+                component.insert(face);
+                for h in self.at_face(face).halfedges()? {
+                    if let Some(neighbor) = self.at_halfedge(h).twin().face_or_boundary()? {
+                        if visited.insert(neighbor) {
+                            stack.push(neighbor);
+                        }
+                    }
+                }
+            }
+            components.push(component);
+        }
+        Ok(components)
+    }
+
+    /// Returns the boundary loops of the mesh. Each loop is the cyclically
+    /// ordered list of the halfedges with no face that surround a hole, or
+    /// the outside of an open surface.
+    pub fn boundary_loops(&self) -> Result<Vec<Vec<HalfEdgeId>>> {
+        let num_halfedges = self.iter_halfedges().count();
+        let mut visited = HashSet::<HalfEdgeId>::new();
+        let mut loops = vec![];
+        for (h0, halfedge) in self.iter_halfedges() {
+            if halfedge.face.is_some() || visited.contains(&h0) {
+                continue;
+            }
+            let mut boundary_loop = vec![];
+            let mut h = h0;
+            loop {
+                if !visited.insert(h) || boundary_loop.len() > num_halfedges {
+                    bail!("Boundary loop does not close. Is the mesh malformed?")
+                }
+                boundary_loop.push(h);
+                h = self.at_halfedge(h).next().try_end()?;
+                if h == h0 {
+                    break;
+                }
+                if self[h].face.is_some() {
+                    bail!("Boundary loop continues into a face. Is the mesh malformed?")
+                }
+            }
+            loops.push(boundary_loop);
+        }
+        Ok(loops)
+    }
+
+    /// When the mesh is a simple open polyline, like the ones built with
+    /// [`primitives::Polyline`], returns its points in order, starting at one
+    /// of its ends. Returns an error for any other kind of mesh.
+    pub fn polyline_points(&self) -> Result<Vec<Vec3>> {
+        if self.iter_faces().next().is_some() {
+            bail!("Expected an open polyline, but the mesh has faces")
+        }
+        let loops = self.boundary_loops()?;
+        let boundary_loop = match loops.as_slice() {
+            [boundary_loop] => boundary_loop,
+            [] => bail!("Expected an open polyline, but the mesh is empty"),
+            _ => bail!(
+                "Expected a single open polyline, but the mesh has {} pieces",
+                loops.len()
+            ),
+        };
+
+        // The loop goes along the polyline and back, turning around at the
+        // ends, so it visits each end once and every other point twice.
+        let srcs = boundary_loop
+            .iter()
+            .map(|h| self.at_halfedge(*h).vertex().try_end())
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut visits = HashMap::<VertexId, usize>::new();
+        for v in &srcs {
+            *visits.entry(*v).or_default() += 1;
+        }
+        let num_points = visits.len();
+        let is_open_polyline = boundary_loop.len() == 2 * (num_points - 1)
+            && visits.values().filter(|count| **count == 1).count() == 2
+            && visits.values().all(|count| *count <= 2);
+        if !is_open_polyline {
+            bail!("Expected an open polyline, but the edges form a loop or branch out")
+        }
+
+        let start = srcs.iter().position(|v| visits[v] == 1).unwrap();
+        Ok(srcs
+            .iter()
+            .cycle()
+            .skip(start)
+            .take(num_points)
+            .map(|v| self.vertex_position(*v))
+            .collect())
+    }
+
+    /// Computes a few statistics about the mesh. See [`MeshStats`].
+    pub fn stats(&self) -> MeshStats {
+        let num_edges = self.iter_edges().count();
+
+        let is_closed = self
+            .iter_halfedges()
+            .all(|(_, halfedge)| halfedge.face.is_some() && halfedge.twin.is_some());
+
+        MeshStats {
+            num_vertices: self.iter_vertices().count(),
+            num_edges,
+            num_faces: self.iter_faces().count(),
+            is_closed,
+            is_manifold: self.is_manifold(),
+            bbox: self.bounding_box().unwrap_or((Vec3::ZERO, Vec3::ZERO)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_select_checker_faces() {
+        // A flat 4x4 grid of quads
+        let n = 4;
+        let positions = (0..=n)
+            .flat_map(|z| (0..=n).map(move |x| Vec3::new(x as f32, 0.0, z as f32)))
+            .collect_vec();
+        let idx = |x: usize, z: usize| z * (n + 1) + x;
+        let polygons = (0..n)
+            .flat_map(|z| {
+                (0..n).map(move |x| [idx(x, z), idx(x, z + 1), idx(x + 1, z + 1), idx(x + 1, z)])
+            })
+            .collect_vec();
+        let mesh = HalfEdgeMesh::build_from_polygons(&positions, &polygons).unwrap();
+
+        let start = mesh.iter_faces().next().unwrap().0;
+        let selected = mesh.select_checker_faces(start);
+        assert_eq!(selected.len(), 8);
+        assert!(selected.contains(&start));
+
+        // No two selected faces share an edge
+        for &face in selected.iter() {
+            for h in mesh.face_edges(face) {
+                if let Some(neighbor) = mesh[h].twin.and_then(|twin| mesh[twin].face) {
+                    assert!(!selected.contains(&neighbor));
+                }
+            }
+        }
+    }
+
+    #[test]
+    pub fn test_structural_diff() {
+        let mesh = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+
+        // Rebuild the same box, with faces in a different order and each face
+        // starting at a different vertex, so all the ids are different.
+        let positions = mesh.iter_vertices().map(|(_, v)| v.position).collect_vec();
+        let idx: HashMap<VertexId, usize> = mesh
+            .iter_vertices()
+            .enumerate()
+            .map(|(i, (v, _))| (v, i))
+            .collect();
+        let polygons = mesh
+            .iter_faces()
+            .map(|(f, _)| {
+                let mut polygon = mesh.face_vertices(f).iter().map(|v| idx[v]).collect_vec();
+                polygon.rotate_left(1);
+                polygon
+            })
+            .rev()
+            .collect_vec();
+        let rebuilt = HalfEdgeMesh::build_from_polygons(&positions, &polygons).unwrap();
+        assert_eq!(mesh.structural_diff(&rebuilt, 1e-5), None);
+
+        let mut moved = rebuilt.clone();
+        let v = moved.iter_vertices().next().unwrap().0;
+        moved.update_vertex_position(v, |pos| pos + Vec3::X);
+        assert!(!mesh.structurally_equal(&moved, 1e-5));
+        assert!(rebuilt
+            .structural_diff(&moved, 1e-5)
+            .unwrap()
+            .contains("no match"));
+    }
+
+    #[test]
+    pub fn test_select_sharp_edges() {
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        assert_eq!(cube.select_sharp_edges(0.1, false).len(), 12);
+        assert_eq!(
+            cube.select_sharp_edges(std::f32::consts::FRAC_PI_2 + 0.1, false)
+                .len(),
+            0
+        );
+
+        // A single quad has no angles, only boundary edges
+        let quad = halfedge::primitives::Quad::build(Vec3::ZERO, Vec3::Y, Vec3::X, Vec2::ONE);
+        assert_eq!(quad.select_sharp_edges(0.1, false).len(), 0);
+        let boundary = quad.select_sharp_edges(0.1, true);
+        assert_eq!(boundary.len(), 4);
+        assert!(boundary.iter().all(|h| quad[*h].face.is_some()));
+    }
+
+    #[test]
+    pub fn test_mirror_selection() {
+        // A row of 4 quads, from x = -2 to x = 2
+        let positions = (0..=1)
+            .flat_map(|z| (-2..=2).map(move |x| Vec3::new(x as f32, 0.0, z as f32)))
+            .collect_vec();
+        let polygons = (0..4usize).map(|x| [x, x + 5, x + 6, x + 1]).collect_vec();
+        let mesh = HalfEdgeMesh::build_from_polygons(&positions, &polygons).unwrap();
+
+        let leftmost = mesh
+            .iter_faces()
+            .map(|(f, _)| f)
+            .find(|f| mesh.face_centroid(*f).x < -1.0)
+            .unwrap();
+        let mirrored = mesh.mirror_selection(&[leftmost], Axis::X, 1e-4);
+        assert_eq!(mirrored.len(), 1);
+        assert!((mesh.face_centroid(mirrored[0]).x - 1.5).abs() < 1e-5);
+
+        // Mirroring across Z finds no counterpart, since all faces have z > 0
+        assert!(mesh.mirror_selection(&[leftmost], Axis::Z, 1e-4).is_empty());
+    }
+
+    #[test]
+    pub fn test_polyline_points() {
+        let points = [Vec3::ZERO, Vec3::X, Vec3::new(1.0, 1.0, 0.0)];
+        let polyline = halfedge::primitives::Polyline::build(&points).unwrap();
+        assert_eq!(polyline.boundary_loops().unwrap().len(), 1);
+        let extracted = polyline.polyline_points().unwrap();
+        assert!(extracted == points || extracted.iter().rev().eq(points.iter()));
+
+        // A closed mesh has no boundary, and a quad is not a polyline
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        assert!(cube.boundary_loops().unwrap().is_empty());
+        let quad = halfedge::primitives::Quad::build(Vec3::ZERO, Vec3::Y, Vec3::X, Vec2::ONE);
+        assert_eq!(quad.boundary_loops().unwrap()[0].len(), 4);
+        assert!(quad.polyline_points().is_err());
+    }
+
+    #[test]
+    pub fn test_vertex_normals() {
+        // A corner where one side is split into many thin triangles. Angle
+        // weighting makes the split irrelevant.
+        let mut positions = vec![Vec3::ZERO, Vec3::X, Vec3::Z];
+        let mut polygons = vec![vec![0usize, 2, 1]];
+        let n = 8;
+        for i in 1..=n {
+            let t = i as f32 / n as f32;
+            positions.push(Vec3::new(0.0, t, 1.0 - t));
+            polygons.push(vec![0, 2 + i, 1 + i]);
+        }
+        let mesh = HalfEdgeMesh::build_from_polygons(&positions, &polygons).unwrap();
+        let v = mesh.iter_vertices().next().unwrap().0;
+
+        let expected = Vec3::new(1.0, 1.0, 0.0).normalize();
+        let normal = mesh.vertex_normal(v).unwrap();
+        assert!(normal.distance(expected) < 1e-4);
+        assert!(mesh.compute_vertex_normals()[&v].distance(normal) < 1e-5);
+    }
+
+    #[test]
+    pub fn test_validate() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        assert_eq!(cube.validate(), Ok(()));
+        let line = halfedge::primitives::Line::build(Vec3::ZERO, Vec3::X, 4);
+        assert_eq!(line.validate(), Ok(()));
+
+        let (h, _) = cube.iter_halfedges().next().unwrap();
+        let twin = cube[h].twin.unwrap();
+        cube[h].twin = cube[h].next;
+        let errors = cube.validate().unwrap_err();
+        assert!(errors.contains(&ValidationError::TwinMismatch(h)));
+        assert!(errors.contains(&ValidationError::TwinMismatch(twin)));
+
+        cube[h].twin = Some(twin);
+        let (v, _) = cube.iter_vertices().next().unwrap();
+        let h_out = cube[v].halfedge.unwrap();
+        cube.remove_halfedge(h_out);
+        assert!(cube
+            .validate()
+            .unwrap_err()
+            .contains(&ValidationError::DeletedVertexHalfEdge(v)));
+    }
+
+    #[test]
+    pub fn test_non_manifold_edges() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        assert!(cube.non_manifold_edges().is_empty());
+        assert!(cube.is_manifold());
+
+        // The 2-sided face made by duplicate_edge goes along the edge in the
+        // same direction as the faces next to it.
+        let (h, _) = cube.iter_halfedges().next().unwrap();
+        let h_dup = edit_ops::duplicate_edge(&mut cube, h).unwrap();
+        let twin = cube[h].twin.unwrap();
+        let twin_dup = cube[h_dup].twin.unwrap();
+        let mut expected = vec![h, h_dup, twin, twin_dup];
+        expected.sort();
+        assert_eq!(cube.non_manifold_edges(), expected);
+        assert!(!cube.is_manifold());
+    }
+}
@@ -0,0 +1,302 @@
+use std::sync::{Arc, Mutex};
+
+use crate::prelude::*;
+
+/// The result of a successful [`HalfEdgeMesh::raycast`].
+#[derive(Debug, Clone, Copy)]
+pub struct RayHit {
+    /// The face that was hit.
+    pub face: FaceId,
+    /// Faces are triangulated as fans for the intersection test. This is the
+    /// triangle of `face` that was hit.
+    pub triangle: [VertexId; 3],
+    /// The barycentric coordinates of the hit point, with respect to the
+    /// vertices in `triangle`.
+    pub barycentric: Vec3,
+    /// The distance from the ray origin to the hit point, measured in units
+    /// of the ray's direction vector.
+    pub distance: f32,
+    /// The position of the hit point.
+    pub position: Vec3,
+}
+
+/// Intersects the line `origin + t * dir` with a triangle, using the
+/// Möller–Trumbore algorithm. Returns the `t` parameter of the intersection,
+/// which may be negative, and the barycentric coordinates of the hit point.
+pub(super) fn line_triangle_intersection(
+    origin: Vec3,
+    dir: Vec3,
+    [a, b, c]: [Vec3; 3],
+) -> Option<(f32, Vec3)> {
+    let (e1, e2) = (b - a, c - a);
+    let p = dir.cross(e2);
+    let det = e1.dot(p);
+    // The line is parallel to the triangle
+    if det.abs() < 1e-8 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+    let s = origin - a;
+    let u = s.dot(p) * inv_det;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(e1);
+    let v = dir.dot(q) * inv_det;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    Some((e2.dot(q) * inv_det, Vec3::new(1.0 - u - v, u, v)))
+}
+
+#[derive(Debug)]
+struct BvhTriangle {
+    face: FaceId,
+    vertices: [VertexId; 3],
+    positions: [Vec3; 3],
+}
+
+#[derive(Debug)]
+enum BvhNode {
+    /// A node with two children, stored at the given indices.
+    Inner {
+        bbox: (Vec3, Vec3),
+        left: usize,
+        right: usize,
+    },
+    /// A node containing the triangles in the given range.
+    Leaf {
+        bbox: (Vec3, Vec3),
+        triangles: std::ops::Range<usize>,
+    },
+}
+
+/// A bounding volume hierarchy over the triangulated faces of a mesh, used
+/// to speed up raycasts. The first node is the root.
+#[derive(Debug)]
+pub(super) struct Bvh {
+    nodes: Vec<BvhNode>,
+    triangles: Vec<BvhTriangle>,
+}
+
+/// Maximum number of triangles stored in a leaf node.
+const BVH_LEAF_SIZE: usize = 4;
+
+fn triangles_bbox(triangles: &[BvhTriangle]) -> (Vec3, Vec3) {
+    triangles
+        .iter()
+        .flat_map(|t| t.positions.iter().cloned())
+        .fold(
+            (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+            |(min, max), p| (min.min(p), max.max(p)),
+        )
+}
+
+/// Returns the entry distance of the ray into the bounding box, or None if
+/// it misses the box or the box is farther than `max_t`. Uses the slab
+/// method.
+fn ray_bbox_intersection(
+    origin: Vec3,
+    inv_dir: Vec3,
+    (min, max): (Vec3, Vec3),
+    max_t: f32,
+) -> Option<f32> {
+    let t1 = (min - origin) * inv_dir;
+    let t2 = (max - origin) * inv_dir;
+    let t_enter = t1.min(t2).max_element().max(0.0);
+    let t_exit = t1.max(t2).min_element().min(max_t);
+    (t_enter <= t_exit).then(|| t_enter)
+}
+
+impl Bvh {
+    fn build(mesh: &HalfEdgeMesh) -> Bvh {
+        let mut triangles = vec![];
+        for (face, _) in mesh.iter_faces() {
+            let vertices = mesh.face_vertices(face);
+            for (&b, &c) in vertices[1..].iter().tuple_windows() {
+                let tri = [vertices[0], b, c];
+                triangles.push(BvhTriangle {
+                    face,
+                    vertices: tri,
+                    positions: tri.map(|v| mesh.vertex_position(v)),
+                });
+            }
+        }
+
+        let mut bvh = Bvh {
+            nodes: vec![],
+            triangles: vec![],
+        };
+        if !triangles.is_empty() {
+            bvh.build_node(triangles);
+        }
+        bvh
+    }
+
+    /// Adds a node for the given triangles, splitting them at the median of
+    /// the longest axis of their bounding box. Returns the node's index.
+    fn build_node(&mut self, mut triangles: Vec<BvhTriangle>) -> usize {
+        let bbox = triangles_bbox(&triangles);
+        let node_idx = self.nodes.len();
+
+        if triangles.len() <= BVH_LEAF_SIZE {
+            let start = self.triangles.len();
+            self.triangles.append(&mut triangles);
+            self.nodes.push(BvhNode::Leaf {
+                bbox,
+                triangles: start..self.triangles.len(),
+            });
+            return node_idx;
+        }
+
+        let extent = bbox.1 - bbox.0;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let centroid = |t: &BvhTriangle| (t.positions[0] + t.positions[1] + t.positions[2])[axis];
+        triangles.sort_by(|t1, t2| centroid(t1).total_cmp(&centroid(t2)));
+        let right_half = triangles.split_off(triangles.len() / 2);
+
+        // The children's indices are not known yet, so reserve the slot.
+        self.nodes.push(BvhNode::Inner {
+            bbox,
+            left: 0,
+            right: 0,
+        });
+        let left_idx = self.build_node(triangles);
+        let right_idx = self.build_node(right_half);
+        self.nodes[node_idx] = BvhNode::Inner {
+            bbox,
+            left: left_idx,
+            right: right_idx,
+        };
+        node_idx
+    }
+
+    fn raycast(&self, origin: Vec3, dir: Vec3) -> Option<RayHit> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+        let inv_dir = dir.recip();
+        let mut best: Option<RayHit> = None;
+        let mut stack = vec![0];
+
+        while let Some(node_idx) = stack.pop() {
+            let max_t = best.map(|hit| hit.distance).unwrap_or(f32::INFINITY);
+            match &self.nodes[node_idx] {
+                BvhNode::Inner { bbox, left, right } => {
+                    if ray_bbox_intersection(origin, inv_dir, *bbox, max_t).is_some() {
+                        stack.push(*left);
+                        stack.push(*right);
+                    }
+                }
+                BvhNode::Leaf { bbox, triangles } => {
+                    if ray_bbox_intersection(origin, inv_dir, *bbox, max_t).is_none() {
+                        continue;
+                    }
+                    for tri in &self.triangles[triangles.clone()] {
+                        let max_t = best.map(|hit| hit.distance).unwrap_or(f32::INFINITY);
+                        if let Some((t, barycentric)) =
+                            line_triangle_intersection(origin, dir, tri.positions)
+                        {
+                            if t >= 0.0 && t < max_t {
+                                best = Some(RayHit {
+                                    face: tri.face,
+                                    triangle: tri.vertices,
+                                    barycentric,
+                                    distance: t,
+                                    position: origin + dir * t,
+                                });
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Holds the BVH for a mesh, built lazily on the first raycast. The mesh
+/// clears the cache on every operation that may change its geometry.
+///
+/// NOTE: Cloning the cache gives back an empty one. This keeps cloning meshes
+/// cheap, and clones often get modified right away.
+#[derive(Default)]
+pub(super) struct BvhCache(Mutex<Option<Arc<Bvh>>>);
+
+impl BvhCache {
+    pub(super) fn invalidate(&mut self) {
+        *self.0.get_mut().unwrap_or_else(|err| err.into_inner()) = None;
+    }
+
+    fn get_or_build(&self, mesh: &HalfEdgeMesh) -> Arc<Bvh> {
+        let mut cached = self.0.lock().unwrap_or_else(|err| err.into_inner());
+        cached
+            .get_or_insert_with(|| Arc::new(Bvh::build(mesh)))
+            .clone()
+    }
+}
+
+impl Clone for BvhCache {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl std::fmt::Debug for BvhCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BvhCache")
+    }
+}
+
+impl HalfEdgeMesh {
+    /// Casts a ray from `origin` along `dir`, returning the closest hit with
+    /// the faces of the mesh, if any. Faces are triangulated as fans, so
+    /// non-planar faces may not give the expected results.
+    ///
+    /// The first raycast on a mesh builds an acceleration structure, which is
+    /// reused by later raycasts until the mesh is modified.
+    pub fn raycast(&self, origin: Vec3, dir: Vec3) -> Option<RayHit> {
+        if dir == Vec3::ZERO {
+            return None;
+        }
+        self.bvh_cache.get_or_build(self).raycast(origin, dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_raycast_box() {
+        let mesh = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+
+        let hit = mesh.raycast(Vec3::new(0.1, 5.0, 0.2), -Vec3::Y).unwrap();
+        assert!((hit.distance - 4.5).abs() < 1e-5);
+        assert!(hit.position.distance(Vec3::new(0.1, 0.5, 0.2)) < 1e-5);
+        let [a, b, c] = hit.triangle.map(|v| mesh.vertex_position(v));
+        let from_barycentric =
+            a * hit.barycentric.x + b * hit.barycentric.y + c * hit.barycentric.z;
+        assert!(from_barycentric.distance(hit.position) < 1e-5);
+
+        // Rays pointing away from the mesh, or missing it, don't hit
+        assert!(mesh.raycast(Vec3::new(0.1, 5.0, 0.2), Vec3::Y).is_none());
+        assert!(mesh.raycast(Vec3::new(2.0, 5.0, 0.0), -Vec3::Y).is_none());
+
+        // The cached structure is rebuilt after moving the vertices
+        let mut moved = mesh.clone();
+        assert!(moved.raycast(Vec3::new(0.1, 5.0, 0.2), -Vec3::Y).is_some());
+        let vertices = moved.iter_vertices().map(|(v, _)| v).collect_vec();
+        for v in vertices {
+            moved.update_vertex_position(v, |pos| pos + Vec3::Y);
+        }
+        let hit = moved.raycast(Vec3::new(0.1, 5.0, 0.2), -Vec3::Y).unwrap();
+        assert!((hit.distance - 3.5).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::prelude::*;
+
+/// A set of mesh elements, which tells an edit operation the elements it
+/// should work on. Selections flow between nodes, e.g. the faces created by
+/// an extrusion can be passed to another operation.
+#[derive(Debug, Clone)]
+pub enum Selection {
+    /// Indices in the iteration order of the mesh, of whichever kind of
+    /// element the operation works on. This is what users type in selection
+    /// parameters.
+    Indices(Vec<u32>),
+    /// The id-based variants also store the [`MeshId`] of the mesh the ids
+    /// belong to, and can't be resolved on unrelated meshes.
+    Vertices(MeshId, Vec<VertexId>),
+    /// Edges, identified by one of their halfedges.
+    Edges(MeshId, Vec<HalfEdgeId>),
+    Faces(MeshId, Vec<FaceId>),
+}
+
+impl Selection {
+    pub fn len(&self) -> usize {
+        match self {
+            Selection::Indices(indices) => indices.len(),
+            Selection::Vertices(_, vertices) => vertices.len(),
+            Selection::Edges(_, edges) => edges.len(),
+            Selection::Faces(_, faces) => faces.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The kind of elements in this selection, as shown to the user.
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Selection::Indices(_) => "indices",
+            Selection::Vertices(..) => "vertices",
+            Selection::Edges(..) => "edges",
+            Selection::Faces(..) => "faces",
+        }
+    }
+
+    /// Returns the ids of the selected elements of `mesh`. Indices are
+    /// resolved to the ids of the elements at those positions in `all_ids`.
+    /// Ids must be of the `expected` kind, come from `mesh_id` and still
+    /// exist in the mesh, which may not be the case if the mesh was edited
+    /// after making the selection.
+    fn resolve<Id: Copy>(
+        &self,
+        expected: &str,
+        mesh_id: MeshId,
+        ids: Option<(MeshId, &[Id])>,
+        all_ids: impl Iterator<Item = Id>,
+        exists: impl Fn(Id) -> bool,
+    ) -> Result<Vec<Id>> {
+        match (self, ids) {
+            (Selection::Indices(indices), _) => {
+                let all_ids = all_ids.collect_vec();
+                indices
+                    .iter()
+                    .map(|idx| {
+                        all_ids
+                            .get(*idx as usize)
+                            .cloned()
+                            .ok_or_else(|| anyhow!("Invalid index: {}", idx))
+                    })
+                    .collect()
+            }
+            (_, Some((source_id, ids))) => {
+                if source_id != mesh_id {
+                    bail!("The selection refers to {} of a different mesh", expected);
+                }
+                if !ids.iter().all(|id| exists(*id)) {
+                    bail!(
+                        "The selection refers to {} that are no longer in the mesh",
+                        expected
+                    );
+                }
+                Ok(ids.to_vec())
+            }
+            (_, None) => bail!(
+                "Expected a selection of {}, but got {}",
+                expected,
+                self.kind_name()
+            ),
+        }
+    }
+
+    pub fn resolve_vertices(&self, mesh: &HalfEdgeMesh) -> Result<Vec<VertexId>> {
+        let ids = match self {
+            Selection::Vertices(source_id, vertices) => Some((*source_id, vertices.as_slice())),
+            _ => None,
+        };
+        self.resolve(
+            "vertices",
+            mesh.id(),
+            ids,
+            mesh.iter_vertices().map(|(v, _)| v),
+            |v| mesh.vertex(v).is_some(),
+        )
+    }
+
+    /// NOTE: Indices refer to halfedges, not edges, so each edge can be
+    /// selected from either side.
+    pub fn resolve_halfedges(&self, mesh: &HalfEdgeMesh) -> Result<Vec<HalfEdgeId>> {
+        let ids = match self {
+            Selection::Edges(source_id, edges) => Some((*source_id, edges.as_slice())),
+            _ => None,
+        };
+        self.resolve(
+            "edges",
+            mesh.id(),
+            ids,
+            mesh.iter_halfedges().map(|(h, _)| h),
+            |h| mesh.halfedge(h).is_some(),
+        )
+    }
+
+    pub fn resolve_faces(&self, mesh: &HalfEdgeMesh) -> Result<Vec<FaceId>> {
+        let ids = match self {
+            Selection::Faces(source_id, faces) => Some((*source_id, faces.as_slice())),
+            _ => None,
+        };
+        self.resolve(
+            "faces",
+            mesh.id(),
+            ids,
+            mesh.iter_faces().map(|(f, _)| f),
+            |f| mesh.face(f).is_some(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_resolve_selection() {
+        let mut mesh = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let faces = mesh.iter_faces().map(|(f, _)| f).collect_vec();
+
+        let by_index = Selection::Indices(vec![2, 0]).resolve_faces(&mesh).unwrap();
+        assert_eq!(by_index, vec![faces[2], faces[0]]);
+        assert!(Selection::Indices(vec![6]).resolve_faces(&mesh).is_err());
+
+        // Ids must be of the right kind, and still be part of the mesh
+        let selection = Selection::Faces(mesh.id(), vec![faces[1]]);
+        assert_eq!(selection.resolve_faces(&mesh).unwrap(), vec![faces[1]]);
+        assert!(selection.resolve_vertices(&mesh).is_err());
+
+        // Clones share the ids, but other meshes don't, even when the same
+        // ids happen to exist in them
+        assert!(selection.resolve_faces(&mesh.clone()).is_ok());
+        let other = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        assert!(other.face(faces[1]).is_some());
+        assert!(selection.resolve_faces(&other).is_err());
+
+        mesh.remove_face(faces[1]);
+        assert!(selection.resolve_faces(&mesh).is_err());
+    }
+}
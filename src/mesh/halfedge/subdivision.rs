@@ -0,0 +1,727 @@
+use crate::prelude::*;
+
+/// Information about an edge of the mesh being subdivided.
+struct EdgeInfo {
+    /// One of the two halfedges of the edge.
+    halfedge: HalfEdgeId,
+    src: VertexId,
+    dst: VertexId,
+    /// The crease weight. Boundary edges are always fully sharp.
+    crease: f32,
+    is_boundary: bool,
+}
+
+/// How [`catmull_clark_subdivide`] treats the boundary of open meshes. In all
+/// modes, the boundary edges are sharp, so the surface doesn't pull away from
+/// them. The string representation is what gets shown in the UI.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::AsRefStr,
+)]
+pub enum BoundaryMode {
+    /// The boundary is smoothed as a curve, except for its corners, i.e.
+    /// the vertices with only two edges, which stay in place. This is the
+    /// default in most modeling tools.
+    #[strum(serialize = "Keep corners")]
+    KeepCorners,
+    /// All of the boundary vertices stay in place, so the boundary keeps its
+    /// original shape.
+    #[strum(serialize = "Keep all")]
+    KeepAll,
+    /// The whole boundary is smoothed as a curve, which rounds its corners.
+    #[strum(serialize = "Smooth")]
+    Smooth,
+}
+
+/// The subdivision algorithms supported by the Subdivide node. The string
+/// representation is what gets shown in the UI.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    strum_macros::EnumIter,
+    strum_macros::EnumString,
+    strum_macros::AsRefStr,
+)]
+pub enum SubdivisionScheme {
+    /// See [`catmull_clark_subdivide`].
+    #[strum(serialize = "Catmull-Clark")]
+    CatmullClark,
+    /// See [`loop_subdivide`].
+    #[strum(serialize = "Loop")]
+    Loop,
+    /// See [`linear_subdivide`].
+    #[strum(serialize = "Linear")]
+    Linear,
+}
+
+/// Applies Catmull-Clark subdivision `iterations` times, returning the
+/// subdivided mesh. Each iteration replaces every face with `n` quads, where
+/// `n` is the number of vertices of the face, and smooths the result.
+///
+/// Edges with a crease weight (see [`HalfEdgeMesh::set_edge_crease`]) are
+/// kept sharper: A weight of 1.0 uses the crease rules, like the boundary of
+/// the mesh, and intermediate weights interpolate between the smooth and the
+/// sharp rules. The edges resulting from subdividing a creased edge keep its
+/// weight, so creases are preserved across iterations.
+///
+/// The vertices on the boundary of open meshes are moved according to
+/// `boundary`, see [`BoundaryMode`].
+///
+/// The mesh is rebuilt on every iteration, so all ids change. Attributes other
+/// than creases are not kept.
+///
+/// Fails before doing any work if the result would exceed
+/// [`limits::max_elements`].
+pub fn catmull_clark_subdivide(
+    mesh: &HalfEdgeMesh,
+    iterations: u32,
+    boundary: BoundaryMode,
+) -> Result<HalfEdgeMesh> {
+    catmull_clark_subdivide_with_progress(mesh, iterations, boundary, &progress::no_progress)
+}
+
+/// Same as [`catmull_clark_subdivide`], but reports its progress and can be
+/// cancelled. See [`Progress`].
+pub fn catmull_clark_subdivide_with_progress(
+    mesh: &HalfEdgeMesh,
+    iterations: u32,
+    boundary: BoundaryMode,
+    progress: Progress,
+) -> Result<HalfEdgeMesh> {
+    projected_counts(mesh, iterations).check("catmull_clark_subdivide")?;
+    iterate(mesh, iterations, progress, |mesh, progress| {
+        subdivide_once(mesh, boundary, true, progress)
+    })
+}
+
+/// Runs `iterations` of a subdivision step, starting from `mesh`. Each step
+/// gets its share of the overall `progress`.
+fn iterate(
+    mesh: &HalfEdgeMesh,
+    iterations: u32,
+    progress: Progress,
+    step: impl Fn(&HalfEdgeMesh, Progress) -> Result<HalfEdgeMesh>,
+) -> Result<HalfEdgeMesh> {
+    // Each iteration roughly multiplies the number of faces by 4, and so does
+    // the work needed for the next one.
+    let total_work = (0..iterations).map(|i| 4f32.powi(i as i32)).sum::<f32>();
+    let mut done = 0.0;
+
+    let mut result = mesh.clone();
+    for i in 0..iterations {
+        let work = 4f32.powi(i as i32);
+        let step_progress =
+            progress::scaled(progress, done / total_work, (done + work) / total_work);
+        result = step(&result, &step_progress)?;
+        done += work;
+    }
+    progress::report(progress, 1.0)?;
+    Ok(result)
+}
+
+/// Splits the faces of the mesh like [`catmull_clark_subdivide`], but without
+/// smoothing: New vertices are placed at the edge midpoints and face centers,
+/// and the original vertices keep their positions. Useful to add detail to a
+/// mesh before deforming it.
+///
+/// The mesh is rebuilt on every iteration, so all ids change. Attributes other
+/// than creases are not kept.
+///
+/// Fails before doing any work if the result would exceed
+/// [`limits::max_elements`].
+pub fn linear_subdivide(mesh: &HalfEdgeMesh, iterations: u32) -> Result<HalfEdgeMesh> {
+    linear_subdivide_with_progress(mesh, iterations, &progress::no_progress)
+}
+
+/// Same as [`linear_subdivide`], but reports its progress and can be
+/// cancelled. See [`Progress`].
+pub fn linear_subdivide_with_progress(
+    mesh: &HalfEdgeMesh,
+    iterations: u32,
+    progress: Progress,
+) -> Result<HalfEdgeMesh> {
+    projected_counts(mesh, iterations).check("linear_subdivide")?;
+    iterate(mesh, iterations, progress, |mesh, progress| {
+        // NOTE: The boundary mode only affects smoothing
+        subdivide_once(mesh, BoundaryMode::KeepAll, false, progress)
+    })
+}
+
+/// Computes the size of the mesh after `iterations` of subdivision, without
+/// subdividing it.
+fn projected_counts(mesh: &HalfEdgeMesh, iterations: u32) -> limits::ElementCounts {
+    let mut counts = limits::ElementCounts::of_mesh(mesh);
+    // Each face is split into as many quads as it has sides.
+    let mut face_sides = mesh
+        .iter_faces()
+        .map(|(f, _)| mesh.num_face_edges(f) as u64)
+        .sum::<u64>();
+    for _ in 0..iterations {
+        counts = limits::ElementCounts {
+            vertices: counts
+                .vertices
+                .saturating_add(counts.edges)
+                .saturating_add(counts.faces),
+            edges: counts.edges.saturating_mul(2).saturating_add(face_sides),
+            faces: face_sides,
+        };
+        face_sides = counts.faces.saturating_mul(4);
+        // NOTE: Counts saturate instead of overflowing. The limit is hit long
+        // before that anyway, so there's no need to keep going.
+        if counts.faces == u64::MAX {
+            break;
+        }
+    }
+    counts
+}
+
+/// Does one iteration of Catmull-Clark subdivision. When `smooth` is false,
+/// new vertices are placed at the midpoints and face centers, and original
+/// vertices don't move.
+fn subdivide_once(
+    mesh: &HalfEdgeMesh,
+    boundary: BoundaryMode,
+    smooth: bool,
+    progress: Progress,
+) -> Result<HalfEdgeMesh> {
+    // The new vertices are, in order: One per original vertex, one per face
+    // and one per edge.
+    let vertex_idx: HashMap<VertexId, usize> = mesh
+        .iter_vertices()
+        .enumerate()
+        .map(|(i, (v, _))| (v, i))
+        .collect();
+    let num_vertices = vertex_idx.len();
+
+    let mut face_points = vec![];
+    let mut face_idx = HashMap::<FaceId, usize>::new();
+    for (face, _) in mesh.iter_faces() {
+        let vertices = mesh.face_vertices(face);
+        let sum = vertices
+            .iter()
+            .fold(Vec3::ZERO, |sum, v| sum + mesh.vertex_position(*v));
+        face_idx.insert(face, num_vertices + face_points.len());
+        face_points.push(sum / vertices.len() as f32);
+    }
+    progress::report(progress, 0.1)?;
+
+    let mut edges = vec![];
+    let mut edge_idx = HashMap::<HalfEdgeId, usize>::new();
+    for h in mesh.iter_edges() {
+        let (src, dst) = mesh.at_halfedge(h).src_dst_pair()?;
+        let twin = mesh[h].twin;
+        let is_boundary = mesh[h].face.is_none() || twin.and_then(|t| mesh[t].face).is_none();
+        edge_idx.insert(h, edges.len());
+        if let Some(twin) = twin {
+            edge_idx.insert(twin, edges.len());
+        }
+        edges.push(EdgeInfo {
+            halfedge: h,
+            src,
+            dst,
+            crease: if is_boundary {
+                1.0
+            } else {
+                mesh.edge_crease(h)
+            },
+            is_boundary,
+        });
+    }
+    let edge_point_idx = |e: usize| num_vertices + face_points.len() + e;
+    progress::report(progress, 0.2)?;
+
+    // Edge points: The average of the endpoints and the adjacent face points
+    // for smooth edges, and the midpoint for sharp ones.
+    let edge_points = edges
+        .iter()
+        .map(|edge| {
+            let midpoint = (mesh.vertex_position(edge.src) + mesh.vertex_position(edge.dst)) * 0.5;
+            if !smooth || edge.crease >= 1.0 {
+                return midpoint;
+            }
+            let twin = mesh[edge.halfedge].twin;
+            let faces = [Some(edge.halfedge), twin]
+                .into_iter()
+                .flatten()
+                .filter_map(|h| mesh[h].face)
+                .map(|f| face_points[face_idx[&f] - num_vertices])
+                .collect::<SVec<_>>();
+            let smooth = (midpoint * 2.0 + faces.iter().fold(Vec3::ZERO, |sum, f| sum + *f))
+                / (2 + faces.len()) as f32;
+            smooth.lerp(midpoint, edge.crease)
+        })
+        .collect_vec();
+    progress::report(progress, 0.3)?;
+
+    // Vertex points
+    let mut vertex_edges = HashMap::<VertexId, SVec<usize>>::new();
+    for (i, edge) in edges.iter().enumerate() {
+        vertex_edges.entry(edge.src).or_default().push(i);
+        vertex_edges.entry(edge.dst).or_default().push(i);
+    }
+    let mut vertex_faces = HashMap::<VertexId, SVec<FaceId>>::new();
+    for (face, _) in mesh.iter_faces() {
+        for v in mesh.face_vertices(face) {
+            vertex_faces.entry(v).or_default().push(face);
+        }
+    }
+
+    let vertex_points = mesh
+        .iter_vertices()
+        .map(|(v, vertex)| {
+            let p = vertex.position;
+            let incident = vertex_edges.get(&v).cloned().unwrap_or_default();
+            let faces = vertex_faces.get(&v).cloned().unwrap_or_default();
+            if !smooth || incident.is_empty() || faces.is_empty() {
+                return p;
+            }
+            let is_boundary = incident.iter().any(|e| edges[*e].is_boundary);
+            let is_pinned = match boundary {
+                BoundaryMode::KeepCorners => is_boundary && incident.len() == 2,
+                BoundaryMode::KeepAll => is_boundary,
+                BoundaryMode::Smooth => false,
+            };
+            if is_pinned {
+                return p;
+            }
+            let other_end = |e: usize| {
+                let edge = &edges[e];
+                mesh.vertex_position(if edge.src == v { edge.dst } else { edge.src })
+            };
+
+            let n = incident.len() as f32;
+            let q = faces.iter().fold(Vec3::ZERO, |sum, f| {
+                sum + face_points[face_idx[f] - num_vertices]
+            }) / faces.len() as f32;
+            let r = incident
+                .iter()
+                .fold(Vec3::ZERO, |sum, e| sum + (p + other_end(*e)) * 0.5)
+                / n;
+            let smooth = (q + r * 2.0 + p * (n - 3.0)) / n;
+
+            let creased = incident
+                .iter()
+                .cloned()
+                .filter(|e| edges[*e].crease > 0.0)
+                .collect::<SVec<_>>();
+            let (sharp, weight) = match creased.len() {
+                0 | 1 => return smooth,
+                // A vertex on a crease line moves along the crease
+                2 => (
+                    (p * 6.0 + other_end(creased[0]) + other_end(creased[1])) / 8.0,
+                    (edges[creased[0]].crease + edges[creased[1]].crease) * 0.5,
+                ),
+                // A corner stays in place
+                _ => (
+                    p,
+                    creased.iter().map(|e| edges[*e].crease).sum::<f32>() / creased.len() as f32,
+                ),
+            };
+            smooth.lerp(sharp, weight)
+        })
+        .collect_vec();
+    progress::report(progress, 0.5)?;
+
+    let positions = vertex_points
+        .into_iter()
+        .chain(face_points.iter().cloned())
+        .chain(edge_points)
+        .collect_vec();
+
+    // Each face is split into quads, one for each of its corners.
+    let mut polygons = vec![];
+    for (face, _) in mesh.iter_faces() {
+        let halfedges = mesh.face_edges(face);
+        for (&h_prev, &h) in halfedges.iter().circular_tuple_windows() {
+            let v = mesh.at_halfedge(h).vertex().try_end()?;
+            polygons.push([
+                vertex_idx[&v],
+                edge_point_idx(edge_idx[&h]),
+                face_idx[&face],
+                edge_point_idx(edge_idx[&h_prev]),
+            ]);
+        }
+    }
+    progress::report(progress, 0.6)?;
+    let mut result = HalfEdgeMesh::build_from_polygons(&positions, &polygons)?;
+    progress::report(progress, 0.9)?;
+
+    // Vertices are allocated in the order they first appear in the polygons.
+    let idx_to_vertex: HashMap<usize, VertexId> = polygons
+        .iter()
+        .flatten()
+        .cloned()
+        .unique()
+        .zip(result.iter_vertices().map(|(v, _)| v))
+        .collect();
+    for (i, edge) in edges.iter().enumerate() {
+        // NOTE: Boundary edges are sharp anyway, only explicit creases are kept
+        let crease = mesh.edge_crease(edge.halfedge);
+        if crease <= 0.0 {
+            continue;
+        }
+        for v in [edge.src, edge.dst] {
+            let a = idx_to_vertex[&vertex_idx[&v]];
+            let b = idx_to_vertex[&edge_point_idx(i)];
+            let h = result.at_vertex(a).halfedge_to(b).try_end()?;
+            result.set_edge_crease(h, crease);
+        }
+    }
+
+    Ok(result)
+}
+
+/// Applies Loop subdivision `iterations` times, returning the subdivided mesh.
+/// Each iteration splits every triangle into four and smooths the result.
+/// Only works on meshes made of triangles.
+///
+/// Boundary edges use the crease rules, and the vertices on the boundary are
+/// moved according to `boundary`, like in [`catmull_clark_subdivide`]. Edge
+/// creases are not supported.
+///
+/// The mesh is rebuilt on every iteration, so all ids change, and attributes
+/// are not kept.
+///
+/// Fails before doing any work if the result would exceed
+/// [`limits::max_elements`].
+pub fn loop_subdivide(
+    mesh: &HalfEdgeMesh,
+    iterations: u32,
+    boundary: BoundaryMode,
+) -> Result<HalfEdgeMesh> {
+    loop_subdivide_with_progress(mesh, iterations, boundary, &progress::no_progress)
+}
+
+/// Same as [`loop_subdivide`], but reports its progress and can be cancelled.
+/// See [`Progress`].
+pub fn loop_subdivide_with_progress(
+    mesh: &HalfEdgeMesh,
+    iterations: u32,
+    boundary: BoundaryMode,
+    progress: Progress,
+) -> Result<HalfEdgeMesh> {
+    if mesh.iter_faces().any(|(f, _)| mesh.num_face_edges(f) != 3) {
+        bail!("loop_subdivide: The mesh must only have triangles")
+    }
+
+    let mut counts = limits::ElementCounts::of_mesh(mesh);
+    for _ in 0..iterations {
+        counts = limits::ElementCounts {
+            vertices: counts.vertices.saturating_add(counts.edges),
+            edges: counts
+                .edges
+                .saturating_mul(2)
+                .saturating_add(counts.faces.saturating_mul(3)),
+            faces: counts.faces.saturating_mul(4),
+        };
+    }
+    counts.check("loop_subdivide")?;
+    iterate(mesh, iterations, progress, |mesh, progress| {
+        loop_subdivide_once(mesh, boundary, progress)
+    })
+}
+
+fn loop_subdivide_once(
+    mesh: &HalfEdgeMesh,
+    boundary: BoundaryMode,
+    progress: Progress,
+) -> Result<HalfEdgeMesh> {
+    // The new vertices are, in order: One per original vertex and one per
+    // edge.
+    let vertex_idx: HashMap<VertexId, usize> = mesh
+        .iter_vertices()
+        .enumerate()
+        .map(|(i, (v, _))| (v, i))
+        .collect();
+    let num_vertices = vertex_idx.len();
+
+    let mut edges = vec![];
+    let mut edge_idx = HashMap::<HalfEdgeId, usize>::new();
+    for h in mesh.iter_edges() {
+        let (src, dst) = mesh.at_halfedge(h).src_dst_pair()?;
+        let twin = mesh[h].twin;
+        let is_boundary = mesh[h].face.is_none() || twin.and_then(|t| mesh[t].face).is_none();
+        edge_idx.insert(h, edges.len());
+        if let Some(twin) = twin {
+            edge_idx.insert(twin, edges.len());
+        }
+        edges.push(EdgeInfo {
+            halfedge: h,
+            src,
+            dst,
+            crease: if is_boundary { 1.0 } else { 0.0 },
+            is_boundary,
+        });
+    }
+    progress::report(progress, 0.1)?;
+
+    // Edge points: 3/8 of each endpoint and 1/8 of each of the opposite
+    // vertices of the two triangles, or the midpoint on the boundary.
+    let edge_points = edges
+        .iter()
+        .map(|edge| -> Result<Vec3> {
+            let (a, b) = (
+                mesh.vertex_position(edge.src),
+                mesh.vertex_position(edge.dst),
+            );
+            if edge.is_boundary {
+                return Ok((a + b) * 0.5);
+            }
+            let twin = mesh.at_halfedge(edge.halfedge).twin().try_end()?;
+            let c = mesh
+                .at_halfedge(edge.halfedge)
+                .previous()
+                .vertex()
+                .try_end()?;
+            let d = mesh.at_halfedge(twin).previous().vertex().try_end()?;
+            Ok((a + b) * 3.0 / 8.0 + (mesh.vertex_position(c) + mesh.vertex_position(d)) / 8.0)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    progress::report(progress, 0.3)?;
+
+    // Vertex points
+    let mut vertex_edges = HashMap::<VertexId, SVec<usize>>::new();
+    for (i, edge) in edges.iter().enumerate() {
+        vertex_edges.entry(edge.src).or_default().push(i);
+        vertex_edges.entry(edge.dst).or_default().push(i);
+    }
+    let vertex_points = mesh
+        .iter_vertices()
+        .map(|(v, vertex)| {
+            let p = vertex.position;
+            let incident = vertex_edges.get(&v).cloned().unwrap_or_default();
+            let other_end = |e: usize| {
+                let edge = &edges[e];
+                mesh.vertex_position(if edge.src == v { edge.dst } else { edge.src })
+            };
+            let boundary_edges = incident
+                .iter()
+                .cloned()
+                .filter(|e| edges[*e].is_boundary)
+                .collect::<SVec<_>>();
+            if incident.is_empty() {
+                return p;
+            }
+            if !boundary_edges.is_empty() {
+                let is_pinned = match boundary {
+                    BoundaryMode::KeepCorners => incident.len() == 2,
+                    BoundaryMode::KeepAll => true,
+                    BoundaryMode::Smooth => false,
+                };
+                // Non-manifold vertices, with more than two boundary edges,
+                // stay in place as well.
+                if is_pinned || boundary_edges.len() != 2 {
+                    return p;
+                }
+                return p * 0.75
+                    + (other_end(boundary_edges[0]) + other_end(boundary_edges[1])) / 8.0;
+            }
+
+            let n = incident.len() as f32;
+            let beta = (5.0 / 8.0
+                - (3.0 / 8.0 + (2.0 * std::f32::consts::PI / n).cos() / 4.0).powi(2))
+                / n;
+            let neighbors = incident
+                .iter()
+                .fold(Vec3::ZERO, |sum, e| sum + other_end(*e));
+            p * (1.0 - n * beta) + neighbors * beta
+        })
+        .collect_vec();
+    progress::report(progress, 0.5)?;
+
+    let positions = vertex_points.into_iter().chain(edge_points).collect_vec();
+    let edge_point_idx = |h: HalfEdgeId| num_vertices + edge_idx[&h];
+
+    // Each triangle is split into one triangle per corner, and a middle one.
+    let mut polygons = vec![];
+    for (face, _) in mesh.iter_faces() {
+        let halfedges = mesh.face_edges(face);
+        let mut middle = [0; 3];
+        for (i, (&h_prev, &h)) in halfedges.iter().circular_tuple_windows().enumerate() {
+            let v = mesh.at_halfedge(h).vertex().try_end()?;
+            polygons.push([vertex_idx[&v], edge_point_idx(h), edge_point_idx(h_prev)]);
+            middle[i] = edge_point_idx(h);
+        }
+        polygons.push(middle);
+    }
+    progress::report(progress, 0.6)?;
+    HalfEdgeMesh::build_from_polygons(&positions, &polygons)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_subdivide_cube() {
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let subdivided = catmull_clark_subdivide(&cube, 2, BoundaryMode::KeepCorners).unwrap();
+        assert_eq!(subdivided.iter_faces().count(), 6 * 16);
+        assert!(subdivided.stats().is_closed);
+        // Smoothing shrinks the cube towards a sphere
+        for (_, vertex) in subdivided.iter_vertices() {
+            assert!(vertex.position.abs().max_element() < 0.5);
+        }
+    }
+
+    #[test]
+    pub fn test_subdivide_creased_cube() {
+        let mut cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let edges = cube.iter_edges().collect_vec();
+        for h in edges {
+            cube.set_edge_crease(h, 1.0);
+        }
+        // With all edges fully creased, every vertex stays on the cube
+        let subdivided = catmull_clark_subdivide(&cube, 2, BoundaryMode::KeepCorners).unwrap();
+        for (_, vertex) in subdivided.iter_vertices() {
+            assert!((vertex.position.abs().max_element() - 0.5).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    pub fn test_subdivide_cancel() {
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let reported = std::cell::RefCell::new(vec![]);
+        let cancel_early = |fraction: f32| {
+            reported.borrow_mut().push(fraction);
+            if fraction > 0.2 {
+                std::ops::ControlFlow::Break(())
+            } else {
+                std::ops::ControlFlow::Continue(())
+            }
+        };
+
+        let result = catmull_clark_subdivide_with_progress(
+            &cube,
+            3,
+            BoundaryMode::KeepCorners,
+            &cancel_early,
+        );
+        assert!(result.unwrap_err().downcast_ref::<Cancelled>().is_some());
+        let reported = reported.into_inner();
+        assert!(reported.windows(2).all(|w| w[0] <= w[1]));
+        assert!(*reported.last().unwrap() < 1.0);
+    }
+
+    #[test]
+    pub fn test_subdivide_progress_within_iteration() {
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let reported = std::cell::RefCell::new(vec![]);
+        let record = |fraction: f32| {
+            reported.borrow_mut().push(fraction);
+            std::ops::ControlFlow::Continue(())
+        };
+
+        catmull_clark_subdivide_with_progress(&cube, 1, BoundaryMode::KeepCorners, &record)
+            .unwrap();
+        let reported = reported.into_inner();
+        assert!(reported.iter().any(|f| *f > 0.0 && *f < 1.0));
+        assert!(reported.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(*reported.last().unwrap(), 1.0);
+    }
+
+    #[test]
+    pub fn test_subdivide_limit() {
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let subdivided = catmull_clark_subdivide(&cube, 2, BoundaryMode::KeepCorners).unwrap();
+        let projected = projected_counts(&cube, 2);
+        assert_eq!(
+            projected.vertices,
+            subdivided.iter_vertices().count() as u64
+        );
+        assert_eq!(projected.edges, subdivided.iter_edges().count() as u64);
+        assert_eq!(projected.faces, subdivided.iter_faces().count() as u64);
+
+        // Too many iterations fail right away, instead of running out of memory
+        assert!(catmull_clark_subdivide(&cube, 20, BoundaryMode::KeepCorners).is_err());
+    }
+
+    #[test]
+    pub fn test_subdivide_boundary_modes() {
+        // A flat 2x2 grid of quads, spanning [-1, 1] on X and Z
+        let positions = (0..=2)
+            .flat_map(|z| (0..=2).map(move |x| Vec3::new(x as f32 - 1.0, 0.0, z as f32 - 1.0)))
+            .collect_vec();
+        let idx = |x: usize, z: usize| z * 3 + x;
+        let polygons = (0..2)
+            .flat_map(|z| {
+                (0..2).map(move |x| [idx(x, z), idx(x, z + 1), idx(x + 1, z + 1), idx(x + 1, z)])
+            })
+            .collect_vec();
+        let plane = HalfEdgeMesh::build_from_polygons(&positions, &polygons).unwrap();
+        let corners =
+            [(-1.0, -1.0), (-1.0, 1.0), (1.0, -1.0), (1.0, 1.0)].map(|(x, z)| Vec3::new(x, 0.0, z));
+        let has_corners = |mesh: &HalfEdgeMesh| {
+            corners.iter().all(|c| {
+                mesh.iter_vertices()
+                    .any(|(_, v)| v.position.distance(*c) < 1e-5)
+            })
+        };
+        let on_square = |p: Vec3| (p.x.abs().max(p.z.abs()) - 1.0).abs() < 1e-5;
+
+        // The outer square keeps its shape
+        for mode in [BoundaryMode::KeepCorners, BoundaryMode::KeepAll] {
+            let subdivided = catmull_clark_subdivide(&plane, 2, mode).unwrap();
+            assert!(has_corners(&subdivided));
+            let boundary = subdivided.boundary_loops().unwrap();
+            for h in &boundary[0] {
+                let v = subdivided.at_halfedge(*h).vertex().try_end().unwrap();
+                assert!(on_square(subdivided.vertex_position(v)));
+            }
+        }
+
+        // The corners get rounded
+        let subdivided = catmull_clark_subdivide(&plane, 2, BoundaryMode::Smooth).unwrap();
+        assert!(!has_corners(&subdivided));
+    }
+
+    #[test]
+    pub fn test_loop_subdivide() {
+        let positions = [
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(1.0, -1.0, -1.0),
+            Vec3::new(-1.0, 1.0, -1.0),
+            Vec3::new(-1.0, -1.0, 1.0),
+        ];
+        let polygons = [[0usize, 1, 2], [0, 3, 1], [0, 2, 3], [1, 3, 2]];
+        let tetrahedron = HalfEdgeMesh::build_from_polygons(&positions, &polygons).unwrap();
+
+        let subdivided = loop_subdivide(&tetrahedron, 2, BoundaryMode::KeepCorners).unwrap();
+        assert_eq!(subdivided.iter_faces().count(), 4 * 16);
+        assert!(subdivided.stats().is_closed);
+        // Smoothing shrinks the tetrahedron
+        for (_, vertex) in subdivided.iter_vertices() {
+            assert!(vertex.position.length() < 3f32.sqrt());
+        }
+
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        assert!(loop_subdivide(&cube, 1, BoundaryMode::KeepCorners).is_err());
+    }
+
+    #[test]
+    pub fn test_linear_subdivide() {
+        let cube = halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        let subdivided = linear_subdivide(&cube, 2).unwrap();
+        assert_eq!(subdivided.iter_faces().count(), 6 * 16);
+        assert!(subdivided.stats().is_closed);
+        // All vertices stay on the surface of the cube, and the corners are
+        // kept
+        for (_, vertex) in subdivided.iter_vertices() {
+            assert!((vertex.position.abs().max_element() - 0.5).abs() < 1e-5);
+        }
+        for (_, vertex) in cube.iter_vertices() {
+            assert!(subdivided
+                .iter_vertices()
+                .any(|(_, v)| v.position.distance(vertex.position) < 1e-5));
+        }
+    }
+}
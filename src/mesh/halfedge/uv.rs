@@ -0,0 +1,425 @@
+use std::f32::consts::PI;
+
+use crate::prelude::*;
+
+/// Assigns UVs to every face corner, computing them from the position of the
+/// corner's vertex using the `project` function. The projection returns the
+/// `u` coordinate, an angle, which may be undefined for points on the
+/// projection axis, and the `v` coordinate.
+///
+/// Since `u` is an angle, it wraps around from 1.0 to 0.0 at the seam. For a
+/// face that straddles the seam, some corners would get a `u` close to 1.0 and
+/// others close to 0.0, so the texture would be squeezed backwards over the
+/// whole face. We detect this and shift the low corners by one, so the face
+/// gets a continuous range of UVs. Since UVs are stored per halfedge, this
+/// only affects the corners of that face, and the vertices keep their
+/// original UVs everywhere else.
+fn angular_uv_project(mesh: &mut HalfEdgeMesh, project: impl Fn(Vec3) -> (Option<f32>, f32)) {
+    let faces = mesh.iter_faces().map(|(f, _)| f).collect_vec();
+    for face in faces {
+        let halfedges = mesh.face_edges(face);
+        let mut corners = halfedges
+            .iter()
+            .map(|&h| project(mesh.vertex_position(mesh.at_halfedge(h).vertex().end())))
+            .collect::<SVec<_>>();
+
+        let (min_u, max_u) = corners
+            .iter()
+            .filter_map(|(u, _)| *u)
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), u| {
+                (min.min(u), max.max(u))
+            });
+        if max_u - min_u > 0.5 {
+            for u in corners.iter_mut().filter_map(|(u, _)| u.as_mut()) {
+                if *u < 0.5 {
+                    *u += 1.0;
+                }
+            }
+        }
+
+        // Vertices on the projection axis (e.g. the poles of a sphere) have no
+        // well defined angle. Give them the average `u` of the other corners.
+        let known_u = corners.iter().filter_map(|(u, _)| *u).collect::<SVec<_>>();
+        let avg_u = if known_u.is_empty() {
+            0.0
+        } else {
+            known_u.iter().sum::<f32>() / known_u.len() as f32
+        };
+
+        for (&h, &(u, v)) in halfedges.iter().zip(corners.iter()) {
+            mesh.set_halfedge_uv(h, Vec2::new(u.unwrap_or(avg_u), v));
+        }
+    }
+}
+
+/// Returns the angle of the (a, b) vector, remapped to the [0, 1] range.
+/// Returns None when the angle is not defined.
+fn angle_to_u(a: f32, b: f32) -> Option<f32> {
+    if a.abs() < 1e-6 && b.abs() < 1e-6 {
+        None
+    } else {
+        Some(0.5 + b.atan2(a) / (2.0 * PI))
+    }
+}
+
+/// Assigns UVs to all face corners using spherical coordinates around
+/// `center`: The `u` coordinate is the longitude, around the Y axis, and the
+/// `v` coordinate is the latitude, going from 0.0 at the bottom pole to 1.0
+/// at the top. Faces crossing the seam get their UVs fixed, see
+/// `angular_uv_project`.
+pub fn spherical_uv_project(mesh: &mut HalfEdgeMesh, center: Vec3) {
+    angular_uv_project(mesh, |pos| {
+        let p = pos - center;
+        let len = p.length();
+        if len < 1e-6 {
+            return (None, 0.5);
+        }
+        let v = 0.5 + (p.y / len).clamp(-1.0, 1.0).asin() / PI;
+        (angle_to_u(p.x, p.z), v)
+    });
+}
+
+/// Assigns UVs to all face corners using cylindrical coordinates around the
+/// given `axis`, passing through the center of the mesh's bounding box. The
+/// `u` coordinate is the angle around the axis, and the `v` coordinate is the
+/// height along it, remapped so the mesh's extent spans the [0, 1] range.
+/// Faces crossing the seam get their UVs fixed, see `angular_uv_project`.
+pub fn cylindrical_uv_project(mesh: &mut HalfEdgeMesh, axis: Axis) {
+    let (min, max) = mesh.iter_vertices().fold(
+        (Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)),
+        |(min, max), (_, v)| (min.min(v.position), max.max(v.position)),
+    );
+    let center = (min + max) * 0.5;
+    let (min_h, max_h) = (axis.component(min), axis.component(max));
+    let height = (max_h - min_h).max(1e-6);
+
+    angular_uv_project(mesh, |pos| {
+        let p = pos - center;
+        // The two coordinates perpendicular to the axis, in a right-handed order
+        let (a_axis, b_axis) = axis.perpendicular();
+        let (a, b) = (a_axis.component(p), b_axis.component(p));
+        let v = (axis.component(pos) - min_h) / height;
+        (angle_to_u(a, b), v)
+    });
+}
+
+/// Returns the (u, v) directions of the plane perpendicular to `axis`, so a
+/// face facing `axis` shows the texture upright, without mirroring. `axis`
+/// must be normalized.
+fn planar_basis(axis: Vec3) -> (Vec3, Vec3) {
+    // Looking down the Y axis, the texture's up direction is -Z
+    let reference = if axis.y.abs() > 0.999 {
+        -Vec3::Z * axis.y.signum()
+    } else {
+        Vec3::Y
+    };
+    let u_dir = reference.cross(axis).normalize();
+    let v_dir = axis.cross(u_dir);
+    (u_dir, v_dir)
+}
+
+/// Assigns UVs to the corners of `face` by projecting their positions on the
+/// plane perpendicular to `axis`.
+fn planar_uv_project_face(mesh: &mut HalfEdgeMesh, face: FaceId, axis: Vec3, scale: f32) {
+    let (u_dir, v_dir) = planar_basis(axis);
+    for h in mesh.face_edges(face) {
+        let pos = mesh.vertex_position(mesh.at_halfedge(h).vertex().end());
+        mesh.set_halfedge_uv(h, Vec2::new(pos.dot(u_dir), pos.dot(v_dir)) * scale);
+    }
+}
+
+/// Assigns UVs to all face corners by projecting the positions of their
+/// vertices on the plane perpendicular to `axis`, as if the texture was
+/// shining through the mesh from that direction. A `scale` of 1.0 maps one
+/// unit of distance to the full texture.
+pub fn planar_uv_project(mesh: &mut HalfEdgeMesh, axis: Vec3, scale: f32) -> Result<()> {
+    let axis = axis
+        .try_normalize()
+        .ok_or_else(|| anyhow!("Projection axis can't be zero"))?;
+    let faces = mesh.iter_faces().map(|(f, _)| f).collect_vec();
+    for face in faces {
+        planar_uv_project_face(mesh, face, axis, scale);
+    }
+    Ok(())
+}
+
+/// Assigns UVs to all face corners with a planar projection per face, like
+/// [`planar_uv_project`], along the world axis closest to the face normal.
+/// This is the projection of a box wrapped around the mesh, so cube-like
+/// meshes get an undistorted texture on every side. Degenerate faces, without
+/// a normal, are projected along Y.
+pub fn box_uv_project(mesh: &mut HalfEdgeMesh, scale: f32) {
+    let faces = mesh.iter_faces().map(|(f, _)| f).collect_vec();
+    for face in faces {
+        let normal = MeshQuery::face_normal(&*mesh, face).unwrap_or(Vec3::Y);
+        let abs = normal.abs();
+        let axis = if abs.x >= abs.y && abs.x >= abs.z {
+            Vec3::X * normal.x.signum()
+        } else if abs.y >= abs.z {
+            Vec3::Y * normal.y.signum()
+        } else {
+            Vec3::Z * normal.z.signum()
+        };
+        planar_uv_project_face(mesh, face, axis, scale);
+    }
+}
+
+/// Smooths the UVs of the mesh, moving each UV towards the average of its
+/// neighbors in UV space, `iterations` times. This evens out the stretching
+/// introduced by projections on curved regions. Every face corner must have
+/// a UV.
+///
+/// Corners are connected in UV space when they belong to the same vertex and
+/// have the same UV, so each side of a seam is relaxed independently. When
+/// `pin_boundary` is set, UVs on the boundary of the UV islands, either seams
+/// or the boundary of the mesh, stay in place. Otherwise, the islands will
+/// shrink as they're relaxed.
+pub fn relax_uvs(mesh: &mut HalfEdgeMesh, iterations: u32, pin_boundary: bool) -> Result<()> {
+    // Group the face corners into UV vertices: Corners of the same vertex
+    // with the same UV.
+    let mut uv_vertices = Vec::<Vec2>::new();
+    let mut corner_to_uv_vertex = HashMap::<HalfEdgeId, usize>::new();
+    let mut by_vertex = HashMap::<VertexId, SVec<usize>>::new();
+    for (h, halfedge) in mesh.iter_halfedges() {
+        if halfedge.face.is_none() {
+            continue;
+        }
+        let uv = mesh
+            .halfedge_uv(h)
+            .ok_or_else(|| anyhow!("relax_uvs: The mesh has face corners without UVs"))?;
+        let v = mesh.at_halfedge(h).vertex().try_end()?;
+        let candidates = by_vertex.entry(v).or_default();
+        let idx = match candidates
+            .iter()
+            .find(|&&i| uv_vertices[i].distance(uv) < 1e-6)
+        {
+            Some(&i) => i,
+            None => {
+                uv_vertices.push(uv);
+                candidates.push(uv_vertices.len() - 1);
+                uv_vertices.len() - 1
+            }
+        };
+        corner_to_uv_vertex.insert(h, idx);
+    }
+
+    // Build the UV edges from consecutive corners of each face. An edge used
+    // by a single face lies on the boundary of its UV island.
+    let mut edge_count = HashMap::<(usize, usize), u32>::new();
+    for (face, _) in mesh.iter_faces() {
+        let corners = mesh.face_edges(face);
+        for (h1, h2) in corners.iter().circular_tuple_windows() {
+            let (a, b) = (corner_to_uv_vertex[h1], corner_to_uv_vertex[h2]);
+            *edge_count.entry((a.min(b), a.max(b))).or_default() += 1;
+        }
+    }
+    let mut neighbors = vec![SVec::<usize>::new(); uv_vertices.len()];
+    let mut pinned = vec![false; uv_vertices.len()];
+    for (&(a, b), &count) in edge_count.iter() {
+        neighbors[a].push(b);
+        neighbors[b].push(a);
+        if count == 1 && pin_boundary {
+            pinned[a] = true;
+            pinned[b] = true;
+        }
+    }
+
+    for _ in 0..iterations {
+        uv_vertices = uv_vertices
+            .iter()
+            .enumerate()
+            .map(|(i, &uv)| {
+                if pinned[i] || neighbors[i].is_empty() {
+                    return uv;
+                }
+                let sum = neighbors[i]
+                    .iter()
+                    .fold(Vec2::ZERO, |sum, &j| sum + uv_vertices[j]);
+                sum / neighbors[i].len() as f32
+            })
+            .collect();
+    }
+
+    for (h, idx) in corner_to_uv_vertex {
+        mesh.set_halfedge_uv(h, uv_vertices[idx]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the variance of the UV areas of the triangles in the mesh,
+    /// triangulating each face as a fan.
+    fn uv_area_variance(mesh: &HalfEdgeMesh) -> f32 {
+        let areas = mesh
+            .iter_faces()
+            .flat_map(|(f, _)| {
+                let uvs = mesh
+                    .face_edges(f)
+                    .iter()
+                    .map(|h| mesh.halfedge_uv(*h).unwrap())
+                    .collect_vec();
+                (1..uvs.len() - 1)
+                    .map(|i| {
+                        let (e1, e2) = (uvs[i] - uvs[0], uvs[i + 1] - uvs[0]);
+                        0.5 * (e1.x * e2.y - e1.y * e2.x).abs()
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        let mean = areas.iter().sum::<f32>() / areas.len() as f32;
+        areas.iter().map(|a| (a - mean).powi(2)).sum::<f32>() / areas.len() as f32
+    }
+
+    /// Returns the UVs of the corners of `face`.
+    fn face_uvs(mesh: &HalfEdgeMesh, face: FaceId) -> SVec<Vec2> {
+        mesh.face_edges(face)
+            .iter()
+            .map(|h| mesh.halfedge_uv(*h).unwrap())
+            .collect()
+    }
+
+    /// Returns the difference between the largest and smallest `u` of `uvs`.
+    fn u_span(uvs: &[Vec2]) -> f32 {
+        let (min, max) = uvs
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), uv| {
+                (min.min(uv.x), max.max(uv.x))
+            });
+        max - min
+    }
+
+    #[test]
+    pub fn test_spherical_uv_project() {
+        // With an odd number of segments, no vertex lies exactly on the seam
+        let segments = 7;
+        let mut mesh =
+            crate::mesh::halfedge::primitives::UVSphere::build(Vec3::ZERO, 1.0, segments, 6);
+        spherical_uv_project(&mut mesh, Vec3::ZERO);
+
+        let mut crosses_seam = false;
+        for (face, _) in mesh.iter_faces() {
+            let uvs = face_uvs(&mesh, face);
+            for uv in uvs.iter() {
+                // Corners of faces on the seam are shifted past 1.0
+                assert!(uv.x >= -1e-5 && uv.x <= 1.5, "u out of range: {}", uv.x);
+                assert!(
+                    uv.y >= -1e-5 && uv.y <= 1.0 + 1e-5,
+                    "v out of range: {}",
+                    uv.y
+                );
+            }
+            crosses_seam |= uvs.iter().any(|uv| uv.x > 1.0 + 1e-5);
+            // No face jumps back across the whole texture at the seam
+            let span = u_span(&uvs);
+            assert!(
+                span <= 1.0 / segments as f32 + 1e-4,
+                "u span too large: {}",
+                span
+            );
+        }
+        assert!(crosses_seam);
+    }
+
+    #[test]
+    pub fn test_cylindrical_uv_project() {
+        let mut mesh = crate::mesh::halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        cylindrical_uv_project(&mut mesh, Axis::Y);
+
+        let mut crosses_seam = false;
+        for (face, _) in mesh.iter_faces() {
+            let uvs = face_uvs(&mesh, face);
+            for uv in uvs.iter() {
+                assert!(uv.x >= -1e-5 && uv.x <= 1.5, "u out of range: {}", uv.x);
+                assert!(
+                    uv.y >= -1e-5 && uv.y <= 1.0 + 1e-5,
+                    "v out of range: {}",
+                    uv.y
+                );
+            }
+            // The caps go all around the axis, only the sides are checked
+            if mesh.face_normal(face).y.abs() > 0.5 {
+                continue;
+            }
+            crosses_seam |= uvs.iter().any(|uv| uv.x > 1.0 + 1e-5);
+            // Each side covers a quarter of the way around
+            assert!((u_span(&uvs) - 0.25).abs() < 1e-4, "{:?}", uvs);
+            assert!(uvs.iter().any(|uv| uv.y.abs() < 1e-5));
+            assert!(uvs.iter().any(|uv| (uv.y - 1.0).abs() < 1e-5));
+        }
+        assert!(crosses_seam);
+    }
+
+    #[test]
+    pub fn test_relax_uvs() {
+        // A flat 4x4 grid of unit quads, from the origin to (n, 0, n)
+        let n = 4;
+        let mut mesh = crate::mesh::halfedge::primitives::Grid::build(
+            Vec3::new(n as f32, 0.0, n as f32) * 0.5,
+            Vec2::splat(n as f32),
+            n,
+            n,
+        );
+
+        // Planar UVs, with the interior vertices pushed around to distort them
+        let corners = mesh.iter_halfedges().map(|(h, _)| h).collect_vec();
+        for h in corners {
+            let pos = mesh.vertex_position(mesh.at_halfedge(h).vertex().end());
+            let interior = pos.x > 0.0 && pos.x < n as f32 && pos.z > 0.0 && pos.z < n as f32;
+            let offset = if interior {
+                Vec2::new((pos.x * 1.7).sin(), (pos.z * 2.3).cos()) * 0.4
+            } else {
+                Vec2::ZERO
+            };
+            mesh.set_halfedge_uv(h, Vec2::new(pos.x, pos.z) + offset);
+        }
+
+        let before = uv_area_variance(&mesh);
+        relax_uvs(&mut mesh, 20, true).unwrap();
+        let after = uv_area_variance(&mesh);
+        assert!(after < before * 0.1, "{} should be < {}", after, before);
+    }
+
+    #[test]
+    pub fn test_box_uv_project() {
+        let mut mesh = crate::mesh::halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        box_uv_project(&mut mesh, 2.0);
+
+        for (face, _) in mesh.iter_faces() {
+            let uvs = mesh
+                .face_edges(face)
+                .iter()
+                .map(|h| mesh.halfedge_uv(*h).unwrap())
+                .collect_vec();
+            let (min, max) = uvs.iter().fold(
+                (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+                |(min, max), uv| (min.min(*uv), max.max(*uv)),
+            );
+            // Each side of the unit cube covers the [-1, 1] range at scale 2
+            assert!((min - Vec2::splat(-1.0)).abs().max_element() < 1e-5);
+            assert!((max - Vec2::splat(1.0)).abs().max_element() < 1e-5);
+
+            // UVs keep the winding of the face, so textures aren't mirrored
+            let (e1, e2) = (uvs[1] - uvs[0], uvs[2] - uvs[0]);
+            assert!(e1.x * e2.y - e1.y * e2.x > 0.0);
+        }
+    }
+
+    #[test]
+    pub fn test_planar_uv_project() {
+        let mut mesh = crate::mesh::halfedge::primitives::Box::build(Vec3::ZERO, Vec3::ONE);
+        assert!(planar_uv_project(&mut mesh, Vec3::ZERO, 1.0).is_err());
+
+        planar_uv_project(&mut mesh, Vec3::new(0.0, 0.0, 3.0), 1.0).unwrap();
+        for (h, _) in mesh.iter_halfedges() {
+            if mesh[h].face.is_none() {
+                continue;
+            }
+            let pos = mesh.vertex_position(mesh.at_halfedge(h).vertex().end());
+            let uv = mesh.halfedge_uv(h).unwrap();
+            assert!(uv.distance(Vec2::new(pos.x, pos.y)) < 1e-5);
+        }
+    }
+}
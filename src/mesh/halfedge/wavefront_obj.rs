@@ -11,6 +11,9 @@ use wavefront_rs::obj::{
 use crate::prelude::*;
 
 impl HalfEdgeMesh {
+    /// Saves the mesh as a Wavefront OBJ file. Vertices are numbered in the
+    /// order they're stored, skipping removed ones, and each face is written
+    /// with all of its vertices in winding order.
     pub fn to_wavefront_obj(&self, path: PathBuf) -> Result<()> {
         let mut writer = BufWriter::new(File::create(path)?);
 
@@ -56,6 +59,10 @@ impl HalfEdgeMesh {
         Ok(())
     }
 
+    /// Loads a mesh from a Wavefront OBJ file. Only vertex positions and faces
+    /// are read, and any other data, like normals or texture coordinates, is
+    /// ignored. Faces can have any number of vertices. Negative indices count
+    /// back from the last vertex read so far, as the format allows.
     pub fn from_wavefront_obj(path: PathBuf) -> Result<HalfEdgeMesh> {
         let mut reader = BufReader::new(File::open(path)?);
         let mut positions = vec![];
@@ -66,12 +73,35 @@ impl HalfEdgeMesh {
             }
             Entity::Face { vertices } => {
                 // NOTE: OBJ Wavefront indices start at 1
-                let polygon: SVec<usize> =
-                    vertices.iter().map(|v| (v.vertex - 1) as usize).collect();
+                let polygon: SVec<i64> = vertices
+                    .iter()
+                    .map(|v| {
+                        if v.vertex < 0 {
+                            positions.len() as i64 + v.vertex
+                        } else {
+                            v.vertex - 1
+                        }
+                    })
+                    .collect();
                 polygons.push(polygon);
             }
             _ => {}
         })?;
+
+        let polygons = polygons
+            .iter()
+            .map(|polygon| {
+                polygon
+                    .iter()
+                    .map(|&idx| {
+                        if idx < 0 || idx as usize >= positions.len() {
+                            bail!("Invalid vertex index in OBJ face: {}", idx + 1)
+                        }
+                        Ok(idx as usize)
+                    })
+                    .collect::<Result<SVec<usize>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
         Ok(halfedge::HalfEdgeMesh::build_from_polygons(
             &positions, &polygons,
         )?)
@@ -89,4 +119,26 @@ mod tests {
             .to_wavefront_obj("/tmp/wat.obj".into())
             .unwrap();
     }
+
+    #[test]
+    pub fn test_load_obj_negative_indices() {
+        let path = std::env::temp_dir().join("blackjack_test_negative_indices.obj");
+        std::fs::write(
+            &path,
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+             vt 0 0\nvn 0 0 1\n\
+             f -4/1/1 -3/1/1 -2/1/1 -1/1/1\n\
+             v 2 0 0\n\
+             f 2 -1 3\n",
+        )
+        .unwrap();
+        let mesh = HalfEdgeMesh::from_wavefront_obj(path.clone()).unwrap();
+        assert_eq!(mesh.iter_vertices().count(), 5);
+        assert_eq!(mesh.iter_faces().count(), 2);
+        // The two faces share an edge
+        assert_eq!(mesh.iter_edges().count(), 6);
+
+        std::fs::write(&path, "v 0 0 0\nv 1 0 0\nf 1 2 3\n").unwrap();
+        assert!(HalfEdgeMesh::from_wavefront_obj(path).is_err());
+    }
 }
@@ -27,4 +27,4 @@ pub use crate::mesh::halfedge::*;
 pub use crate::mesh::halfedge;
 pub use crate::mesh::debug_viz;
 
-pub use crate::math::{Vec3Ord, ToOrd, ToVec};
\ No newline at end of file
+pub use crate::math::{Axis, Vec3Ord, ToOrd, ToVec};
\ No newline at end of file
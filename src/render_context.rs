@@ -8,20 +8,34 @@ use rend3::{
     Renderer,
 };
 use rend3_egui::EguiRenderRoutine;
-use rend3_routine::{PbrRenderRoutine, TonemappingRoutine};
+use rend3_routine::PbrRenderRoutine;
 use wgpu::{Features, Surface, TextureFormat};
 
-use crate::rendergraph::{self, wireframe_pass::WireframeRoutine};
+use crate::rendergraph::{
+    self,
+    face_orientation_pass::FaceOrientationRoutine,
+    normals_pass::NormalsRoutine,
+    points_pass::PointsRoutine,
+    tonemap_pass::{TonemapRoutine, Tonemapping},
+    wireframe_pass::WireframeRoutine,
+};
 
 pub struct RenderContext {
     pub renderer: Arc<Renderer>,
     pub pbr_routine: PbrRenderRoutine,
-    pub tonemapping_routine: TonemappingRoutine,
+    pub tonemapping_routine: TonemapRoutine,
+    /// The tonemapping operator and exposure used to display the viewport.
+    pub tonemapping: Tonemapping,
     pub wireframe_routine: WireframeRoutine,
+    pub normals_routine: NormalsRoutine,
+    pub face_orientation_routine: FaceOrientationRoutine,
+    pub points_routine: PointsRoutine,
     pub egui_routine: EguiRenderRoutine,
     pub grid_routine: GridRoutine,
     pub surface: Arc<Surface>,
     pub texture_format: TextureFormat,
+    /// The MSAA sample count of the viewport's render targets.
+    samples: SampleCount,
 
     pub objects: Vec<ResourceHandle<Object>>,
     lights: Vec<ResourceHandle<DirectionalLight>>,
@@ -49,11 +63,12 @@ impl RenderContext {
             rend3::types::PresentMode::Mailbox,
         );
 
-        let renderer = rend3::Renderer::new(
-            iad,
-            Some(window_size.width as f32 / window_size.height as f32),
-        )
-        .unwrap();
+        let viewport = rendergraph::viewport_resolution(glam::UVec2::new(
+            window_size.width,
+            window_size.height,
+        ));
+        let renderer =
+            rend3::Renderer::new(iad, Some(viewport.x as f32 / viewport.y as f32)).unwrap();
 
         let render_texture_options = rend3_routine::RenderTextureOptions {
             resolution: glam::UVec2::new(window_size.width, window_size.height),
@@ -61,11 +76,8 @@ impl RenderContext {
         };
         let mut pbr_routine =
             rend3_routine::PbrRenderRoutine::new(&renderer, render_texture_options);
-        let tonemapping_routine = rend3_routine::TonemappingRoutine::new(
-            &renderer,
-            render_texture_options.resolution,
-            format,
-        );
+        let tonemapping_routine =
+            TonemapRoutine::new(&renderer.device, render_texture_options.resolution, format);
 
         let egui_routine = EguiRenderRoutine::new(
             &renderer,
@@ -76,9 +88,17 @@ impl RenderContext {
             window.scale_factor() as f32,
         );
 
-        let grid_routine = GridRoutine::new(&renderer.device);
+        let grid_routine = GridRoutine::new(&renderer.device, render_texture_options.samples);
+
+        let wireframe_routine =
+            WireframeRoutine::new(&renderer.device, render_texture_options.samples);
+
+        let normals_routine = NormalsRoutine::new(&renderer.device, render_texture_options.samples);
 
-        let wireframe_routine = WireframeRoutine::new(&renderer.device, &pbr_routine);
+        let face_orientation_routine =
+            FaceOrientationRoutine::new(&renderer.device, render_texture_options.samples);
+
+        let points_routine = PointsRoutine::new(&renderer.device, render_texture_options.samples);
 
         pbr_routine.set_ambient_color(glam::Vec4::ONE * 0.25);
 
@@ -86,11 +106,16 @@ impl RenderContext {
             renderer,
             pbr_routine,
             tonemapping_routine,
+            tonemapping: Tonemapping::default(),
             egui_routine,
             wireframe_routine,
+            normals_routine,
+            face_orientation_routine,
+            points_routine,
             grid_routine,
             surface,
             texture_format: format,
+            samples: render_texture_options.samples,
             objects: vec![],
             lights: vec![],
         }
@@ -98,6 +123,22 @@ impl RenderContext {
 
     pub fn clear_objects(&mut self) {
         self.objects.clear();
+        self.wireframe_routine.clear_mesh();
+        self.normals_routine.clear_mesh();
+        self.face_orientation_routine.clear_mesh();
+        self.points_routine.clear_mesh();
+    }
+
+    /// Sets the mesh drawn by the overlay routines: Its edges by the
+    /// wireframe routine, its vertex normals by the normals routine, its
+    /// faces by the face orientation routine and its vertices by the points
+    /// routine.
+    pub fn set_overlay_mesh(&mut self, mesh: &HalfEdgeMesh) {
+        self.wireframe_routine.set_mesh(&self.renderer.device, mesh);
+        self.normals_routine.set_mesh(&self.renderer.device, mesh);
+        self.face_orientation_routine
+            .set_mesh(&self.renderer.device, mesh);
+        self.points_routine.set_mesh(&self.renderer.device, mesh);
     }
 
     pub fn add_mesh_as_object(&mut self, mesh: Mesh) {
@@ -161,9 +202,13 @@ impl RenderContext {
             &self.pbr_routine,
             None,
             &self.tonemapping_routine,
+            self.tonemapping,
             &self.wireframe_routine,
+            &self.normals_routine,
+            &self.face_orientation_routine,
+            &self.points_routine,
             &self.grid_routine,
-            rend3::types::SampleCount::One,
+            self.samples,
         );
 
         if let Some(platform) = egui_platform {
@@ -181,6 +226,34 @@ impl RenderContext {
         graph.execute(&self.renderer, frame, cmd_bufs, &ready);
     }
 
+    pub fn samples(&self) -> SampleCount {
+        self.samples
+    }
+
+    /// Changes the MSAA sample count of the viewport. Every routine drawing to
+    /// the viewport's render targets is rebuilt to match.
+    pub fn set_samples(&mut self, samples: SampleCount) {
+        if samples == self.samples {
+            return;
+        }
+        self.samples = samples;
+        let options = rend3_routine::RenderTextureOptions {
+            resolution: self.pbr_routine.render_texture_options.resolution,
+            samples,
+        };
+        self.pbr_routine.resize(&self.renderer, options);
+        self.grid_routine
+            .set_samples(&self.renderer.device, samples);
+        self.wireframe_routine
+            .set_samples(&self.renderer.device, samples);
+        self.normals_routine
+            .set_samples(&self.renderer.device, samples);
+        self.face_orientation_routine
+            .set_samples(&self.renderer.device, samples);
+        self.points_routine
+            .set_samples(&self.renderer.device, samples);
+    }
+
     pub fn on_resize(&mut self, width: u32, height: u32, scale_factor: f32) {
         rend3::configure_surface(
             &self.surface,
@@ -190,12 +263,13 @@ impl RenderContext {
             rend3::types::PresentMode::Mailbox,
         );
 
-        self.renderer.set_aspect_ratio(width as f32 / height as f32 * 2.0);
-
         let size = UVec2::new(width, height);
+        let viewport = rendergraph::viewport_resolution(size);
+        self.renderer
+            .set_aspect_ratio(viewport.x as f32 / viewport.y as f32);
         let options = rend3_routine::RenderTextureOptions {
             resolution: size,
-            samples: SampleCount::One,
+            samples: self.samples,
         };
 
         self.pbr_routine.resize(&self.renderer, options);
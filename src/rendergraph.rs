@@ -1,3 +1,4 @@
+use glam::UVec2;
 use rend3::{
     types::SampleCount, util::bind_merge::BindGroupBuilder, DataHandle, DepthHandle, ReadyData,
     RenderGraph, RenderPassDepthTarget, RenderPassTarget, RenderPassTargets,
@@ -5,18 +6,94 @@ use rend3::{
 };
 use rend3_routine::{
     material::TransparencyType, uniforms, CulledPerMaterial, PbrRenderRoutine, SkyboxRoutine,
-    TonemappingRoutine,
 };
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    BindGroup, BindGroupLayout, Buffer, Color, Device, PipelineLayoutDescriptor, RenderPipeline,
-    RenderPipelineDescriptor, TextureFormat, TextureUsages,
+    BindGroup, BindGroupLayout, Buffer, Color, CompareFunction, DepthBiasState, DepthStencilState,
+    Device, PipelineLayout, PipelineLayoutDescriptor, RenderPipeline, RenderPipelineDescriptor,
+    ShaderModule, StencilState, TextureFormat, TextureUsages,
 };
 
-use self::wireframe_pass::WireframeRoutine;
+use self::{
+    face_orientation_pass::FaceOrientationRoutine,
+    normals_pass::NormalsRoutine,
+    points_pass::PointsRoutine,
+    tonemap_pass::{TonemapRoutine, Tonemapping},
+    wireframe_pass::{WireframeMode, WireframeRoutine},
+};
 
+pub mod face_orientation_pass;
+pub mod normals_pass;
+pub mod points_pass;
+pub mod tonemap_pass;
 pub mod wireframe_pass;
 
+/// Returns the size of the 3d viewport for a window of the given size. The
+/// viewport occupies the top half of the window, with the graph editor below
+/// it, so the scene is rendered at full width and half the height.
+pub fn viewport_resolution(window_resolution: UVec2) -> UVec2 {
+    UVec2::new(window_resolution.x, (window_resolution.y / 2).max(1))
+}
+
+/// The depth state shared by the passes drawing to the scene's depth target.
+/// Depth is reversed, so closer fragments have greater values and pass the
+/// `GreaterEqual` test.
+pub fn depth_test(depth_write_enabled: bool) -> DepthStencilState {
+    DepthStencilState {
+        format: TextureFormat::Depth32Float,
+        depth_write_enabled,
+        depth_compare: CompareFunction::GreaterEqual,
+        stencil: StencilState::default(),
+        bias: DepthBiasState::default(),
+    }
+}
+
+/// Builds the pipeline(s) of a routine for the given sample count.
+pub type BuildPipeline<P> = fn(&Device, &ShaderModule, &PipelineLayout, SampleCount) -> P;
+
+/// The pipeline(s) of a routine drawing to the multisampled render targets of
+/// the viewport. A pipeline must match the sample count of the targets it
+/// draws to, so this keeps what's needed to rebuild it when that changes.
+pub struct MsaaPipeline<P = RenderPipeline> {
+    pipeline: P,
+    shader: ShaderModule,
+    layout: PipelineLayout,
+    /// The sample count of the render targets the pipeline draws to.
+    samples: SampleCount,
+    build: BuildPipeline<P>,
+}
+
+impl<P> MsaaPipeline<P> {
+    pub fn new(
+        device: &Device,
+        shader: ShaderModule,
+        layout: PipelineLayout,
+        samples: SampleCount,
+        build: BuildPipeline<P>,
+    ) -> Self {
+        Self {
+            pipeline: build(device, &shader, &layout, samples),
+            shader,
+            layout,
+            samples,
+            build,
+        }
+    }
+
+    pub fn get(&self) -> &P {
+        &self.pipeline
+    }
+
+    /// Rebuilds the pipeline when the sample count of the render targets
+    /// changes.
+    pub fn set_samples(&mut self, device: &Device, samples: SampleCount) {
+        if samples != self.samples {
+            self.pipeline = (self.build)(device, &self.shader, &self.layout, samples);
+            self.samples = samples;
+        }
+    }
+}
+
 struct PerTransparencyInfo {
     ty: TransparencyType,
     pre_cull: DataHandle<Buffer>,
@@ -64,8 +141,12 @@ pub fn add_default_rendergraph<'node>(
     _ready: &ReadyData,
     pbr: &'node PbrRenderRoutine,
     _skybox: Option<&'node SkyboxRoutine>,
-    tonemapping: &'node TonemappingRoutine,
-    _wireframe: &'node WireframeRoutine,
+    tonemapping: &'node TonemapRoutine,
+    tonemapping_config: Tonemapping,
+    wireframe: &'node WireframeRoutine,
+    normals: &'node NormalsRoutine,
+    face_orientation: &'node FaceOrientationRoutine,
+    points: &'node PointsRoutine,
     grid: &'node GridRoutine,
     samples: SampleCount,
 ) {
@@ -94,18 +175,41 @@ pub fn add_default_rendergraph<'node>(
     let grid_uniform_bg = graph.add_data::<BindGroup>();
     grid.create_bind_groups(graph, grid_uniform_bg);
 
+    let wireframe_mode = wireframe.settings.mode;
+    let wireframe_uniform_bg = graph.add_data::<BindGroup>();
+    if wireframe_mode != WireframeMode::Disabled {
+        wireframe.create_bind_groups(graph, wireframe_uniform_bg);
+    }
+
+    let face_orientation_enabled = face_orientation.enabled;
+    let face_orientation_uniform_bg = graph.add_data::<BindGroup>();
+    if face_orientation_enabled {
+        face_orientation.create_bind_groups(graph, face_orientation_uniform_bg);
+    }
+
+    let normals_enabled = normals.settings.enabled;
+    let normals_uniform_bg = graph.add_data::<BindGroup>();
+    if normals_enabled {
+        normals.create_bind_groups(graph, normals_uniform_bg);
+    }
+
     // Add primary culling
     for trans in &per_transparency {
         pbr.add_culling_to_graph(graph, trans.ty, trans.pre_cull, trans.cull);
     }
 
-    let mut resolution = pbr.render_texture_options.resolution;
-    resolution.y /= 2;
+    let resolution = viewport_resolution(pbr.render_texture_options.resolution);
+
+    let points_enabled = points.settings.enabled;
+    let points_uniform_bg = graph.add_data::<BindGroup>();
+    if points_enabled {
+        points.create_bind_groups(graph, points_uniform_bg, resolution);
+    }
 
     // Make the actual render targets we want to render to.
     let color = graph.add_render_target(RenderTargetDescriptor {
         label: Some("hdr color".into()),
-        dim: resolution,//pbr.render_texture_options.resolution,
+        dim: resolution,
         samples,
         format: TextureFormat::Rgba16Float,
         usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
@@ -113,7 +217,7 @@ pub fn add_default_rendergraph<'node>(
     let resolve = samples.needs_resolve().then(|| {
         graph.add_render_target(RenderTargetDescriptor {
             label: Some("hdr resolve".into()),
-            dim: resolution,//pbr.render_texture_options.resolution,
+            dim: resolution,
             samples: SampleCount::One,
             format: TextureFormat::Rgba16Float,
             usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING,
@@ -121,67 +225,76 @@ pub fn add_default_rendergraph<'node>(
     });
     let depth = graph.add_render_target(RenderTargetDescriptor {
         label: Some("hdr depth".into()),
-        dim: resolution,//pbr.render_texture_options.resolution,
+        dim: resolution,
         samples,
         format: TextureFormat::Depth32Float,
         usage: TextureUsages::RENDER_ATTACHMENT,
     });
 
-    // Add depth prepass
-    for trans in per_transparency_no_blend {
-        pbr.add_prepass_to_graph(
-            graph,
-            trans.ty,
-            color,
-            resolve,
-            depth,
-            forward_uniform_bg,
-            trans.cull,
-        );
-    }
-
-    // Add primary rendering
-    for trans in &per_transparency {
-        pbr.add_forward_to_graph(
-            graph,
-            trans.ty,
-            color,
-            resolve,
-            depth,
-            forward_uniform_bg,
-            trans.cull,
-            false,
-        );
+    // The shaded mesh is not drawn in wireframe mode. The face orientation
+    // routine draws the mesh in its place when enabled.
+    if wireframe_mode != WireframeMode::Wireframe {
+        if face_orientation_enabled {
+            face_orientation.add_to_graph(
+                graph,
+                color,
+                depth,
+                resolve,
+                face_orientation_uniform_bg,
+            );
+        } else {
+            // Add depth prepass
+            for trans in per_transparency_no_blend {
+                pbr.add_prepass_to_graph(
+                    graph,
+                    trans.ty,
+                    color,
+                    resolve,
+                    depth,
+                    forward_uniform_bg,
+                    trans.cull,
+                );
+            }
+
+            // Add primary rendering
+            for trans in &per_transparency {
+                pbr.add_forward_to_graph(
+                    graph,
+                    trans.ty,
+                    color,
+                    resolve,
+                    depth,
+                    forward_uniform_bg,
+                    trans.cull,
+                    false,
+                );
+            }
+        }
     }
 
     grid.add_to_graph(graph, color, depth, resolve, grid_uniform_bg);
 
-    /*
-    // Add wireframe rendering
-    for trans in &per_transparency {
-        pbr.add_forward_to_graph(
-            graph,
-            trans.ty,
-            color,
-            resolve,
-            depth,
-            forward_uniform_bg,
-            trans.cull,
-            true,
-        );
+    // Edges go last, so they are drawn on top of the shaded mesh
+    if wireframe_mode != WireframeMode::Disabled {
+        wireframe.add_to_graph(graph, color, depth, resolve, wireframe_uniform_bg);
+    }
+
+    if normals_enabled {
+        normals.add_to_graph(graph, color, depth, resolve, normals_uniform_bg);
     }
-    */
 
-    //wireframe.add_to_graph(graph, color);
+    if points_enabled {
+        points.add_to_graph(graph, color, depth, resolve, points_uniform_bg);
+    }
 
     // Make the reference to the surface
     let surface = graph.add_surface_texture();
 
-    tonemapping.add_to_graph(graph, resolve.unwrap_or(color), surface, forward_uniform_bg);
+    tonemapping.add_to_graph(graph, resolve.unwrap_or(color), surface, tonemapping_config);
 }
 
 pub struct GridRoutine {
-    pipeline: RenderPipeline,
+    pipeline: MsaaPipeline,
     bgl: BindGroupLayout,
 }
 
@@ -194,21 +307,56 @@ pub struct GridRoutineUniform {
     pub inv_proj: [[f32; 4]; 4],
 }
 
+fn build_grid_pipeline(
+    device: &Device,
+    shader: &ShaderModule,
+    layout: &PipelineLayout,
+    samples: SampleCount,
+) -> RenderPipeline {
+    use wgpu::*;
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("Grid Pipeline"),
+        layout: Some(layout),
+        vertex: VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        primitive: PrimitiveState {
+            topology: PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: FrontFace::Ccw,
+            cull_mode: Some(Face::Back),
+            clamp_depth: false,
+            polygon_mode: PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: Some(depth_test(true)),
+        multisample: MultisampleState {
+            count: samples as u32,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        fragment: Some(FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[wgpu::ColorTargetState {
+                format: TextureFormat::Rgba16Float,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                write_mask: wgpu::ColorWrites::ALL,
+            }],
+        }),
+    })
+}
+
 impl GridRoutine {
-    pub fn new(device: &Device) -> Self {
+    pub fn new(device: &Device, samples: SampleCount) -> Self {
         use wgpu::*;
         let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
             label: Some("Shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
         });
 
-        let uniform_buffer = device.create_buffer(&BufferDescriptor {
-            label: Some("Grid uniform buffer"),
-            size: std::mem::size_of::<GridRoutineUniform>() as u64,
-            usage: BufferUsages::UNIFORM | BufferUsages::MAP_WRITE,
-            mapped_at_creation: false,
-        });
-
         let bgl = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
             label: Some("Grid BGL"),
             entries: &[BindGroupLayoutEntry {
@@ -229,47 +377,14 @@ impl GridRoutine {
             push_constant_ranges: &[],
         });
 
-        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
-            label: Some("Grid Pipeline"),
-            layout: Some(&layout),
-            vertex: VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[],
-            },
-            primitive: PrimitiveState {
-                topology: PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: FrontFace::Ccw,
-                cull_mode: Some(Face::Back),
-                clamp_depth: false,
-                polygon_mode: PolygonMode::Fill,
-                conservative: false,
-            },
-            depth_stencil: Some(DepthStencilState {
-                format: TextureFormat::Depth32Float,
-                depth_write_enabled: true,
-                depth_compare: CompareFunction::GreaterEqual,
-                stencil: StencilState::default(),
-                bias: DepthBiasState::default(),
-            }),
-            multisample: MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            fragment: Some(FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[wgpu::ColorTargetState {
-                    format: TextureFormat::Rgba16Float,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
-                    write_mask: wgpu::ColorWrites::ALL,
-                }],
-            }),
-        });
+        Self {
+            pipeline: MsaaPipeline::new(device, shader, layout, samples, build_grid_pipeline),
+            bgl,
+        }
+    }
 
-        Self { pipeline, bgl }
+    pub fn set_samples(&mut self, device: &Device, samples: SampleCount) {
+        self.pipeline.set_samples(device, samples);
     }
 
     fn add_to_graph<'node>(
@@ -308,7 +423,7 @@ impl GridRoutine {
                 let grid_uniform_bg = graph_data.get_data(temps, grid_uniform_handle).unwrap();
 
                 rpass.set_bind_group(0, grid_uniform_bg, &[]);
-                rpass.set_pipeline(&this.pipeline);
+                rpass.set_pipeline(this.pipeline.get());
                 rpass.draw(0..6, 0..1);
             },
         );
@@ -355,3 +470,18 @@ impl GridRoutine {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn test_viewport_resolution() {
+        // The render targets span the full width of the window, and the
+        // height of the top half, where the viewport is.
+        let window = UVec2::new(1920, 1080);
+        assert_eq!(viewport_resolution(window), UVec2::new(1920, 540));
+        // Render targets can't be empty, even for a minimized window
+        assert_eq!(viewport_resolution(UVec2::new(800, 1)).y, 1);
+    }
+}
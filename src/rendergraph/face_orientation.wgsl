@@ -0,0 +1,43 @@
+[[block]]
+struct FaceOrientationRoutineUniform {
+    view_proj: mat4x4<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> uniforms: FaceOrientationRoutineUniform;
+
+// Vertex shader
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] normal: vec3<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main(
+    [[location(0)]] position: vec3<f32>,
+    [[location(1)]] normal: vec3<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = uniforms.view_proj * vec4<f32>(position, 1.0);
+    out.normal = normal;
+    return out;
+}
+
+// Fragment shader
+
+[[stage(fragment)]]
+fn fs_main(
+    in: VertexOutput,
+    [[builtin(front_facing)]] front_facing: bool,
+) -> [[location(0)]] vec4<f32> {
+    var color = vec3<f32>(0.1, 0.3, 1.0);
+    if (!front_facing) {
+        color = vec3<f32>(1.0, 0.1, 0.1);
+    }
+    // Some simple shading, so the shape of the mesh can still be told apart.
+    // Both sides of a face are lit the same.
+    let light_dir = normalize(vec3<f32>(0.3, 1.0, 0.5));
+    let light = 0.5 + 0.5 * abs(dot(normalize(in.normal), light_dir));
+    return vec4<f32>(color * light, 1.0);
+}
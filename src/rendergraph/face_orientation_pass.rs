@@ -0,0 +1,257 @@
+use rend3::{
+    types::SampleCount, DataHandle, DepthHandle, RenderGraph, RenderPassDepthTarget,
+    RenderPassTarget, RenderPassTargets, RenderTargetHandle,
+};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupLayout, Buffer, Color, Device, PipelineLayout, RenderPipeline,
+    ShaderModule,
+};
+
+use crate::prelude::*;
+
+use super::{depth_test, MsaaPipeline};
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+pub struct FaceOrientationRoutineUniform {
+    pub view_proj: [[f32; 4]; 4],
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct FaceOrientationVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+}
+
+fn build_pipeline(
+    device: &Device,
+    shader: &ShaderModule,
+    layout: &PipelineLayout,
+    samples: SampleCount,
+) -> RenderPipeline {
+    use wgpu::*;
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("Face orientation Pipeline"),
+        layout: Some(layout),
+        vertex: VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[VertexBufferLayout {
+                array_stride: std::mem::size_of::<FaceOrientationVertex>() as u64,
+                step_mode: VertexStepMode::Vertex,
+                attributes: &vertex_attr_array![0 => Float32x3, 1 => Float32x3],
+            }],
+        },
+        // NOTE: Back faces are not culled, the fragment shader colors them
+        // differently instead.
+        primitive: PrimitiveState {
+            topology: PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: FrontFace::Ccw,
+            cull_mode: None,
+            clamp_depth: false,
+            polygon_mode: PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: Some(depth_test(true)),
+        multisample: MultisampleState {
+            count: samples as u32,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        fragment: Some(FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[ColorTargetState {
+                format: TextureFormat::Rgba16Float,
+                blend: None,
+                write_mask: ColorWrites::ALL,
+            }],
+        }),
+    })
+}
+
+/// Draws the faces of the current mesh in place of the shaded mesh, with the
+/// front side of each face in blue and the back side in red. Faces with an
+/// inconsistent orientation stand out from their neighbors.
+pub struct FaceOrientationRoutine {
+    pipeline: MsaaPipeline,
+    bgl: BindGroupLayout,
+    /// A triangle list with the faces to draw, and its number of vertices.
+    triangles: Option<(Buffer, u32)>,
+    pub enabled: bool,
+}
+
+impl FaceOrientationRoutine {
+    pub fn new(device: &Device, samples: SampleCount) -> Self {
+        use wgpu::*;
+        let shader = device.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("Face orientation shader"),
+            source: ShaderSource::Wgsl(include_str!("face_orientation.wgsl").into()),
+        });
+
+        let bgl = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Face orientation BGL"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Face orientation pipeline layout"),
+            bind_group_layouts: &[&bgl],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            pipeline: MsaaPipeline::new(device, shader, layout, samples, build_pipeline),
+            bgl,
+            triangles: None,
+            enabled: false,
+        }
+    }
+
+    pub fn set_samples(&mut self, device: &Device, samples: SampleCount) {
+        self.pipeline.set_samples(device, samples);
+    }
+
+    /// Uploads the faces of `mesh`, replacing the previous ones. Faces are
+    /// triangulated as a fan, keeping their winding. Nothing is uploaded
+    /// while the routine is disabled.
+    pub fn set_mesh(&mut self, device: &Device, mesh: &HalfEdgeMesh) {
+        if !self.enabled {
+            self.triangles = None;
+            return;
+        }
+        let mut vertices = vec![];
+        for (face_id, _) in mesh.iter_faces() {
+            let positions = mesh
+                .face_vertices(face_id)
+                .iter()
+                .map(|v| mesh.vertex_position(*v))
+                .collect::<SVec<_>>();
+            if positions.len() < 3 {
+                continue;
+            }
+            let normal = MeshQuery::face_normal(mesh, face_id)
+                .unwrap_or(Vec3::Y)
+                .to_array();
+            for (&b, &c) in positions[1..].iter().tuple_windows() {
+                for p in [positions[0], b, c] {
+                    vertices.push(FaceOrientationVertex {
+                        position: p.to_array(),
+                        normal,
+                    });
+                }
+            }
+        }
+        if vertices.is_empty() {
+            self.triangles = None;
+            return;
+        }
+        let buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Face orientation triangles"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        self.triangles = Some((buffer, vertices.len() as u32));
+    }
+
+    pub fn clear_mesh(&mut self) {
+        self.triangles = None;
+    }
+
+    pub fn add_to_graph<'node>(
+        &'node self,
+        graph: &mut RenderGraph<'node>,
+        color: RenderTargetHandle,
+        depth: RenderTargetHandle,
+        resolve: Option<RenderTargetHandle>,
+        face_orientation_uniform_bg: DataHandle<BindGroup>,
+    ) {
+        let mut builder = graph.add_node("Face orientation");
+        let color_handle = builder.add_render_target_output(color);
+        let resolve = builder.add_optional_render_target_output(resolve);
+        let depth_handle = builder.add_render_target_output(depth);
+
+        let rpass_handle = builder.add_renderpass(RenderPassTargets {
+            targets: vec![RenderPassTarget {
+                color: color_handle,
+                clear: Color::BLACK,
+                resolve,
+            }],
+            depth_stencil: Some(RenderPassDepthTarget {
+                target: DepthHandle::RenderTarget(depth_handle),
+                depth_clear: Some(0.0),
+                stencil_clear: None,
+            }),
+        });
+
+        let uniform_handle = builder.add_data_input(face_orientation_uniform_bg);
+        let pt_handle = builder.passthrough_ref(self);
+
+        builder.build(
+            move |pt, _renderer, encoder_or_pass, temps, _ready, graph_data| {
+                let this = pt.get(pt_handle);
+                let (triangles, num_vertices) = match &this.triangles {
+                    Some(triangles) => triangles,
+                    None => return,
+                };
+                let rpass = encoder_or_pass.get_rpass(rpass_handle);
+                let uniform_bg = graph_data.get_data(temps, uniform_handle).unwrap();
+
+                rpass.set_bind_group(0, uniform_bg, &[]);
+                rpass.set_pipeline(this.pipeline.get());
+                rpass.set_vertex_buffer(0, triangles.slice(..));
+                rpass.draw(0..*num_vertices, 0..1);
+            },
+        );
+    }
+
+    pub fn create_bind_groups<'node>(
+        &'node self,
+        graph: &mut RenderGraph<'node>,
+        face_orientation_uniform_bg: DataHandle<BindGroup>,
+    ) {
+        use wgpu::*;
+        let mut builder = graph.add_node("build face orientation uniforms");
+        let output_handle = builder.add_data_output(face_orientation_uniform_bg);
+        let pt_handle = builder.passthrough_ref(self);
+        builder.build(
+            move |pt, renderer, _encoder_or_pass, _temps, _ready, graph_data| {
+                let this = pt.get(pt_handle);
+
+                let camera_manager = renderer.camera_manager.read();
+                let data = FaceOrientationRoutineUniform {
+                    view_proj: camera_manager.view_proj().to_cols_array_2d(),
+                };
+
+                let buffer = renderer.device.create_buffer_init(&BufferInitDescriptor {
+                    label: None,
+                    contents: bytemuck::cast_slice(&[data]),
+                    usage: BufferUsages::UNIFORM,
+                });
+
+                let bind_group = renderer.device.create_bind_group(&BindGroupDescriptor {
+                    label: Some("Face orientation BindGroup"),
+                    layout: &this.bgl,
+                    entries: &[BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                });
+
+                graph_data.set_data(output_handle, Some(bind_group));
+            },
+        );
+    }
+}
@@ -0,0 +1,29 @@
+[[block]]
+struct NormalsRoutineUniform {
+    view_proj: mat4x4<f32>;
+    color: vec4<f32>;
+    segment_length: f32;
+};
+
+[[group(0), binding(0)]]
+var<uniform> uniforms: NormalsRoutineUniform;
+
+// Vertex shader
+
+[[stage(vertex)]]
+fn vs_main(
+    [[location(0)]] position: vec3<f32>,
+    [[location(1)]] direction: vec3<f32>,
+) -> [[builtin(position)]] vec4<f32> {
+    // Each segment starts with a zero direction at the vertex, and ends at the
+    // vertex moved along its normal.
+    let world_position = position + direction * uniforms.segment_length;
+    return uniforms.view_proj * vec4<f32>(world_position, 1.0);
+}
+
+// Fragment shader
+
+[[stage(fragment)]]
+fn fs_main() -> [[location(0)]] vec4<f32> {
+    return uniforms.color;
+}
@@ -0,0 +1,275 @@
+use rend3::{
+    types::SampleCount, DataHandle, DepthHandle, RenderGraph, RenderPassDepthTarget,
+    RenderPassTarget, RenderPassTargets, RenderTargetHandle,
+};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupLayout, Buffer, Color, Device, PipelineLayout, RenderPipeline,
+    ShaderModule,
+};
+
+use crate::prelude::*;
+
+use super::{depth_test, MsaaPipeline};
+
+#[derive(Debug, Clone, Copy)]
+pub struct NormalsSettings {
+    pub enabled: bool,
+    /// The length of the segments, in world units.
+    pub length: f32,
+    pub color: Vec3,
+}
+
+impl Default for NormalsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            length: 0.1,
+            color: Vec3::new(0.2, 0.4, 1.0),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+pub struct NormalsRoutineUniform {
+    pub view_proj: [[f32; 4]; 4],
+    pub color: [f32; 4],
+    pub segment_length: f32,
+    pub _padding: [f32; 3],
+}
+
+/// A vertex of the line list. Each segment has a vertex with a zero
+/// direction, and another one with the normal as direction, which the shader
+/// scales by the segment length. This way, the length can change without rebuilding the
+/// buffer.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct NormalsVertex {
+    position: [f32; 3],
+    direction: [f32; 3],
+}
+
+fn build_pipeline(
+    device: &Device,
+    shader: &ShaderModule,
+    layout: &PipelineLayout,
+    samples: SampleCount,
+) -> RenderPipeline {
+    use wgpu::*;
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("Normals Pipeline"),
+        layout: Some(layout),
+        vertex: VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[VertexBufferLayout {
+                array_stride: std::mem::size_of::<NormalsVertex>() as u64,
+                step_mode: VertexStepMode::Vertex,
+                attributes: &vertex_attr_array![0 => Float32x3, 1 => Float32x3],
+            }],
+        },
+        primitive: PrimitiveState {
+            topology: PrimitiveTopology::LineList,
+            strip_index_format: None,
+            front_face: FrontFace::Ccw,
+            cull_mode: None,
+            clamp_depth: false,
+            polygon_mode: PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: Some(depth_test(false)),
+        multisample: MultisampleState {
+            count: samples as u32,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        fragment: Some(FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[ColorTargetState {
+                format: TextureFormat::Rgba16Float,
+                blend: Some(BlendState::ALPHA_BLENDING),
+                write_mask: ColorWrites::ALL,
+            }],
+        }),
+    })
+}
+
+/// Draws a short segment from each vertex of the current mesh, along its
+/// normal. Useful to debug the orientation of faces.
+pub struct NormalsRoutine {
+    pipeline: MsaaPipeline,
+    bgl: BindGroupLayout,
+    /// A line list with the segments to draw, and its number of vertices.
+    segments: Option<(Buffer, u32)>,
+    pub settings: NormalsSettings,
+}
+
+impl NormalsRoutine {
+    pub fn new(device: &Device, samples: SampleCount) -> Self {
+        use wgpu::*;
+        let shader = device.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("Normals shader"),
+            source: ShaderSource::Wgsl(include_str!("normals.wgsl").into()),
+        });
+
+        let bgl = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Normals BGL"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Normals pipeline layout"),
+            bind_group_layouts: &[&bgl],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            pipeline: MsaaPipeline::new(device, shader, layout, samples, build_pipeline),
+            bgl,
+            segments: None,
+            settings: NormalsSettings::default(),
+        }
+    }
+
+    pub fn set_samples(&mut self, device: &Device, samples: SampleCount) {
+        self.pipeline.set_samples(device, samples);
+    }
+
+    /// Uploads the vertex normals of `mesh`, replacing the previous ones. The
+    /// mesh is uploaded regardless of the settings, and only drawn while the
+    /// routine is enabled.
+    pub fn set_mesh(&mut self, device: &Device, mesh: &HalfEdgeMesh) {
+        let vertices = mesh
+            .compute_vertex_normals()
+            .into_iter()
+            .flat_map(|(v, normal)| {
+                let position = mesh.vertex_position(v).to_array();
+                [
+                    NormalsVertex {
+                        position,
+                        direction: [0.0; 3],
+                    },
+                    NormalsVertex {
+                        position,
+                        direction: normal.to_array(),
+                    },
+                ]
+            })
+            .collect_vec();
+        if vertices.is_empty() {
+            self.segments = None;
+            return;
+        }
+        let buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Normal segments"),
+            contents: bytemuck::cast_slice(&vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        self.segments = Some((buffer, vertices.len() as u32));
+    }
+
+    pub fn clear_mesh(&mut self) {
+        self.segments = None;
+    }
+
+    pub fn add_to_graph<'node>(
+        &'node self,
+        graph: &mut RenderGraph<'node>,
+        color: RenderTargetHandle,
+        depth: RenderTargetHandle,
+        resolve: Option<RenderTargetHandle>,
+        normals_uniform_bg: DataHandle<BindGroup>,
+    ) {
+        let mut builder = graph.add_node("Vertex normals");
+        let color_handle = builder.add_render_target_output(color);
+        let resolve = builder.add_optional_render_target_output(resolve);
+        let depth_handle = builder.add_render_target_output(depth);
+
+        let rpass_handle = builder.add_renderpass(RenderPassTargets {
+            targets: vec![RenderPassTarget {
+                color: color_handle,
+                clear: Color::BLACK,
+                resolve,
+            }],
+            depth_stencil: Some(RenderPassDepthTarget {
+                target: DepthHandle::RenderTarget(depth_handle),
+                depth_clear: Some(0.0),
+                stencil_clear: None,
+            }),
+        });
+
+        let normals_uniform_handle = builder.add_data_input(normals_uniform_bg);
+        let pt_handle = builder.passthrough_ref(self);
+
+        builder.build(
+            move |pt, _renderer, encoder_or_pass, temps, _ready, graph_data| {
+                let this = pt.get(pt_handle);
+                let (segments, num_vertices) = match &this.segments {
+                    Some(segments) => segments,
+                    None => return,
+                };
+                let rpass = encoder_or_pass.get_rpass(rpass_handle);
+                let normals_uniform_bg =
+                    graph_data.get_data(temps, normals_uniform_handle).unwrap();
+
+                rpass.set_bind_group(0, normals_uniform_bg, &[]);
+                rpass.set_pipeline(this.pipeline.get());
+                rpass.set_vertex_buffer(0, segments.slice(..));
+                rpass.draw(0..*num_vertices, 0..1);
+            },
+        );
+    }
+
+    pub fn create_bind_groups<'node>(
+        &'node self,
+        graph: &mut RenderGraph<'node>,
+        normals_uniform_bg: DataHandle<BindGroup>,
+    ) {
+        use wgpu::*;
+        let mut builder = graph.add_node("build normals uniforms");
+        let output_handle = builder.add_data_output(normals_uniform_bg);
+        let pt_handle = builder.passthrough_ref(self);
+        builder.build(
+            move |pt, renderer, _encoder_or_pass, _temps, _ready, graph_data| {
+                let this = pt.get(pt_handle);
+
+                let camera_manager = renderer.camera_manager.read();
+                let settings = &this.settings;
+                let data = NormalsRoutineUniform {
+                    view_proj: camera_manager.view_proj().to_cols_array_2d(),
+                    color: settings.color.extend(1.0).to_array(),
+                    segment_length: settings.length,
+                    _padding: [0.0; 3],
+                };
+
+                let buffer = renderer.device.create_buffer_init(&BufferInitDescriptor {
+                    label: None,
+                    contents: bytemuck::cast_slice(&[data]),
+                    usage: BufferUsages::UNIFORM,
+                });
+
+                let bind_group = renderer.device.create_bind_group(&BindGroupDescriptor {
+                    label: Some("Normals BindGroup"),
+                    layout: &this.bgl,
+                    entries: &[BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                });
+
+                graph_data.set_data(output_handle, Some(bind_group));
+            },
+        );
+    }
+}
@@ -0,0 +1,47 @@
+[[block]]
+struct PointsRoutineUniform {
+    view_proj: mat4x4<f32>;
+    color: vec4<f32>;
+    viewport_size: vec2<f32>;
+    point_size: f32;
+};
+
+[[group(0), binding(0)]]
+var<uniform> uniforms: PointsRoutineUniform;
+
+// The two triangles of the quad drawn for each point
+var<private> corners: array<vec2<f32>, 6> = array<vec2<f32>, 6>(
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(1.0, -1.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(-1.0, -1.0),
+    vec2<f32>(1.0, 1.0),
+    vec2<f32>(-1.0, 1.0),
+);
+
+// Vertex shader
+
+[[stage(vertex)]]
+fn vs_main(
+    [[builtin(vertex_index)]] in_vertex_index: u32,
+    [[location(0)]] position: vec3<f32>,
+) -> [[builtin(position)]] vec4<f32> {
+    let clip_position = uniforms.view_proj * vec4<f32>(position, 1.0);
+    // The offset is scaled by w so it stays the same after the perspective
+    // divide, and the points have the same size in pixels at any distance.
+    let offset = corners[in_vertex_index] * uniforms.point_size / uniforms.viewport_size;
+    return vec4<f32>(
+        clip_position.xy + offset * clip_position.w,
+        // Depth is reversed, so scaling it up moves the points slightly
+        // towards the camera, and they are not hidden by their faces.
+        clip_position.z * 1.002,
+        clip_position.w,
+    );
+}
+
+// Fragment shader
+
+[[stage(fragment)]]
+fn fs_main() -> [[location(0)]] vec4<f32> {
+    return uniforms.color;
+}
@@ -0,0 +1,256 @@
+use rend3::{
+    types::SampleCount, DataHandle, DepthHandle, RenderGraph, RenderPassDepthTarget,
+    RenderPassTarget, RenderPassTargets, RenderTargetHandle,
+};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupLayout, Buffer, Color, Device, PipelineLayout, RenderPipeline,
+    ShaderModule,
+};
+
+use crate::prelude::*;
+
+use super::{depth_test, MsaaPipeline};
+
+#[derive(Debug, Clone, Copy)]
+pub struct PointsSettings {
+    pub enabled: bool,
+    /// The size of the points on screen, in pixels.
+    pub size: f32,
+    pub color: Vec3,
+}
+
+impl Default for PointsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            size: 6.0,
+            color: Vec3::new(1.0, 0.6, 0.0),
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+pub struct PointsRoutineUniform {
+    pub view_proj: [[f32; 4]; 4],
+    pub color: [f32; 4],
+    pub viewport_size: [f32; 2],
+    pub point_size: f32,
+    pub _padding: f32,
+}
+
+fn build_pipeline(
+    device: &Device,
+    shader: &ShaderModule,
+    layout: &PipelineLayout,
+    samples: SampleCount,
+) -> RenderPipeline {
+    use wgpu::*;
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("Points Pipeline"),
+        layout: Some(layout),
+        vertex: VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            // Each point is an instance, drawn as a camera-facing quad
+            buffers: &[VertexBufferLayout {
+                array_stride: std::mem::size_of::<[f32; 3]>() as u64,
+                step_mode: VertexStepMode::Instance,
+                attributes: &vertex_attr_array![0 => Float32x3],
+            }],
+        },
+        primitive: PrimitiveState {
+            topology: PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: FrontFace::Ccw,
+            cull_mode: None,
+            clamp_depth: false,
+            polygon_mode: PolygonMode::Fill,
+            conservative: false,
+        },
+        depth_stencil: Some(depth_test(false)),
+        multisample: MultisampleState {
+            count: samples as u32,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        fragment: Some(FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[ColorTargetState {
+                format: TextureFormat::Rgba16Float,
+                blend: Some(BlendState::ALPHA_BLENDING),
+                write_mask: ColorWrites::ALL,
+            }],
+        }),
+    })
+}
+
+/// Draws the vertices of the current mesh as points, with a fixed size on
+/// screen.
+pub struct PointsRoutine {
+    pipeline: MsaaPipeline,
+    bgl: BindGroupLayout,
+    /// The positions of the points to draw, and the number of points.
+    points: Option<(Buffer, u32)>,
+    pub settings: PointsSettings,
+}
+
+impl PointsRoutine {
+    pub fn new(device: &Device, samples: SampleCount) -> Self {
+        use wgpu::*;
+        let shader = device.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("Points shader"),
+            source: ShaderSource::Wgsl(include_str!("points.wgsl").into()),
+        });
+
+        let bgl = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Points BGL"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Points pipeline layout"),
+            bind_group_layouts: &[&bgl],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            pipeline: MsaaPipeline::new(device, shader, layout, samples, build_pipeline),
+            bgl,
+            points: None,
+            settings: PointsSettings::default(),
+        }
+    }
+
+    pub fn set_samples(&mut self, device: &Device, samples: SampleCount) {
+        self.pipeline.set_samples(device, samples);
+    }
+
+    /// Uploads the vertex positions of `mesh`, replacing the previous ones. The
+    /// mesh is uploaded regardless of the settings, and only drawn while the
+    /// routine is enabled.
+    pub fn set_mesh(&mut self, device: &Device, mesh: &HalfEdgeMesh) {
+        let positions = mesh
+            .iter_vertices()
+            .map(|(_, v)| v.position.to_array())
+            .collect_vec();
+        if positions.is_empty() {
+            self.points = None;
+            return;
+        }
+        let buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Vertex points"),
+            contents: bytemuck::cast_slice(&positions),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        self.points = Some((buffer, positions.len() as u32));
+    }
+
+    pub fn clear_mesh(&mut self) {
+        self.points = None;
+    }
+
+    pub fn add_to_graph<'node>(
+        &'node self,
+        graph: &mut RenderGraph<'node>,
+        color: RenderTargetHandle,
+        depth: RenderTargetHandle,
+        resolve: Option<RenderTargetHandle>,
+        points_uniform_bg: DataHandle<BindGroup>,
+    ) {
+        let mut builder = graph.add_node("Vertex points");
+        let color_handle = builder.add_render_target_output(color);
+        let resolve = builder.add_optional_render_target_output(resolve);
+        let depth_handle = builder.add_render_target_output(depth);
+
+        let rpass_handle = builder.add_renderpass(RenderPassTargets {
+            targets: vec![RenderPassTarget {
+                color: color_handle,
+                clear: Color::BLACK,
+                resolve,
+            }],
+            depth_stencil: Some(RenderPassDepthTarget {
+                target: DepthHandle::RenderTarget(depth_handle),
+                depth_clear: Some(0.0),
+                stencil_clear: None,
+            }),
+        });
+
+        let points_uniform_handle = builder.add_data_input(points_uniform_bg);
+        let pt_handle = builder.passthrough_ref(self);
+
+        builder.build(
+            move |pt, _renderer, encoder_or_pass, temps, _ready, graph_data| {
+                let this = pt.get(pt_handle);
+                let (points, num_points) = match &this.points {
+                    Some(points) => points,
+                    None => return,
+                };
+                let rpass = encoder_or_pass.get_rpass(rpass_handle);
+                let points_uniform_bg = graph_data.get_data(temps, points_uniform_handle).unwrap();
+
+                rpass.set_bind_group(0, points_uniform_bg, &[]);
+                rpass.set_pipeline(this.pipeline.get());
+                rpass.set_vertex_buffer(0, points.slice(..));
+                rpass.draw(0..6, 0..*num_points);
+            },
+        );
+    }
+
+    /// The `resolution` is the size of the render targets, used to keep the
+    /// size of the points in pixels.
+    pub fn create_bind_groups<'node>(
+        &'node self,
+        graph: &mut RenderGraph<'node>,
+        points_uniform_bg: DataHandle<BindGroup>,
+        resolution: UVec2,
+    ) {
+        use wgpu::*;
+        let mut builder = graph.add_node("build points uniforms");
+        let output_handle = builder.add_data_output(points_uniform_bg);
+        let pt_handle = builder.passthrough_ref(self);
+        builder.build(
+            move |pt, renderer, _encoder_or_pass, _temps, _ready, graph_data| {
+                let this = pt.get(pt_handle);
+
+                let camera_manager = renderer.camera_manager.read();
+                let settings = &this.settings;
+                let data = PointsRoutineUniform {
+                    view_proj: camera_manager.view_proj().to_cols_array_2d(),
+                    color: settings.color.extend(1.0).to_array(),
+                    viewport_size: [resolution.x as f32, resolution.y as f32],
+                    point_size: settings.size,
+                    _padding: 0.0,
+                };
+
+                let buffer = renderer.device.create_buffer_init(&BufferInitDescriptor {
+                    label: None,
+                    contents: bytemuck::cast_slice(&[data]),
+                    usage: BufferUsages::UNIFORM,
+                });
+
+                let bind_group = renderer.device.create_bind_group(&BindGroupDescriptor {
+                    label: Some("Points BindGroup"),
+                    layout: &this.bgl,
+                    entries: &[BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                });
+
+                graph_data.set_data(output_handle, Some(bind_group));
+            },
+        );
+    }
+}
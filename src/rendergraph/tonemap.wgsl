@@ -0,0 +1,66 @@
+[[block]]
+struct TonemapRoutineUniform {
+    exposure: f32;
+    operator: u32;
+};
+
+[[group(0), binding(0)]]
+var<uniform> uniforms: TonemapRoutineUniform;
+
+[[group(0), binding(1)]]
+var hdr_color: texture_2d<f32>;
+
+[[group(0), binding(2)]]
+var hdr_sampler: sampler;
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] uv: vec2<f32>;
+};
+
+// Vertex shader
+
+[[stage(vertex)]]
+fn vs_main(
+    [[builtin(vertex_index)]] in_vertex_index: u32,
+) -> VertexOutput {
+    // A single triangle covering the whole viewport
+    let uv = vec2<f32>(f32((in_vertex_index << 1u) & 2u), f32(in_vertex_index & 2u));
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+
+// Fragment shader
+
+fn reinhard(color: vec3<f32>) -> vec3<f32> {
+    return color / (vec3<f32>(1.0) + color);
+}
+
+// Krzysztof Narkowicz's fit of the ACES filmic curve
+fn aces(color: vec3<f32>) -> vec3<f32> {
+    let a = 2.51;
+    let b = 0.03;
+    let c = 2.43;
+    let d = 0.59;
+    let e = 0.14;
+    return (color * (a * color + b)) / (color * (c * color + d) + e);
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    let hdr = textureSample(hdr_color, hdr_sampler, in.uv);
+    let color = hdr.rgb * exp2(uniforms.exposure);
+
+    var mapped: vec3<f32> = color;
+    if (uniforms.operator == 1u) {
+        mapped = reinhard(color);
+    } else {
+        if (uniforms.operator == 2u) {
+            mapped = aces(color);
+        }
+    }
+    return vec4<f32>(clamp(mapped, vec3<f32>(0.0), vec3<f32>(1.0)), 1.0);
+}
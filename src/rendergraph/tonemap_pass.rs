@@ -0,0 +1,237 @@
+use rend3::{RenderGraph, RenderPassTarget, RenderPassTargets, RenderTargetHandle};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroupLayout, Color, Device, RenderPipeline, Sampler, TextureFormat,
+};
+
+use crate::prelude::*;
+
+/// The curve used to map the HDR colors of the scene to the display range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TonemapOperator {
+    /// Colors are only clamped to the display range.
+    Linear,
+    Reinhard,
+    /// An approximation of the ACES filmic curve.
+    Aces,
+}
+
+impl TonemapOperator {
+    /// The value identifying this operator in the shader.
+    fn shader_id(&self) -> u32 {
+        match self {
+            TonemapOperator::Linear => 0,
+            TonemapOperator::Reinhard => 1,
+            TonemapOperator::Aces => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Tonemapping {
+    pub operator: TonemapOperator,
+    /// Exposure adjustment, in stops. Colors are multiplied by 2^exposure
+    /// before applying the operator.
+    pub exposure: f32,
+}
+
+impl Default for Tonemapping {
+    fn default() -> Self {
+        Self {
+            operator: TonemapOperator::Linear,
+            exposure: 0.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+pub struct TonemapRoutineUniform {
+    pub exposure: f32,
+    pub operator: u32,
+    pub _padding: [u32; 2],
+}
+
+/// Maps the HDR render target to the output surface, applying exposure and a
+/// tonemapping operator. The viewport only covers the top half of the
+/// window, so that's where the result is drawn.
+pub struct TonemapRoutine {
+    pipeline: RenderPipeline,
+    bgl: BindGroupLayout,
+    sampler: Sampler,
+    resolution: UVec2,
+}
+
+impl TonemapRoutine {
+    pub fn new(device: &Device, resolution: UVec2, output_format: TextureFormat) -> Self {
+        use wgpu::*;
+        let shader = device.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("Tonemap shader"),
+            source: ShaderSource::Wgsl(include_str!("tonemap.wgsl").into()),
+        });
+
+        let bgl = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Tonemap BGL"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler {
+                        filtering: true,
+                        comparison: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Tonemap pipeline layout"),
+            bind_group_layouts: &[&bgl],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Tonemap Pipeline"),
+            layout: Some(&layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: FrontFace::Ccw,
+                cull_mode: None,
+                clamp_depth: false,
+                polygon_mode: PolygonMode::Fill,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[ColorTargetState {
+                    format: output_format,
+                    blend: None,
+                    write_mask: ColorWrites::ALL,
+                }],
+            }),
+        });
+
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("Tonemap sampler"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            pipeline,
+            bgl,
+            sampler,
+            resolution,
+        }
+    }
+
+    pub fn resize(&mut self, resolution: UVec2) {
+        self.resolution = resolution;
+    }
+
+    pub fn add_to_graph<'node>(
+        &'node self,
+        graph: &mut RenderGraph<'node>,
+        src: RenderTargetHandle,
+        dst: RenderTargetHandle,
+        tonemapping: Tonemapping,
+    ) {
+        let mut builder = graph.add_node("Tonemapping");
+        let input_handle = builder.add_render_target_input(src);
+        let output_handle = builder.add_render_target_output(dst);
+
+        let rpass_handle = builder.add_renderpass(RenderPassTargets {
+            targets: vec![RenderPassTarget {
+                color: output_handle,
+                clear: Color::BLACK,
+                resolve: None,
+            }],
+            depth_stencil: None,
+        });
+
+        let pt_handle = builder.passthrough_ref(self);
+
+        builder.build(
+            move |pt, renderer, encoder_or_pass, temps, _ready, graph_data| {
+                use wgpu::*;
+                let this = pt.get(pt_handle);
+                let rpass = encoder_or_pass.get_rpass(rpass_handle);
+                let hdr_color = graph_data.get_render_target(input_handle);
+
+                let data = TonemapRoutineUniform {
+                    exposure: tonemapping.exposure,
+                    operator: tonemapping.operator.shader_id(),
+                    _padding: [0; 2],
+                };
+                let buffer = renderer.device.create_buffer_init(&BufferInitDescriptor {
+                    label: None,
+                    contents: bytemuck::cast_slice(&[data]),
+                    usage: BufferUsages::UNIFORM,
+                });
+
+                let bind_group =
+                    temps.add(renderer.device.create_bind_group(&BindGroupDescriptor {
+                        label: Some("Tonemap BindGroup"),
+                        layout: &this.bgl,
+                        entries: &[
+                            BindGroupEntry {
+                                binding: 0,
+                                resource: buffer.as_entire_binding(),
+                            },
+                            BindGroupEntry {
+                                binding: 1,
+                                resource: BindingResource::TextureView(hdr_color),
+                            },
+                            BindGroupEntry {
+                                binding: 2,
+                                resource: BindingResource::Sampler(&this.sampler),
+                            },
+                        ],
+                    }));
+
+                // NOTE: The HDR render target only covers the 3d viewport, at
+                // the top of the window.
+                let viewport = super::viewport_resolution(this.resolution);
+                rpass.set_viewport(0.0, 0.0, viewport.x as f32, viewport.y as f32, 0.0, 1.0);
+                rpass.set_pipeline(&this.pipeline);
+                rpass.set_bind_group(0, bind_group, &[]);
+                rpass.draw(0..3, 0..1);
+            },
+        );
+    }
+}
@@ -1,118 +1,293 @@
-use rend3::{RenderGraph, RenderTargetHandle};
-use rend3_routine::PbrRenderRoutine;
-use wgpu::Device;
+use rend3::{
+    types::SampleCount, DataHandle, DepthHandle, RenderGraph, RenderPassDepthTarget,
+    RenderPassTarget, RenderPassTargets, RenderTargetHandle,
+};
+use wgpu::{
+    util::{BufferInitDescriptor, DeviceExt},
+    BindGroup, BindGroupLayout, Buffer, Color, Device, PipelineLayout, RenderPipeline,
+    ShaderModule,
+};
 
-pub fn build_wireframe_pass_pipeline(_device: &Device, _pbr_routine: &PbrRenderRoutine) -> () {
-    /*
-    let wireframe_pass_shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
-        label: Some("wireframe_pass_vertex"),
-        source: wgpu::ShaderSource::Wgsl(include_str!("wireframe.wgsl").into()),
-    });
+use crate::prelude::*;
 
-    let mut bgls: Vec<&BindGroupLayout> = Vec::new();
-    bgls.push(&pbr_routine.interfaces.per_material_bgl);
+use super::{depth_test, MsaaPipeline};
 
-    let pll = device.create_pipeline_layout(&PipelineLayoutDescriptor {
-        label: Some("wireframe pass"),
-        bind_group_layouts: &bgls,
-        push_constant_ranges: &[],
-    });
+/// How the edges of the mesh are drawn in the viewport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireframeMode {
+    /// Only the shaded mesh is drawn.
+    Disabled,
+    /// Only the edges are drawn, including the ones hidden behind faces.
+    Wireframe,
+    /// The edges are drawn on top of the shaded mesh, hiding the ones behind
+    /// faces.
+    WireOnShaded,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WireframeSettings {
+    pub mode: WireframeMode,
+    pub color: Vec3,
+    pub opacity: f32,
+    /// Moves the edges towards the camera by this fraction of their distance,
+    /// so they are not hidden by the faces they belong to in `WireOnShaded`
+    /// mode.
+    pub depth_bias: f32,
+}
+
+impl Default for WireframeSettings {
+    fn default() -> Self {
+        Self {
+            mode: WireframeMode::WireOnShaded,
+            color: Vec3::splat(0.05),
+            opacity: 0.8,
+            depth_bias: 0.002,
+        }
+    }
+}
 
-    let cpu_vertex_buffers = cpu_vertex_buffers();
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable, Default)]
+pub struct WireframeRoutineUniform {
+    pub view_proj: [[f32; 4]; 4],
+    pub color: [f32; 4],
+    pub depth_bias: f32,
+    pub _padding: [f32; 3],
+}
 
+fn build_pipeline(
+    device: &Device,
+    shader: &ShaderModule,
+    layout: &PipelineLayout,
+    depth_stencil: wgpu::DepthStencilState,
+    samples: SampleCount,
+) -> RenderPipeline {
+    use wgpu::*;
     device.create_render_pipeline(&RenderPipelineDescriptor {
-        label: Some("wireframe pass"),
-        layout: Some(&pll),
+        label: Some("Wireframe Pipeline"),
+        layout: Some(layout),
         vertex: VertexState {
-            module: &wireframe_pass_shader,
-            entry_point: "vertex_main",
-            buffers: &cpu_vertex_buffers,
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[VertexBufferLayout {
+                array_stride: std::mem::size_of::<[f32; 3]>() as u64,
+                step_mode: VertexStepMode::Vertex,
+                attributes: &vertex_attr_array![0 => Float32x3],
+            }],
         },
         primitive: PrimitiveState {
-            topology: PrimitiveTopology::TriangleList,
+            topology: PrimitiveTopology::LineList,
             strip_index_format: None,
-            front_face: FrontFace::Cw,
-            cull_mode: Some(Face::Back),
+            front_face: FrontFace::Ccw,
+            cull_mode: None,
             clamp_depth: false,
-            polygon_mode: PolygonMode::Line,
+            polygon_mode: PolygonMode::Fill,
             conservative: false,
         },
-        depth_stencil: None,
+        depth_stencil: Some(depth_stencil),
         multisample: MultisampleState {
-            count: 1,
-            ..Default::default()
+            count: samples as u32,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
         },
         fragment: Some(FragmentState {
-            module: &wireframe_pass_shader,
-            entry_point: "fragment_main",
+            module: shader,
+            entry_point: "fs_main",
             targets: &[ColorTargetState {
                 format: TextureFormat::Rgba16Float,
-                blend: None,
-                write_mask: ColorWrites::all(),
+                blend: Some(BlendState::ALPHA_BLENDING),
+                write_mask: ColorWrites::ALL,
             }],
         }),
     })
-    */
 }
 
+/// Builds the overlay and standalone pipelines, see [`WireframeRoutine`].
+fn build_pipelines(
+    device: &Device,
+    shader: &ShaderModule,
+    layout: &PipelineLayout,
+    samples: SampleCount,
+) -> (RenderPipeline, RenderPipeline) {
+    let ignore_depth = wgpu::DepthStencilState {
+        depth_compare: wgpu::CompareFunction::Always,
+        ..depth_test(false)
+    };
+    (
+        build_pipeline(device, shader, layout, depth_test(false), samples),
+        build_pipeline(device, shader, layout, ignore_depth, samples),
+    )
+}
+
+/// Draws the edges of the current mesh as lines.
 pub struct WireframeRoutine {
-    pipeline: (),
+    /// The overlay pipeline tests against the depth of the shaded mesh, and is
+    /// used in `WireOnShaded` mode. The standalone one ignores depth, and is
+    /// used in `Wireframe` mode.
+    pipelines: MsaaPipeline<(RenderPipeline, RenderPipeline)>,
+    bgl: BindGroupLayout,
+    /// A line list with the edges to draw, and its number of vertices.
+    edges: Option<(Buffer, u32)>,
+    pub settings: WireframeSettings,
 }
 
 impl WireframeRoutine {
-    pub fn new(device: &Device, pbr_routine: &PbrRenderRoutine) -> Self {
-        let pipeline = build_wireframe_pass_pipeline(device, pbr_routine);
-        WireframeRoutine { pipeline }
+    pub fn new(device: &Device, samples: SampleCount) -> Self {
+        use wgpu::*;
+        let shader = device.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("Wireframe shader"),
+            source: ShaderSource::Wgsl(include_str!("wireframe.wgsl").into()),
+        });
+
+        let bgl = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("Wireframe BGL"),
+            entries: &[BindGroupLayoutEntry {
+                binding: 0,
+                visibility: ShaderStages::VERTEX_FRAGMENT,
+                ty: BindingType::Buffer {
+                    ty: BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Wireframe pipeline layout"),
+            bind_group_layouts: &[&bgl],
+            push_constant_ranges: &[],
+        });
+
+        Self {
+            pipelines: MsaaPipeline::new(device, shader, layout, samples, build_pipelines),
+            bgl,
+            edges: None,
+            settings: WireframeSettings::default(),
+        }
+    }
+
+    pub fn set_samples(&mut self, device: &Device, samples: SampleCount) {
+        self.pipelines.set_samples(device, samples);
     }
 
-    pub fn add_to_graph(&self, _graph: &mut RenderGraph, _color: RenderTargetHandle) {
-        /*
-               let mut builder = graph.add_node("Wireframe Pass");
-
-               let hdr_color_handle = builder.add_render_target_output(color);
-
-               let rpass_handle = builder.add_renderpass(RenderPassTargets {
-                   targets: vec![RenderPassTarget {
-                       color: hdr_color_handle,
-                       clear: Color::BLACK,
-                       resolve: None,
-                   }],
-                   depth_stencil: None,
-               });
-
-               let _ = builder.add_shadow_array_input();
-
-               let forward_uniform_handle = builder.add_data_input(forward_uniform_bg);
-               let cull_handle = builder.add_data_input(culled);
-
-               let pt_handle = builder.passthrough_ref(self);
-
-               builder.build(move |pt, renderer, encoder_or_pass, temps, ready, graph_data| {
-                   let this = pt.get(pt_handle);
-                   let rpass = encoder_or_pass.get_rpass(rpass_handle);
-                   let forward_uniform_bg = graph_data.get_data(temps, forward_uniform_handle).unwrap();
-                   let culled = graph_data.get_data(temps, cull_handle).unwrap();
-
-                   let pass = match transparency {
-                       TransparencyType::Opaque => &this.primary_passes.opaque_pass,
-                       TransparencyType::Cutout => &this.primary_passes.cutout_pass,
-                       TransparencyType::Blend => &this.primary_passes.transparent_pass,
-                   };
-
-                   let d2_texture_output_bg_ref = ready.d2_texture.bg.as_ref().map(|_| (), |a| &**a);
-
-                   pass.draw(forward::ForwardPassDrawArgs {
-                       device: &renderer.device,
-                       rpass,
-                       materials: graph_data.material_manager,
-                       meshes: graph_data.mesh_manager.buffers(),
-                       samplers: &this.samplers,
-                       forward_uniform_bg,
-                       per_material_bg: &culled.per_material,
-                       texture_bg: d2_texture_output_bg_ref,
-                       culled_objects: &culled.inner,
-                   });
-               });
-        */
+    /// Uploads the edges of `mesh`, replacing the previous ones.
+    pub fn set_mesh(&mut self, device: &Device, mesh: &HalfEdgeMesh) {
+        let positions = mesh
+            .iter_edges()
+            .filter_map(|h| mesh.at_halfedge(h).src_dst_pair().ok())
+            .flat_map(|(src, dst)| [mesh.vertex_position(src), mesh.vertex_position(dst)])
+            .map(|p| p.to_array())
+            .collect_vec();
+        if positions.is_empty() {
+            self.edges = None;
+            return;
+        }
+        let buffer = device.create_buffer_init(&BufferInitDescriptor {
+            label: Some("Wireframe edges"),
+            contents: bytemuck::cast_slice(&positions),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        self.edges = Some((buffer, positions.len() as u32));
+    }
+
+    pub fn clear_mesh(&mut self) {
+        self.edges = None;
+    }
+
+    pub fn add_to_graph<'node>(
+        &'node self,
+        graph: &mut RenderGraph<'node>,
+        color: RenderTargetHandle,
+        depth: RenderTargetHandle,
+        resolve: Option<RenderTargetHandle>,
+        wireframe_uniform_bg: DataHandle<BindGroup>,
+    ) {
+        let mut builder = graph.add_node("Wireframe");
+        let color_handle = builder.add_render_target_output(color);
+        let resolve = builder.add_optional_render_target_output(resolve);
+        let depth_handle = builder.add_render_target_output(depth);
+
+        let rpass_handle = builder.add_renderpass(RenderPassTargets {
+            targets: vec![RenderPassTarget {
+                color: color_handle,
+                clear: Color::BLACK,
+                resolve,
+            }],
+            depth_stencil: Some(RenderPassDepthTarget {
+                target: DepthHandle::RenderTarget(depth_handle),
+                depth_clear: Some(0.0),
+                stencil_clear: None,
+            }),
+        });
+
+        let wireframe_uniform_handle = builder.add_data_input(wireframe_uniform_bg);
+        let pt_handle = builder.passthrough_ref(self);
+
+        builder.build(
+            move |pt, _renderer, encoder_or_pass, temps, _ready, graph_data| {
+                let this = pt.get(pt_handle);
+                let (edges, num_vertices) = match &this.edges {
+                    Some(edges) => edges,
+                    None => return,
+                };
+                let (overlay_pipeline, standalone_pipeline) = this.pipelines.get();
+                let pipeline = match this.settings.mode {
+                    WireframeMode::Wireframe => standalone_pipeline,
+                    _ => overlay_pipeline,
+                };
+                let rpass = encoder_or_pass.get_rpass(rpass_handle);
+                let wireframe_uniform_bg = graph_data
+                    .get_data(temps, wireframe_uniform_handle)
+                    .unwrap();
+
+                rpass.set_bind_group(0, wireframe_uniform_bg, &[]);
+                rpass.set_pipeline(pipeline);
+                rpass.set_vertex_buffer(0, edges.slice(..));
+                rpass.draw(0..*num_vertices, 0..1);
+            },
+        );
+    }
+
+    pub fn create_bind_groups<'node>(
+        &'node self,
+        graph: &mut RenderGraph<'node>,
+        wireframe_uniform_bg: DataHandle<BindGroup>,
+    ) {
+        use wgpu::*;
+        let mut builder = graph.add_node("build wireframe uniforms");
+        let output_handle = builder.add_data_output(wireframe_uniform_bg);
+        let pt_handle = builder.passthrough_ref(self);
+        builder.build(
+            move |pt, renderer, _encoder_or_pass, _temps, _ready, graph_data| {
+                let this = pt.get(pt_handle);
+
+                let camera_manager = renderer.camera_manager.read();
+                let settings = &this.settings;
+                let data = WireframeRoutineUniform {
+                    view_proj: camera_manager.view_proj().to_cols_array_2d(),
+                    color: settings.color.extend(settings.opacity).to_array(),
+                    depth_bias: settings.depth_bias,
+                    _padding: [0.0; 3],
+                };
+
+                let buffer = renderer.device.create_buffer_init(&BufferInitDescriptor {
+                    label: None,
+                    contents: bytemuck::cast_slice(&[data]),
+                    usage: BufferUsages::UNIFORM,
+                });
+
+                let bind_group = renderer.device.create_bind_group(&BindGroupDescriptor {
+                    label: Some("Wireframe BindGroup"),
+                    layout: &this.bgl,
+                    entries: &[BindGroupEntry {
+                        binding: 0,
+                        resource: buffer.as_entire_binding(),
+                    }],
+                });
+
+                graph_data.set_data(output_handle, Some(bind_group));
+            },
+        );
     }
 }